@@ -19,6 +19,10 @@ impl<const N: usize, T, C: Chunk<T>> ChunkMapHash<N, T, C> {
             _phantom: PhantomData,
         }
     }
+    /// the chunk at `location`, if any tile of it was set
+    pub fn chunk(&self, location: ChunkLocation) -> Option<&C> {
+        self.data.get(&location)
+    }
 }
 
 impl<const N: usize, T, C: Chunk<T>> Default for ChunkMapHash<N, T, C> {
@@ -95,6 +95,10 @@ pub struct ChunkLocation {
     south8: i16,
 }
 impl ChunkLocation {
+    /// the chunk that contains `pos`
+    pub fn containing(pos: Position) -> Self {
+        to_chunk_pos(pos).0
+    }
     /// this is the 0, 0 (west-north) corner of the chunk
     pub fn north_west_pos(&self) -> Position {
         Position::add_to_anchor(Vec2::new_east_south(8 * self.east8, 8 * self.south8))
@@ -172,6 +176,17 @@ impl ChunkBool {
         }
     }
 }
+impl Chunk<bool> for ChunkBool {
+    fn new() -> Self {
+        Self { data: 0 }
+    }
+    fn get(&self, index: ChunkIndex) -> bool {
+        ChunkBool::get(*self, index)
+    }
+    fn set(&mut self, index: ChunkIndex, t: bool) {
+        ChunkBool::set(self, index, t)
+    }
+}
 
 pub trait ChunkMap<T, C: Chunk<T>> {
     /// Return a mutable reference to the chunk at the given index. If it does not exist yet, it
@@ -0,0 +1,358 @@
+use core::marker::PhantomData;
+
+use async_kartoffel_generic::{Direction, Global, Position, RadarSize, Vec2};
+use heapless::FnvIndexMap;
+
+use super::{
+    Breakpoint, Map, Move, MoveCosts, Pose, PoseDistances,
+    error::OutOfMemory,
+    terrain::Terrain,
+    weighted_navigation::{distances_get, distances_set},
+};
+
+/// What [`Coverage`] requires of every reachable tile.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CoverageMode {
+    /// the bot has stood on the tile
+    Visit,
+    /// the tile was inside a radar scan
+    See,
+}
+
+/// Next destination of the coverage route, see [`Coverage::plan`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CoverageGoal {
+    pub pos: Position,
+    /// facing of the bot when it arrives at [`Self::pos`]
+    pub facing: Direction,
+    /// cost of the moves from the bot to [`Self::pos`], see [`MoveCosts`]
+    pub cost: u16,
+    /// first tile of the path that differs from the position of the bot, [`Self::pos`] if the
+    /// bot is there already
+    pub next: Position,
+}
+
+/// Plans a route that visits or sees (see [`CoverageMode`]) every tile that can be reached from
+/// the bot, where the radar scans have size `Size`.
+///
+/// The route is planned greedily: the next goal is the cheapest position to reach that still
+/// covers something. The cost is that of the motor actions over position and facing with
+/// [`MoveCosts::BOT`], so turns and backward steps are included, like in
+/// [`WeightedNavigation`](crate::WeightedNavigation). In [`CoverageMode::See`], a position covers
+/// something if an unknown tile or a walkable tile that was not seen yet lies within its scan.
+/// Since the plan only depends on the current map and the covered tiles, it adapts to new terrain
+/// by planning again after every scan.
+///
+/// The search stores the cost of every reached pose in `D`, up to `N` poses are waiting to be
+/// searched at once. `N` has to be a power of two.
+pub struct Coverage<C: Map<bool>, D: Map<PoseDistances>, Size: RadarSize, const N: usize> {
+    mode: CoverageMode,
+    costs: MoveCosts,
+    covered: C,
+    /// cost of the poses reached by the current search, plus one
+    distances: D,
+    /// poses whose neighbors were not searched yet, with their cost
+    active: FnvIndexMap<Pose, u16, N>,
+    _size: PhantomData<Size>,
+}
+
+impl<C: Map<bool> + Default, D: Map<PoseDistances> + Default, Size: RadarSize, const N: usize>
+    Coverage<C, D, Size, N>
+{
+    pub fn new(mode: CoverageMode) -> Self {
+        assert!(N >= 1);
+        Self {
+            mode,
+            costs: MoveCosts::BOT,
+            covered: Default::default(),
+            distances: Default::default(),
+            active: Default::default(),
+            _size: PhantomData,
+        }
+    }
+}
+
+impl<C: Map<bool>, D: Map<PoseDistances>, Size: RadarSize, const N: usize> Coverage<C, D, Size, N> {
+    pub fn mode(&self) -> CoverageMode {
+        self.mode
+    }
+
+    /// Notify that the bot was at `pos`, and in [`CoverageMode::See`] that it scanned there. The
+    /// map has to be updated with the scan before the next [`Self::plan`].
+    pub fn visit(&mut self, pos: Position) -> Result<(), OutOfMemory> {
+        match self.mode {
+            CoverageMode::Visit => self.covered.set(pos, true).map_err(|_| OutOfMemory),
+            CoverageMode::See => {
+                for vec in square::<Size>() {
+                    self.covered.set(pos + vec, true).map_err(|_| OutOfMemory)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_covered(&self, pos: Position) -> bool {
+        self.covered.get(pos).unwrap_or(false)
+    }
+
+    /// forget all covered tiles
+    pub fn clear(&mut self) {
+        self.covered.clear();
+    }
+
+    /// whether going to `pos` covers something
+    fn is_goal(&self, map: &impl Map<Terrain>, pos: Position) -> bool {
+        match self.mode {
+            CoverageMode::Visit => !self.is_covered(pos),
+            CoverageMode::See => square::<Size>().any(|vec| {
+                let tile = pos + vec;
+                !self.is_covered(tile)
+                    && map.get(tile).unwrap_or(Terrain::Unknown) != Terrain::Blocked
+            }),
+        }
+    }
+
+    /// Cancelable search of the next goal for a bot at `from`, over the known walkable tiles of
+    /// `map`. Returns `None` if everything reachable is covered.
+    pub async fn plan(
+        &mut self,
+        map: &impl Map<Terrain>,
+        from: Pose,
+    ) -> Result<Option<CoverageGoal>, OutOfMemory> {
+        self.plan_with_targets(map, from, &[]).await
+    }
+
+    /// Like [`Self::plan`], but the cheapest reachable position of `targets` (e.g. known flags)
+    /// is preferred over the coverage route. If the search runs out of memory before a target is
+    /// found, the coverage goal is returned.
+    pub async fn plan_with_targets(
+        &mut self,
+        map: &impl Map<Terrain>,
+        from: Pose,
+        targets: &[Position],
+    ) -> Result<Option<CoverageGoal>, OutOfMemory> {
+        // Future can be dropped at any await point, the search is restarted on the next call
+        self.distances.clear();
+        self.active.clear();
+        distances_set(&mut self.distances, from, 0)?;
+        // unwrap: we checked N >= 1
+        self.active.insert(from, 0).unwrap();
+
+        let mut coverage_goal: Option<Pose> = None;
+        while let Some((pose, cost)) = self.pop_cheapest() {
+            if targets.contains(&pose.0) {
+                return Ok(Some(self.goal(from, pose)));
+            }
+            if coverage_goal.is_none() && self.is_goal(map, pose.0) {
+                if targets.is_empty() {
+                    return Ok(Some(self.goal(from, pose)));
+                }
+                coverage_goal = Some(pose);
+            }
+
+            for step in Move::all() {
+                let after = step.apply(pose);
+                if !map.get(after.0).is_some_and(Terrain::is_known_walkable) {
+                    continue;
+                }
+                let cost_after = cost.saturating_add(step.cost(&self.costs));
+                if distances_get(&self.distances, after).is_some_and(|dist| dist <= cost_after) {
+                    continue;
+                }
+                let inserted =
+                    distances_set(&mut self.distances, after, cost_after).and_then(|()| {
+                        self.active
+                            .insert(after, cost_after)
+                            .map_err(|_| OutOfMemory)
+                    });
+                if let Err(err) = inserted {
+                    return match coverage_goal {
+                        Some(goal) => Ok(Some(self.goal(from, goal))),
+                        None => Err(err),
+                    };
+                }
+            }
+            Breakpoint::new().await;
+        }
+        Ok(coverage_goal.map(|goal| self.goal(from, goal)))
+    }
+
+    fn pop_cheapest(&mut self) -> Option<(Pose, u16)> {
+        let (&pose, &cost) = self.active.iter().min_by_key(|&(_, &cost)| cost)?;
+        self.active.remove(&pose);
+        Some((pose, cost))
+    }
+
+    /// Follow the cheapest path from `pose` back to `from`.
+    fn goal(&self, from: Pose, pose: Pose) -> CoverageGoal {
+        // unwrap: the search reached the pose
+        let cost = distances_get(&self.distances, pose).unwrap();
+        let mut next = pose.0;
+        let mut current = (pose, cost);
+        while current.1 > 0 {
+            // unwrap: every reached pose except the start has a cheaper predecessor
+            let previous = Move::all()
+                .into_iter()
+                .find_map(|step| {
+                    let before = step.revert(current.0);
+                    let dist = distances_get(&self.distances, before)?;
+                    (dist.saturating_add(step.cost(&self.costs)) == current.1)
+                        .then_some((before, dist))
+                })
+                .unwrap();
+            if previous.0.0 == from.0 && current.0.0 != from.0 {
+                next = current.0.0;
+            }
+            current = previous;
+        }
+        CoverageGoal {
+            pos: pose.0,
+            facing: pose.1,
+            cost,
+            next,
+        }
+    }
+}
+
+/// offsets of the tiles inside a scan
+fn square<Size: RadarSize>() -> impl Iterator<Item = Vec2<Global>> {
+    Size::range().flat_map(|east| {
+        Size::range().map(move |south| Vec2::new_east_south(east.into(), south.into()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::D3;
+    use embassy_futures::block_on;
+
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{ChunkBool, ChunkMapHash, ChunkTerrain, DistanceManhattan, DistanceMeasure};
+
+    type TestMap = ChunkMapHash<16, Terrain, ChunkTerrain>;
+    type TestCoverage = Coverage<
+        ChunkMapHash<16, bool, ChunkBool>,
+        ChunkMapHash<16, PoseDistances, [[PoseDistances; 8]; 8]>,
+        D3,
+        64,
+    >;
+
+    /// L-shaped corridor
+    fn corridor(pos: Position) -> bool {
+        let vec = pos.subtract_anchor();
+        (vec.south() == 0 && (0..=9).contains(&vec.east()))
+            || (vec.east() == 9 && (0..=6).contains(&vec.south()))
+    }
+
+    /// map where the tiles in `east` times `south` are walkable, surrounded by walls
+    fn room(
+        east: core::ops::RangeInclusive<i16>,
+        south: core::ops::RangeInclusive<i16>,
+    ) -> TestMap {
+        let mut map = TestMap::new();
+        for e in east.start() - 1..=east.end() + 1 {
+            for s in south.start() - 1..=south.end() + 1 {
+                let inside = east.contains(&e) && south.contains(&s);
+                map.set(pos(e, s), Terrain::from_walkable(inside)).unwrap();
+            }
+        }
+        map
+    }
+
+    /// walk the goals until everything is covered, scanning at every goal, returns the cost and
+    /// the number of goals
+    fn drive(coverage: &mut TestCoverage, map: &mut TestMap, mut bot: Pose) -> (u16, u16) {
+        let (mut cost, mut goals) = (0, 0);
+        loop {
+            for vec in square::<D3>() {
+                let tile = bot.0 + vec;
+                if map.get(tile).unwrap_or(Terrain::Unknown) == Terrain::Unknown {
+                    map.set(tile, Terrain::from_walkable(corridor(tile)))
+                        .unwrap();
+                }
+            }
+            coverage.visit(bot.0).unwrap();
+            let Some(goal) = block_on(coverage.plan(map, bot)).unwrap() else {
+                return (cost, goals);
+            };
+            assert!(goal.cost > 0);
+            assert_eq!(DistanceManhattan::measure(goal.next - bot.0), 1);
+            (bot, cost, goals) = ((goal.pos, goal.facing), cost + goal.cost, goals + 1);
+        }
+    }
+
+    #[test]
+    fn see_unknown_corridor() {
+        let mut coverage = TestCoverage::new(CoverageMode::See);
+        let mut map = TestMap::new();
+        let (cost, goals) = drive(&mut coverage, &mut map, (pos(0, 0), Direction::East));
+        for east in -1..=10 {
+            for south in -1..=7 {
+                let tile = pos(east, south);
+                if corridor(tile) {
+                    assert!(coverage.is_covered(tile));
+                    assert_eq!(map.get(tile), Some(Terrain::Walkable));
+                }
+            }
+        }
+        // every scan reveals a new tile at the end of the corridor, a single turn at the corner
+        assert_eq!((cost, goals), (15 * 4 + 5, 15));
+    }
+
+    #[test]
+    fn visit_known_room() {
+        let mut coverage = TestCoverage::new(CoverageMode::Visit);
+        let map = room(0..=2, 0..=1);
+        let mut bot = (pos(0, 0), Direction::East);
+        let mut cost = 0;
+        coverage.visit(bot.0).unwrap();
+        while let Some(goal) = block_on(coverage.plan(&map, bot)).unwrap() {
+            assert_eq!(goal.next, goal.pos);
+            (bot, cost) = ((goal.pos, goal.facing), cost + goal.cost);
+            coverage.visit(bot.0).unwrap();
+        }
+        // along the northern row, turn twice into the southern one and back
+        assert_eq!(cost, 2 * 4 + 2 * (5 + 4) + 4);
+        assert!((0..=2).all(|east| coverage.is_covered(pos(east, 1))));
+    }
+
+    #[test]
+    fn turns_are_expensive() {
+        let mut coverage = TestCoverage::new(CoverageMode::Visit);
+        let map = room(0..=2, -1..=0);
+        for tile in [pos(0, 0), pos(1, 0), pos(1, -1), pos(2, -1)] {
+            coverage.visit(tile).unwrap();
+        }
+        // two steps ahead are cheaper than a turn and a single step to the north
+        let goal = block_on(coverage.plan(&map, (pos(0, 0), Direction::East)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (goal.pos, goal.cost, goal.next),
+            (pos(2, 0), 2 * 4, pos(1, 0))
+        );
+    }
+
+    #[test]
+    fn targets_first() {
+        let mut coverage = TestCoverage::new(CoverageMode::Visit);
+        let map = room(0..=5, 0..=0);
+        let from = (pos(0, 0), Direction::East);
+        coverage.visit(from.0).unwrap();
+        let plan = |coverage: &mut TestCoverage, targets: &[Position]| {
+            block_on(coverage.plan_with_targets(&map, from, targets))
+                .unwrap()
+                .map(|goal| goal.pos)
+        };
+        assert_eq!(plan(&mut coverage, &[]), Some(pos(1, 0)));
+        assert_eq!(plan(&mut coverage, &[pos(4, 0)]), Some(pos(4, 0)));
+        // unreachable targets are ignored
+        assert_eq!(plan(&mut coverage, &[pos(8, 0)]), Some(pos(1, 0)));
+        for east in 1..=5 {
+            coverage.visit(pos(east, 0)).unwrap();
+        }
+        assert_eq!(plan(&mut coverage, &[]), None);
+        assert_eq!(plan(&mut coverage, &[pos(3, 0)]), Some(pos(3, 0)));
+    }
+}
@@ -0,0 +1,160 @@
+use core::fmt::Write;
+
+use async_kartoffel_generic::{
+    Bot, BotBackend, D5, Direction, ItemKind, Position, RadarScanTrait, Rotation, Tile, Vec2,
+};
+use heapless::Vec;
+
+use super::{
+    Breakpoint, ChunkBool, ChunkMapHash, ChunkTerrain, Coverage, CoverageMode, ItemMemory, Map,
+    Move, Pose, PoseDistances, Terrain, update_chunk_map,
+};
+
+pub type CoverageBotMap = ChunkMapHash<128, Terrain, ChunkTerrain>;
+pub type CoverageBotPlanner = Coverage<
+    ChunkMapHash<128, bool, ChunkBool>,
+    ChunkMapHash<32, PoseDistances, [[PoseDistances; 8]; 8]>,
+    D5,
+    64,
+>;
+
+/// Drives a bot along the route of [`Coverage`] in [`CoverageMode::See`], until it has seen every
+/// reachable tile, and picks the flags it finds on the way. This is the strategy of
+/// `challenge-roomba`, written against the generic [`Bot`] so that it also runs on the host.
+///
+/// Positions are relative to the start of the bot, it keeps track of its moves without checking
+/// them, so other bots must not block it.
+pub struct CoverageBot {
+    map: CoverageBotMap,
+    coverage: CoverageBotPlanner,
+    items: ItemMemory<8>,
+    pos: Position,
+    facing: Direction,
+}
+
+impl CoverageBot {
+    /// `facing` is the direction of the bot at the start, e.g. from its compass
+    pub fn new(facing: Direction) -> Self {
+        let mut map = CoverageBotMap::default();
+        // unwrap: the map is empty
+        map.set(Position::default(), Terrain::Walkable).unwrap();
+        Self {
+            map,
+            coverage: CoverageBotPlanner::new(CoverageMode::See),
+            items: ItemMemory::new(),
+            pos: Position::default(),
+            facing,
+        }
+    }
+
+    pub fn map(&self) -> &CoverageBotMap {
+        &self.map
+    }
+
+    pub fn coverage(&self) -> &CoverageBotPlanner {
+        &self.coverage
+    }
+
+    /// position and facing of the bot
+    pub fn pose(&self) -> (Position, Direction) {
+        (self.pos, self.facing)
+    }
+
+    /// Scan, plan and move until everything reachable was seen and no reachable flag is left.
+    /// Errors are written to `log` and don't end the run. Calling it again afterwards scans once
+    /// more, e.g. to look for new flags.
+    pub async fn run<B: BotBackend>(&mut self, bot: &mut Bot<B>, mut log: impl Write) {
+        loop {
+            let scan = bot.radar.scan::<D5>().await;
+            let scan_pos = self.pos;
+            if let Err(err) = update_chunk_map(&mut self.map, &scan, scan_pos, self.facing).await {
+                _ = writeln!(log, "error in map {err:?}");
+            }
+            Breakpoint::new().await;
+            if let Err(err) = self.coverage.visit(scan_pos) {
+                _ = writeln!(log, "error in coverage {err:?}");
+            }
+            if let Err(err) = self.items.observe(&scan, scan_pos, self.facing) {
+                _ = writeln!(log, "error in items {err:?}");
+            }
+
+            if scan.at(Vec2::new_front(1)) == Some(Tile::Flag) {
+                bot.arm.pick().await;
+                self.items
+                    .remove(scan_pos + Vec2::new_front(1).global(self.facing));
+            }
+
+            // flags are priority destinations, otherwise continue the coverage route
+            let flags: Vec<Position, 8> = self
+                .items
+                .iter()
+                .filter(|&(flag_pos, kind)| {
+                    kind == ItemKind::Flag
+                        && self
+                            .map
+                            .get(flag_pos)
+                            .is_some_and(Terrain::is_known_walkable)
+                })
+                .map(|(flag_pos, _kind)| flag_pos)
+                .collect();
+            let goal = match self
+                .coverage
+                .plan_with_targets(&self.map, (scan_pos, self.facing), &flags)
+                .await
+            {
+                Ok(Some(goal)) => goal,
+                Ok(None) => return,
+                Err(err) => {
+                    _ = writeln!(log, "error in coverage {err:?}");
+                    continue;
+                }
+            };
+            Breakpoint::new().await;
+
+            // walk to the next tile of the path, or face the flag there, then scan again
+            let face = flags.contains(&goal.next);
+            for step in moves_to((self.pos, self.facing), goal.next, face) {
+                if matches!(step, Move::Forward | Move::Backward) {
+                    let after = step.apply((self.pos, self.facing)).0;
+                    let vec = (after - scan_pos).local(self.facing);
+                    if !scan.at(vec).is_some_and(|tile| tile.is_empty()) {
+                        // blocked, e.g. by another bot
+                        break;
+                    }
+                }
+                match step {
+                    Move::Forward => bot.motor.step_fw().await,
+                    Move::Backward => bot.motor.step_bw().await,
+                    Move::TurnLeft => bot.motor.turn_left().await,
+                    Move::TurnRight => bot.motor.turn_right().await,
+                }
+                (self.pos, self.facing) = step.apply((self.pos, self.facing));
+            }
+        }
+    }
+}
+
+/// The cheapest moves from `pose` to the neighboring tile `next`, with the costs of
+/// [`MoveCosts::BOT`](crate::MoveCosts::BOT). If `face` is set, the bot only turns towards it,
+/// e.g. to pick a flag there.
+fn moves_to((pos, facing): Pose, next: Position, face: bool) -> Vec<Move, 2> {
+    let direction = pos
+        .neighbors()
+        .into_iter()
+        .find_map(|(tile, direction)| (tile == next).then_some(direction));
+    let moves: &[Move] = match direction {
+        None => &[],
+        Some(direction) if direction == facing && face => &[],
+        Some(direction) if direction == facing => &[Move::Forward],
+        Some(direction) if direction == facing + Rotation::Inverse && face => {
+            &[Move::TurnRight, Move::TurnRight]
+        }
+        Some(direction) if direction == facing + Rotation::Inverse => &[Move::Backward],
+        Some(direction) if direction == facing + Rotation::Left && face => &[Move::TurnLeft],
+        Some(direction) if direction == facing + Rotation::Left => &[Move::TurnLeft, Move::Forward],
+        Some(_) if face => &[Move::TurnRight],
+        Some(_) => &[Move::TurnRight, Move::Forward],
+    };
+    // unwrap: at most two moves
+    Vec::from_slice(moves).unwrap()
+}
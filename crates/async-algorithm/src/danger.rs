@@ -0,0 +1,199 @@
+use core::num::NonZeroU64;
+
+use async_kartoffel_generic::{
+    ClockBackend, Direction, Duration, Instant, Position, RadarScanTrait, RadarSize, Tile,
+};
+use heapless::FnvIndexMap;
+
+use super::{DistanceManhattan, DistanceMeasure};
+
+/// Identifies a seen bot: by its id if the radar reported one, otherwise by its position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum BotKey {
+    Id(NonZeroU64),
+    Anonymous(Position),
+}
+
+#[derive(Debug)]
+struct Sighting<C: ClockBackend> {
+    pos: Position,
+    seen: Instant<C>,
+}
+
+/// Time-decaying cost of the tiles around recently seen enemy bots, to be used as the soft
+/// `tile_cost` of [`WeightedNavigation::run_with_cost`](crate::WeightedNavigation::run_with_cost).
+///
+/// A bot costs `cost` on its own tile, falling off linearly to zero at Manhattan distance
+/// `radius + 1`, and fading out linearly until it was last seen `lifetime` ago. The costs of
+/// several bots add up. Every bot is only remembered at its latest position, up to `N` bots.
+pub struct DangerMap<C: ClockBackend, const N: usize> {
+    sightings: FnvIndexMap<BotKey, Sighting<C>, N>,
+    cost: u16,
+    radius: u16,
+    lifetime: Duration<C>,
+}
+
+impl<C: ClockBackend, const N: usize> DangerMap<C, N> {
+    pub fn new(cost: u16, radius: u16, lifetime: Duration<C>) -> Self {
+        assert!(lifetime.as_ticks() > 0);
+        Self {
+            sightings: FnvIndexMap::new(),
+            cost,
+            radius,
+            lifetime,
+        }
+    }
+
+    /// Record the bots of a radar scan taken at `pos` facing `direction`. Bots that were seen
+    /// inside the scanned area before but are not there anymore are forgotten.
+    pub fn observe<Size: RadarSize>(
+        &mut self,
+        scan: &impl RadarScanTrait<Size>,
+        pos: Position,
+        direction: Direction,
+        now: Instant<C>,
+    ) {
+        self.sightings.retain(|_key, sighting| {
+            let vec = (sighting.pos - pos).local(direction);
+            !scan.contains(vec) || scan.at(vec) == Some(Tile::Bot)
+        });
+        for vec in scan.iter_tile(Tile::Bot) {
+            let bot_pos = pos + vec.global(direction);
+            let key = match scan.bot_at(vec) {
+                Some(id) => BotKey::Id(id),
+                None => BotKey::Anonymous(bot_pos),
+            };
+            let sighting = Sighting {
+                pos: bot_pos,
+                seen: now,
+            };
+            if let Err((key, sighting)) = self.sightings.insert(key, sighting) {
+                self.forget_oldest();
+                // can't fail, we just removed an entry
+                _ = self.sightings.insert(key, sighting);
+            }
+        }
+        self.forget_expired(now);
+    }
+
+    /// Remove the bots that were last seen at least `lifetime` ago.
+    pub fn forget_expired(&mut self, now: Instant<C>) {
+        let lifetime = self.lifetime;
+        self.sightings
+            .retain(|_key, sighting| (now - sighting.seen).is_none_or(|age| age < lifetime));
+    }
+
+    fn forget_oldest(&mut self) {
+        let oldest = self
+            .sightings
+            .iter()
+            .min_by(|(_, a), (_, b)| a.seen.cmp_wrapping(&b.seen))
+            .map(|(&key, _sighting)| key);
+        if let Some(key) = oldest {
+            self.sightings.remove(&key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.sightings.clear();
+    }
+
+    /// number of remembered bots
+    pub fn len(&self) -> usize {
+        self.sightings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sightings.is_empty()
+    }
+
+    /// latest positions of the remembered bots and when they were seen there
+    pub fn iter(&self) -> impl Iterator<Item = (Position, Instant<C>)> + use<'_, C, N> {
+        self.sightings
+            .values()
+            .map(|sighting| (sighting.pos, sighting.seen))
+    }
+
+    /// sum of the decayed costs of all remembered bots at `pos`
+    pub fn cost_at(&self, pos: Position, now: Instant<C>) -> u16 {
+        let lifetime = self.lifetime.as_ticks();
+        let range = u32::from(self.radius) + 1;
+        let total: u32 = self
+            .sightings
+            .values()
+            .map(|sighting| {
+                let dist = u32::from(DistanceManhattan::measure(pos - sighting.pos));
+                let age = (now - sighting.seen).map_or(0, |age| age.as_ticks());
+                let (Some(near), Some(fresh)) =
+                    (range.checked_sub(dist), lifetime.checked_sub(age))
+                else {
+                    return 0;
+                };
+                let spread = u32::from(self.cost) * near / range;
+                (u64::from(spread) * u64::from(fresh) / u64::from(lifetime)) as u32
+            })
+            .sum();
+        total.min(u16::MAX.into()) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::Vec2;
+
+    use super::*;
+    use crate::test_util::{TestClock, TestScan, at, pos};
+
+    #[test]
+    fn decay_and_spread() {
+        let mut danger = DangerMap::<TestClock, 4>::new(40, 1, Duration::from_ticks(100));
+        let scan = TestScan::default().with_bot(Vec2::new_front(1), 7);
+        danger.observe(&scan, pos(0, 0), Direction::East, at(0));
+        assert_eq!(danger.len(), 1);
+
+        let bot = pos(1, 0);
+        assert_eq!(danger.cost_at(bot, at(0)), 40);
+        assert_eq!(danger.cost_at(pos(1, 1), at(0)), 20);
+        assert_eq!(danger.cost_at(pos(2, 1), at(0)), 0);
+        assert_eq!(danger.cost_at(bot, at(50)), 20);
+        assert_eq!(danger.cost_at(bot, at(100)), 0);
+
+        danger.forget_expired(at(99));
+        assert_eq!(danger.len(), 1);
+        danger.forget_expired(at(100));
+        assert!(danger.is_empty());
+    }
+
+    #[test]
+    fn bots_move_and_leave() {
+        let mut danger = DangerMap::<TestClock, 2>::new(10, 0, Duration::from_ticks(1000));
+        let facing = Direction::North;
+
+        // the same bot seen again at a different position
+        let scan = TestScan::default().with_bot(Vec2::new_front(1), 7);
+        danger.observe(&scan, pos(0, 0), facing, at(0));
+        danger.observe(&scan, pos(5, 0), facing, at(10));
+        assert_eq!(
+            danger.iter().collect::<heapless::Vec<_, 2>>(),
+            [(pos(5, -1), at(10))]
+        );
+
+        // a bot without id, then the area is scanned again without it
+        let scan = TestScan::default().with_bot(Vec2::new_right(1), 0);
+        danger.observe(&scan, pos(0, 0), facing, at(20));
+        assert_eq!(danger.cost_at(pos(1, 0), at(20)), 10);
+        danger.observe(&TestScan::default(), pos(1, 1), facing, at(30));
+        assert_eq!(danger.cost_at(pos(1, 0), at(30)), 0);
+        assert_eq!(danger.len(), 1);
+
+        // out of memory: the oldest bot is replaced
+        let scan = TestScan::default()
+            .with_bot(Vec2::new_front(1), 8)
+            .with_bot(Vec2::new_back(1), 9);
+        danger.observe(&scan, pos(20, 0), facing, at(40));
+        assert_eq!(danger.len(), 2);
+        assert_eq!(danger.cost_at(pos(5, -1), at(40)), 0);
+        assert_eq!(danger.cost_at(pos(20, -1), at(40)), 10);
+        assert_eq!(danger.cost_at(pos(20, 1), at(40)), 10);
+    }
+}
@@ -0,0 +1,231 @@
+use core::marker::PhantomData;
+
+use async_kartoffel_generic::{Direction, Position, RadarSize, Vec2};
+use heapless::{FnvIndexSet, Vec};
+
+use super::{
+    Breakpoint, DistanceManhattan, DistanceMax, DistanceMeasure, Exploration, Map,
+    distance_walk_with_rotation, error::OutOfMemory, terrain::Terrain,
+};
+
+/// Next destination of the exploration, see [`Frontier`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrontierGoal {
+    /// border position to navigate to
+    pub pos: Position,
+    /// unknown tiles a radar scan at [`Self::pos`] would reveal
+    pub gain: u16,
+    /// [`distance_walk_with_rotation`] from the bot to [`Self::pos`]
+    pub cost: u16,
+    /// number of border positions in the cluster of [`Self::pos`]
+    pub size: u16,
+}
+
+impl FrontierGoal {
+    fn score(&self, gain_weight: u16) -> i32 {
+        i32::from(self.gain) * i32::from(gain_weight) - i32::from(self.cost)
+    }
+}
+
+/// Chooses where to explore next, among the positions of [`Exploration::border`].
+///
+/// Border positions are grouped into clusters of neighbors (including diagonal ones), that are
+/// not further apart than a radar scan of size `Size` reaches. Every cluster is represented by the
+/// member closest to its center. The representative is scored by the unknown tiles a scan there
+/// would reveal, times `gain_weight`, minus the walking cost from the bot. The best one is the
+/// goal for [`Navigation`](crate::Navigation).
+///
+/// Up to `N` border positions are considered.
+pub struct Frontier<Size: RadarSize, const N: usize> {
+    /// border positions, that are not part of a cluster yet
+    border: FnvIndexSet<Position, N>,
+    /// members of the current cluster
+    cluster: Vec<Position, N>,
+    gain_weight: u16,
+    _size: PhantomData<Size>,
+}
+
+impl<Size: RadarSize, const N: usize> Default for Frontier<Size, N> {
+    /// one revealed tile is worth one step, see [`DistanceBotWalk`](crate::DistanceBotWalk)
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl<Size: RadarSize, const N: usize> Frontier<Size, N> {
+    pub fn new(gain_weight: u16) -> Self {
+        Self {
+            border: Default::default(),
+            cluster: Default::default(),
+            gain_weight,
+            _size: PhantomData,
+        }
+    }
+
+    /// Cancelable computation of the next goal for a bot at `pos` facing `facing`. Returns `None`
+    /// if the exploration has no border, e.g. because it is completed.
+    pub async fn plan<T: Map<Terrain>, const M: usize>(
+        &mut self,
+        exploration: &Exploration<M, T>,
+        map: &T,
+        pos: Position,
+        facing: Direction,
+    ) -> Result<Option<FrontierGoal>, OutOfMemory> {
+        // Future can be dropped at any await point, the buffers are cleared on the next call
+        self.border.clear();
+        let Some(border) = exploration.border(map) else {
+            return Ok(None);
+        };
+        for (i, border_pos) in border.enumerate() {
+            self.border.insert(border_pos).map_err(|_| OutOfMemory)?;
+            if i % 8 == 7 {
+                Breakpoint::new().await;
+            }
+        }
+
+        let mut best: Option<FrontierGoal> = None;
+        while let Some(&seed) = self.border.first() {
+            self.collect_cluster(seed);
+            Breakpoint::new().await;
+
+            let representative = self.representative();
+            let goal = FrontierGoal {
+                pos: representative,
+                gain: gain(map, representative, Size::R),
+                cost: distance_walk_with_rotation(representative - pos, facing),
+                size: self.cluster.len() as u16,
+            };
+            let key = |goal: &FrontierGoal| (goal.score(self.gain_weight), -i32::from(goal.cost));
+            if best.is_none_or(|best| key(&goal) > key(&best)) {
+                best = Some(goal);
+            }
+            Breakpoint::new().await;
+        }
+        Ok(best)
+    }
+
+    /// moves the connected border positions around `seed` into [`Self::cluster`]
+    fn collect_cluster(&mut self, seed: Position) {
+        self.cluster.clear();
+        self.border.remove(&seed);
+        // can't fail, the cluster is never larger than the border
+        _ = self.cluster.push(seed);
+        let mut i = 0;
+        while let Some(&member) = self.cluster.get(i) {
+            i += 1;
+            for east in -1..=1 {
+                for south in -1..=1 {
+                    let neighbor = member + Vec2::new_east_south(east, south);
+                    if DistanceMax::measure(neighbor - seed) <= Size::R.into()
+                        && self.border.remove(&neighbor)
+                    {
+                        _ = self.cluster.push(neighbor);
+                    }
+                }
+            }
+        }
+    }
+
+    /// member of [`Self::cluster`] closest to its mean position
+    fn representative(&self) -> Position {
+        let (sum_east, sum_south) = self.cluster.iter().fold((0, 0), |(east, south), pos| {
+            let vec = pos.subtract_anchor();
+            (east + i32::from(vec.east()), south + i32::from(vec.south()))
+        });
+        let n = self.cluster.len() as i32;
+        let mean = Position::add_to_anchor(Vec2::new_east_south(
+            (sum_east / n) as i16,
+            (sum_south / n) as i16,
+        ));
+        // unwrap: clusters are never empty
+        *self
+            .cluster
+            .iter()
+            .min_by_key(|&&member| DistanceManhattan::measure(member - mean))
+            .unwrap()
+    }
+}
+
+/// number of unknown tiles within `r` of `pos`
+fn gain(map: &impl Map<Terrain>, pos: Position, r: u8) -> u16 {
+    let r = i16::from(r);
+    let mut gain = 0;
+    for east in -r..=r {
+        for south in -r..=r {
+            let tile = map.get(pos + Vec2::new_east_south(east, south));
+            if tile.unwrap_or(Terrain::Unknown) == Terrain::Unknown {
+                gain += 1;
+            }
+        }
+    }
+    gain
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::D5;
+    use embassy_futures::block_on;
+
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{ChunkMapHash, ChunkTerrain};
+
+    type TestMap = ChunkMapHash<16, Terrain, ChunkTerrain>;
+
+    fn fill(map: &mut TestMap, east: [i16; 2], south: [i16; 2], terrain: Terrain) {
+        for e in east[0]..=east[1] {
+            for s in south[0]..=south[1] {
+                map.set(pos(e, s), terrain).unwrap();
+            }
+        }
+    }
+
+    /// 5x5 room with an exit to the west and a wide exit to the east, surrounded by unknown
+    fn room() -> TestMap {
+        let mut map = TestMap::new();
+        fill(&mut map, [-3, 3], [-3, 3], Terrain::Blocked);
+        fill(&mut map, [-3, 3], [-2, 2], Terrain::Walkable);
+        fill(&mut map, [-3, -3], [-2, -1], Terrain::Blocked);
+        fill(&mut map, [-3, -3], [1, 2], Terrain::Blocked);
+        fill(&mut map, [3, 3], [-2, -2], Terrain::Blocked);
+        fill(&mut map, [3, 3], [2, 2], Terrain::Blocked);
+        map
+    }
+
+    fn plan(map: &mut TestMap, facing: Direction) -> FrontierGoal {
+        let mut exploration = Exploration::<64, TestMap>::new();
+        exploration.initialize(map, pos(0, 0));
+        block_on(exploration.run(map));
+        let mut frontier = Frontier::<D5, 64>::default();
+        block_on(frontier.plan(&exploration, map, pos(0, 0), facing))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn closest_cluster() {
+        let goal = plan(&mut room(), Direction::East);
+        assert_eq!(
+            goal,
+            FrontierGoal {
+                pos: pos(3, 0),
+                gain: 10,
+                cost: 12,
+                size: 3,
+            }
+        );
+        // turning around is more expensive than the walk
+        let goal = plan(&mut room(), Direction::West);
+        assert_eq!((goal.pos, goal.cost, goal.size), (pos(-3, 0), 12, 1));
+    }
+
+    #[test]
+    fn information_gain() {
+        // behind the east exit, only a single tile is unknown
+        let mut map = room();
+        fill(&mut map, [4, 5], [-2, 2], Terrain::Blocked);
+        map.set(pos(4, 0), Terrain::Unknown).unwrap();
+        let goal = plan(&mut map, Direction::East);
+        assert_eq!((goal.pos, goal.gain, goal.cost), (pos(-3, 0), 10, 22));
+    }
+}
@@ -0,0 +1,409 @@
+use core::num::NonZeroU16;
+
+use async_kartoffel_generic::Position;
+use heapless::FnvIndexMap;
+
+use super::{
+    DistanceManhattan, DistanceMeasure, Map,
+    breakpoint::Breakpoint,
+    error::{NoDestination, OutOfMemory},
+    navigation::{DirectionCombination, HasTask, NavigationTask, State},
+};
+
+const INFINITE: u16 = u16::MAX;
+
+/// Distance estimates of a position for [`IncrementalNavigation`], plus one to take advantage of
+/// niche optimizations. None is infinite.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Estimate {
+    /// distance to the destination, as of the last expansion of the position
+    g: Option<NonZeroU16>,
+    /// distance to the destination, looking ahead one step from the neighbors
+    rhs: Option<NonZeroU16>,
+}
+
+fn from_option(dist: Option<NonZeroU16>) -> u16 {
+    dist.map_or(INFINITE, |dist| u16::from(dist) - 1)
+}
+fn to_option(dist: u16) -> Option<NonZeroU16> {
+    match dist {
+        INFINITE => None,
+        dist => NonZeroU16::new(dist + 1),
+    }
+}
+
+/// handles Option<NonZeroU16> and addition of 1, returns (g, rhs)
+fn estimates_get(estimates: &impl Map<Estimate>, pos: Position) -> (u16, u16) {
+    let estimate = estimates.get(pos).unwrap_or_default();
+    (from_option(estimate.g), from_option(estimate.rhs))
+}
+fn estimates_set(
+    estimates: &mut impl Map<Estimate>,
+    pos: Position,
+    (g, rhs): (u16, u16),
+) -> Result<(), OutOfMemory> {
+    let estimate = Estimate {
+        g: to_option(g),
+        rhs: to_option(rhs),
+    };
+    // don't allocate memory for unreachable positions
+    if estimates.get(pos).unwrap_or_default() == estimate {
+        return Ok(());
+    }
+    estimates.set(pos, estimate).map_err(|_| OutOfMemory)
+}
+
+/// Priority of an inconsistent position, the smallest is expanded first.
+type Key = (u16, u16);
+
+#[derive(Debug)]
+struct Progress<const N: usize> {
+    /// Inconsistent positions, the estimates differ. Searched linearly for the minimal key.
+    open: FnvIndexMap<Position, Key, N>,
+    /// Sum of the heuristic between the starts, added to new keys instead of updating the open
+    /// positions when the start moves.
+    key_modifier: u16,
+    task: NavigationTask,
+}
+
+enum NavigationResult {
+    Impossible,
+    Success,
+}
+
+impl<const N: usize> HasTask for Progress<N> {
+    type Task = NavigationTask;
+    fn task(&self) -> NavigationTask {
+        self.task
+    }
+}
+
+impl<const N: usize> Progress<N> {
+    fn key(&self, pos: Position, (g, rhs): (u16, u16)) -> Key {
+        let dist = g.min(rhs);
+        if dist == INFINITE {
+            return (INFINITE, INFINITE);
+        }
+        let cost = dist
+            .saturating_add(DistanceManhattan::measure(pos - self.task.from))
+            .saturating_add(self.key_modifier);
+        (cost, dist)
+    }
+
+    fn change_start(&mut self, new_start: Position) {
+        self.key_modifier = self
+            .key_modifier
+            .saturating_add(DistanceManhattan::measure(new_start - self.task.from));
+        self.task.from = new_start;
+    }
+
+    /// Recompute the lookahead of `pos` from its neighbors and queue it if it became
+    /// inconsistent.
+    fn update_position(
+        &mut self,
+        estimates: &mut impl Map<Estimate>,
+        pos: Position,
+        can_go: &impl Fn(Position) -> bool,
+    ) -> Result<(), OutOfMemory> {
+        let (g, mut rhs) = estimates_get(estimates, pos);
+        if pos != self.task.to {
+            rhs = INFINITE;
+            // the bot is at the start, even if it is not known to be walkable
+            if pos == self.task.from || can_go(pos) {
+                for (neighbor, _) in pos.neighbors() {
+                    if can_go(neighbor) {
+                        let (g_neighbor, _) = estimates_get(estimates, neighbor);
+                        rhs = rhs.min(g_neighbor.saturating_add(1));
+                    }
+                }
+            }
+            estimates_set(estimates, pos, (g, rhs))?;
+        }
+        if g != rhs {
+            self.open
+                .insert(pos, self.key(pos, (g, rhs)))
+                .map_err(|_| OutOfMemory)?;
+        } else {
+            self.open.remove(&pos);
+        }
+        Ok(())
+    }
+
+    fn update_neighbors(
+        &mut self,
+        estimates: &mut impl Map<Estimate>,
+        pos: Position,
+        can_go: &impl Fn(Position) -> bool,
+    ) -> Result<(), OutOfMemory> {
+        for (neighbor, _) in pos.neighbors() {
+            self.update_position(estimates, neighbor, can_go)?;
+        }
+        Ok(())
+    }
+
+    async fn run(
+        &mut self,
+        estimates: &mut impl Map<Estimate>,
+        can_go: impl Fn(Position) -> bool,
+    ) -> Result<NavigationResult, OutOfMemory> {
+        if !can_go(self.task.to) {
+            return Ok(NavigationResult::Impossible);
+        }
+        loop {
+            let start = estimates_get(estimates, self.task.from);
+            let key_start = self.key(self.task.from, start);
+            let Some((&pos, &key_old)) = self.open.iter().min_by_key(|&(_, key)| key) else {
+                break;
+            };
+            if key_old >= key_start && start.0 == start.1 {
+                break;
+            }
+
+            let (g, rhs) = estimates_get(estimates, pos);
+            let key_new = self.key(pos, (g, rhs));
+            if key_old < key_new {
+                // the start moved since it was queued
+                self.open.insert(pos, key_new).map_err(|_| OutOfMemory)?;
+            } else if g > rhs {
+                // shorter path found
+                estimates_set(estimates, pos, (rhs, rhs))?;
+                self.open.remove(&pos);
+                self.update_neighbors(estimates, pos, &can_go)?;
+            } else {
+                // path got longer, recompute pos and everything that depends on it
+                estimates_set(estimates, pos, (INFINITE, rhs))?;
+                self.update_position(estimates, pos, &can_go)?;
+                self.update_neighbors(estimates, pos, &can_go)?;
+            }
+            // future is cancellable here
+            Breakpoint::new().await;
+        }
+
+        match estimates_get(estimates, self.task.from) {
+            (INFINITE, _) => Ok(NavigationResult::Impossible),
+            _ => Ok(NavigationResult::Success),
+        }
+    }
+}
+
+/// A interruptable navigation computation to a fixed destination, that is repaired instead of
+/// recomputed when the terrain changes (D* Lite).
+///
+/// Every tile whose result of `can_go` changes, e.g. from
+/// [`update_chunk_map_reporting`](crate::update_chunk_map_reporting) or because another bot stands
+/// there, has to be reported with [`IncrementalNavigation::update_tile`]. The next
+/// [`IncrementalNavigation::run`] only revisits the positions affected by the changes.
+pub struct IncrementalNavigation<T: Map<Estimate>, const N: usize> {
+    estimates: T,
+    state: State<Progress<N>>,
+}
+
+impl<T: Map<Estimate> + Default, const N: usize> Default for IncrementalNavigation<T, N> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T: Map<Estimate>, const N: usize> IncrementalNavigation<T, N> {
+    pub fn new(estimates: T) -> Self {
+        assert!(N >= 1);
+        Self {
+            estimates,
+            state: State::Ready,
+        }
+    }
+
+    pub fn initialize(&mut self, from: Position, to: Position) {
+        // Initialize open as default to prevent allocating it on the stack.
+        self.state = State::Running(Progress {
+            open: Default::default(),
+            key_modifier: 0,
+            task: NavigationTask { from, to },
+        });
+        let State::Running(ref mut progress) = self.state else {
+            panic!()
+        };
+        self.estimates.clear();
+        estimates_set(&mut self.estimates, to, (INFINITE, 0))
+            .expect("map should always allow the initial entry to succeed");
+        let key = progress.key(to, (INFINITE, 0));
+        // unwrap: we checked N >= 1
+        progress.open.insert(to, key).unwrap();
+    }
+
+    pub fn get_state(&self) -> State<NavigationTask> {
+        self.state.strip_data()
+    }
+
+    fn progress(&mut self) -> Result<&mut Progress<N>, NoDestination> {
+        match &mut self.state {
+            State::Running(progress) | State::Success(progress) | State::Impossible(progress) => {
+                Ok(progress)
+            }
+            _ => Err(NoDestination),
+        }
+    }
+
+    /// The bot moved, the estimates stay valid.
+    pub fn update_start(&mut self, new_start: Position) -> Result<(), NoDestination> {
+        let progress = self.progress()?;
+        if progress.task.from != new_start {
+            progress.change_start(new_start);
+            self.state.running();
+        }
+        Ok(())
+    }
+
+    /// `can_go` changed at `pos`, call [`IncrementalNavigation::run`] to repair the distances.
+    pub fn update_tile(
+        &mut self,
+        pos: Position,
+        can_go: impl Fn(Position) -> bool,
+    ) -> Result<(), NoDestination> {
+        let (State::Running(progress) | State::Success(progress) | State::Impossible(progress)) =
+            &mut self.state
+        else {
+            return Err(NoDestination);
+        };
+        let result = progress
+            .update_position(&mut self.estimates, pos, &can_go)
+            .and_then(|()| progress.update_neighbors(&mut self.estimates, pos, &can_go));
+        match result {
+            Ok(()) => self.state.running(),
+            Err(err) => self.state.error(err),
+        }
+        Ok(())
+    }
+
+    /// number of queued positions, for debugging purposes
+    pub fn n_active(&self) -> Option<usize> {
+        match &self.state {
+            State::Running(progress) | State::Success(progress) | State::Impossible(progress) => {
+                Some(progress.open.len())
+            }
+            _ => None,
+        }
+    }
+
+    pub async fn run(&mut self, can_go: impl Fn(Position) -> bool) {
+        if let State::Running(progress) = &mut self.state {
+            match progress.run(&mut self.estimates, can_go).await {
+                Ok(NavigationResult::Impossible) => self.state.impossible(),
+                Ok(NavigationResult::Success) => self.state.success(),
+                Err(err) => self.state.error(err),
+            }
+        }
+    }
+
+    pub fn next_step(&self, pos: Position) -> DirectionCombination {
+        let mut ret = DirectionCombination::default();
+        if let Some(dist_at) = self.get_dist_at(pos) {
+            for (neighbor, dir) in pos.neighbors() {
+                if self
+                    .get_dist_at(neighbor)
+                    .is_some_and(|dist_neighbor| dist_neighbor + 1 == dist_at)
+                {
+                    ret.set(dir, true);
+                }
+            }
+        }
+        ret
+    }
+
+    /// distance to the destination, if it is known and up to date
+    pub fn get_dist_at(&self, pos: Position) -> Option<u16> {
+        match estimates_get(&self.estimates, pos) {
+            (g, rhs) if g == rhs && g != INFINITE => Some(g),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::Vec2;
+    use embassy_futures::block_on;
+    use heapless::Vec;
+
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{ChunkMapHash, Navigation, NavigationState};
+
+    type TestNav = IncrementalNavigation<ChunkMapHash<16, Estimate, [[Estimate; 8]; 8]>, 64>;
+
+    /// 8 by 8 room without the walls
+    fn room(walls: &[Position]) -> impl Fn(Position) -> bool {
+        move |pos| {
+            let vec = pos.subtract_anchor();
+            (0..8).contains(&vec.east()) && (0..8).contains(&vec.south()) && !walls.contains(&pos)
+        }
+    }
+
+    /// distance computed from scratch by [`Navigation`]
+    fn reference(from: Position, to: Position, can_go: impl Fn(Position) -> bool) -> Option<u16> {
+        let mut nav = Navigation::<
+            ChunkMapHash<16, Option<NonZeroU16>, [[Option<NonZeroU16>; 8]; 8]>,
+            64,
+        >::default();
+        nav.initialize(from, to);
+        block_on(nav.run(can_go));
+        nav.get_state()
+            .is_success()
+            .then(|| nav.get_dist_at(from))
+            .flatten()
+    }
+
+    /// set a wall and report it
+    fn toggle(nav: &mut TestNav, walls: &mut Vec<Position, 16>, wall: Position) {
+        match walls.iter().position(|&pos| pos == wall) {
+            Some(i) => _ = walls.swap_remove(i),
+            None => walls.push(wall).unwrap(),
+        }
+        nav.update_tile(wall, room(walls)).unwrap();
+    }
+
+    #[test]
+    fn repair_after_changes() {
+        // wall between the bot and the destination, with a gap at the south end
+        let mut walls: Vec<Position, 16> = (0..6).map(|south| pos(3, south)).collect();
+        let (from, to) = (pos(0, 0), pos(6, 0));
+        let mut nav = TestNav::default();
+        nav.initialize(from, to);
+        block_on(nav.run(room(&walls)));
+        assert!(nav.get_state().is_success());
+        assert_eq!(nav.get_dist_at(from), Some(18));
+
+        // the wall has a door
+        toggle(&mut nav, &mut walls, pos(3, 0));
+        assert!(matches!(nav.get_state(), NavigationState::Running(_)));
+        block_on(nav.run(room(&walls)));
+        assert_eq!(nav.get_dist_at(from), Some(6));
+
+        // the door is closed again, and there are bots in the way
+        toggle(&mut nav, &mut walls, pos(3, 0));
+        toggle(&mut nav, &mut walls, pos(3, 7));
+        toggle(&mut nav, &mut walls, pos(0, 3));
+        block_on(nav.run(room(&walls)));
+        assert_eq!(nav.get_dist_at(from), reference(from, to, room(&walls)));
+
+        // walk along the path, a bot leaves
+        let mut start = from;
+        for _ in 0..5 {
+            let &dir = nav.next_step(start).all().first().unwrap();
+            start += Vec2::new_in_direction(dir, 1);
+            nav.update_start(start).unwrap();
+        }
+        toggle(&mut nav, &mut walls, pos(0, 3));
+        block_on(nav.run(room(&walls)));
+        assert!(nav.get_state().is_success());
+        assert_eq!(nav.get_dist_at(start), Some(13));
+        assert_eq!(nav.get_dist_at(start), reference(start, to, room(&walls)));
+
+        // the gap is closed
+        toggle(&mut nav, &mut walls, pos(3, 6));
+        block_on(nav.run(room(&walls)));
+        assert!(matches!(nav.get_state(), NavigationState::Impossible(_)));
+        assert_eq!(nav.get_dist_at(start), None);
+        assert!(!nav.next_step(start).any());
+    }
+}
@@ -0,0 +1,315 @@
+use core::num::NonZeroU16;
+
+use async_kartoffel_generic::{Direction, ItemKind, Position, RadarScanTrait, RadarSize};
+use heapless::{FnvIndexMap, Vec};
+
+use super::{
+    Breakpoint, DistanceManhattan, DistanceMeasure, Map, Navigation, NavigationState,
+    error::OutOfMemory,
+};
+
+/// Items seen by the radar, in global coordinates. Up to `N` items are remembered.
+pub struct ItemMemory<const N: usize> {
+    items: FnvIndexMap<Position, ItemKind, N>,
+}
+
+impl<const N: usize> Default for ItemMemory<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ItemMemory<N> {
+    pub fn new() -> Self {
+        Self {
+            items: FnvIndexMap::new(),
+        }
+    }
+
+    /// Record the items of a radar scan taken at `pos` facing `direction`. Items that were seen
+    /// inside the scanned area before but are not there anymore are forgotten.
+    pub fn observe<Size: RadarSize>(
+        &mut self,
+        scan: &impl RadarScanTrait<Size>,
+        pos: Position,
+        direction: Direction,
+    ) -> Result<(), OutOfMemory> {
+        self.items.retain(|&item_pos, &mut kind| {
+            let vec = (item_pos - pos).local(direction);
+            !scan.contains(vec) || scan.at(vec) == Some(kind.to_tile())
+        });
+        for (vec, tile) in scan.iter() {
+            if let Some(kind) = ItemKind::from_tile(tile) {
+                self.items
+                    .insert(pos + vec.global(direction), kind)
+                    .map_err(|_| OutOfMemory)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget the item at `pos`, e.g. after picking it.
+    pub fn remove(&mut self, pos: Position) -> Option<ItemKind> {
+        self.items.remove(&pos)
+    }
+
+    pub fn get(&self, pos: Position) -> Option<ItemKind> {
+        self.items.get(&pos).copied()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// number of remembered items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, ItemKind)> + use<'_, N> {
+        self.items.iter().map(|(&pos, &kind)| (pos, kind))
+    }
+
+    /// the item of one of the `kinds` with the smallest [`DistanceManhattan`] to `pos`
+    pub fn closest(&self, pos: Position, kinds: &[ItemKind]) -> Option<(Position, ItemKind)> {
+        self.iter()
+            .filter(|(_item_pos, kind)| kinds.contains(kind))
+            .min_by_key(|&(item_pos, _kind)| DistanceManhattan::measure(item_pos - pos))
+    }
+}
+
+/// Order of the pickups found by [`PickupPlanner::plan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PickupPlan<const K: usize> {
+    /// item positions, in the order they should be picked
+    pub order: Vec<Position, K>,
+    /// the drop-off point visited after the last item
+    pub drop_off: Option<Position>,
+    /// number of steps of the whole route, from the start to the drop-off
+    pub steps: u16,
+}
+
+/// Orders the pickups of remembered items, optionally returning to a drop-off point afterwards.
+///
+/// The distances between all stops are computed with [`Navigation`], the route is then built with
+/// the nearest neighbor heuristic and improved with 2-opt. The items themselves are used as
+/// stops, although the bot picks them from a neighboring tile. Up to `K` stops are planned,
+/// including the start and the drop-off point, the items closest to the start are preferred.
+/// Items that can't be reached from the start are left out.
+pub struct PickupPlanner<T: Map<Option<NonZeroU16>>, const N: usize, const K: usize> {
+    navigation: Navigation<T, N>,
+    /// the start, the items and the drop-off point
+    stops: Vec<Position, K>,
+    /// number of steps between stops, `None` if there is no path
+    steps: [[Option<u16>; K]; K],
+}
+
+impl<T: Map<Option<NonZeroU16>> + Default, const N: usize, const K: usize> Default
+    for PickupPlanner<T, N, K>
+{
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T: Map<Option<NonZeroU16>>, const N: usize, const K: usize> PickupPlanner<T, N, K> {
+    pub fn new(distances: T) -> Self {
+        assert!(K >= 2);
+        Self {
+            navigation: Navigation::new(distances),
+            stops: Vec::new(),
+            steps: [[None; K]; K],
+        }
+    }
+
+    /// Cancelable planning of the pickups of the items of one of the `kinds`, starting at `start`.
+    pub async fn plan<const M: usize>(
+        &mut self,
+        items: &ItemMemory<M>,
+        kinds: &[ItemKind],
+        start: Position,
+        drop_off: Option<Position>,
+        can_go: impl Fn(Position) -> bool,
+    ) -> Result<PickupPlan<K>, OutOfMemory> {
+        // Future can be dropped at any await point, everything is recomputed on the next call
+        let mut candidates: Vec<Position, M> = items
+            .iter()
+            .filter(|(_pos, kind)| kinds.contains(kind))
+            .map(|(pos, _kind)| pos)
+            .collect();
+        candidates.sort_unstable_by_key(|&pos| DistanceManhattan::measure(pos - start));
+        let n_items = candidates
+            .len()
+            .min(K - 1 - usize::from(drop_off.is_some()));
+
+        self.stops.clear();
+        // can't fail, the number of stops was checked
+        _ = self.stops.push(start);
+        _ = self.stops.extend_from_slice(&candidates[..n_items]);
+        if let Some(drop_off) = drop_off {
+            _ = self.stops.push(drop_off);
+        }
+
+        for i in 0..self.stops.len() {
+            self.steps[i][i] = Some(0);
+            for j in i + 1..self.stops.len() {
+                let steps = self.navigate(self.stops[i], self.stops[j], &can_go).await?;
+                self.steps[i][j] = steps;
+                self.steps[j][i] = steps;
+            }
+        }
+
+        // indices of the reachable items
+        let mut route: Vec<usize, K> = (1..=n_items)
+            .filter(|&i| self.steps[0][i].is_some())
+            .collect();
+        let end = drop_off
+            .map(|_| n_items + 1)
+            .filter(|&end| self.steps[0][end].is_some());
+        self.nearest_neighbor(&mut route).await;
+        self.two_opt(&mut route, end).await;
+
+        Ok(PickupPlan {
+            order: route.iter().map(|&i| self.stops[i]).collect(),
+            drop_off: end.map(|end| self.stops[end]),
+            steps: self.route_steps(&route, end),
+        })
+    }
+
+    async fn navigate(
+        &mut self,
+        from: Position,
+        to: Position,
+        can_go: impl Fn(Position) -> bool,
+    ) -> Result<Option<u16>, OutOfMemory> {
+        self.navigation.initialize(from, to);
+        self.navigation.run(can_go).await;
+        match self.navigation.get_state() {
+            NavigationState::Success(_) => Ok(self.navigation.get_dist_at(from)),
+            NavigationState::Error(err) => Err(err),
+            _ => Ok(None),
+        }
+    }
+
+    /// steps between stops, unreachable stops are treated as very far away
+    fn between(&self, i: usize, j: usize) -> u16 {
+        self.steps[i][j].unwrap_or(u16::MAX / K as u16)
+    }
+
+    fn route_steps(&self, route: &[usize], end: Option<usize>) -> u16 {
+        let mut steps = 0u16;
+        let mut previous = 0;
+        for &stop in route.iter().chain(end.iter()) {
+            steps = steps.saturating_add(self.between(previous, stop));
+            previous = stop;
+        }
+        steps
+    }
+
+    /// reorder `route`, so that it always continues with the closest item
+    async fn nearest_neighbor(&self, route: &mut [usize]) {
+        let mut previous = 0;
+        for i in 0..route.len() {
+            let closest = (i..route.len())
+                .min_by_key(|&j| self.between(previous, route[j]))
+                .unwrap();
+            route.swap(i, closest);
+            previous = route[i];
+            Breakpoint::new().await;
+        }
+    }
+
+    /// reverse parts of `route` as long as it gets shorter
+    async fn two_opt(&self, route: &mut [usize], end: Option<usize>) {
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..route.len() {
+                for j in i + 1..route.len() {
+                    let current = self.route_steps(route, end);
+                    route[i..=j].reverse();
+                    if self.route_steps(route, end) < current {
+                        improved = true;
+                    } else {
+                        route[i..=j].reverse();
+                    }
+                }
+                Breakpoint::new().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::{Tile, Vec2};
+    use embassy_futures::block_on;
+
+    use super::*;
+    use crate::ChunkMapHash;
+    use crate::test_util::{TestScan, pos};
+
+    type TestPlanner =
+        PickupPlanner<ChunkMapHash<16, Option<NonZeroU16>, [[Option<NonZeroU16>; 8]; 8]>, 64, 8>;
+
+    #[test]
+    fn sightings_and_invalidation() {
+        let mut items = ItemMemory::<4>::new();
+        let facing = Direction::North;
+        let scan = TestScan::default()
+            .with_item(Vec2::new_front(1), Tile::Diamond)
+            .with_item(Vec2::new_right(1), Tile::Flag);
+        items.observe(&scan, pos(0, 0), facing).unwrap();
+        assert_eq!(items.get(pos(0, -1)), Some(ItemKind::Diamond));
+        assert_eq!(items.get(pos(1, 0)), Some(ItemKind::Flag));
+        assert_eq!(
+            items.closest(pos(5, 0), &[ItemKind::Diamond, ItemKind::Flag]),
+            Some((pos(1, 0), ItemKind::Flag))
+        );
+
+        // the diamond is gone, the flag is outside of the scan
+        items
+            .observe(&TestScan::default(), pos(-1, 0), facing)
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items.closest(pos(5, 0), &[ItemKind::Diamond]), None);
+        assert_eq!(items.remove(pos(1, 0)), Some(ItemKind::Flag));
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn pickup_order() {
+        // 10 by 3 room with a wall in the middle, open at the north
+        let can_go = |p: Position| {
+            let vec = p.subtract_anchor();
+            (0..10).contains(&vec.east())
+                && (0..3).contains(&vec.south())
+                && (vec.east() != 5 || vec.south() == 0)
+        };
+        let mut items = ItemMemory::<8>::new();
+        let kinds = [ItemKind::Diamond];
+        for (east, south) in [(4, 2), (6, 2), (1, 2), (9, 2)] {
+            let scan = TestScan::default().with_item(Vec2::new_front(1), Tile::Diamond);
+            items
+                .observe(&scan, pos(east, south + 1), Direction::North)
+                .unwrap();
+        }
+        // not reachable
+        let scan = TestScan::default().with_item(Vec2::new_front(1), Tile::Diamond);
+        items.observe(&scan, pos(20, 1), Direction::North).unwrap();
+
+        let mut planner = TestPlanner::default();
+        let plan = block_on(planner.plan(&items, &kinds, pos(0, 0), None, can_go)).unwrap();
+        assert_eq!(plan.order, [pos(1, 2), pos(4, 2), pos(6, 2), pos(9, 2)]);
+        assert_eq!(plan.steps, 3 + 3 + 6 + 3);
+
+        let plan =
+            block_on(planner.plan(&items, &kinds, pos(0, 0), Some(pos(0, 0)), can_go)).unwrap();
+        assert_eq!(plan.drop_off, Some(pos(0, 0)));
+        assert_eq!(plan.steps, 3 + 3 + 6 + 3 + 11);
+    }
+}
@@ -2,13 +2,26 @@
 
 mod breakpoint;
 mod chunk_map;
+mod coverage;
+mod coverage_bot;
+mod danger;
 mod error;
 mod exploration;
+mod frontier;
+mod incremental_navigation;
+mod items;
 mod map;
 mod measure;
 mod navigation;
+mod pose;
+mod serial_writer;
 mod stats;
+pub mod telemetry;
 mod terrain;
+#[cfg(test)]
+mod test_util;
+mod tracker;
+mod weighted_navigation;
 
 pub use breakpoint::Breakpoint;
 pub use chunk_map::ChunkBool;
@@ -16,8 +29,22 @@ pub use chunk_map::ChunkIndex;
 pub use chunk_map::ChunkLocation;
 pub use chunk_map::IterInChunk;
 pub use chunk_map::hash::ChunkMapHash;
+pub use coverage::Coverage;
+pub use coverage::CoverageGoal;
+pub use coverage::CoverageMode;
+pub use coverage_bot::CoverageBot;
+pub use coverage_bot::CoverageBotMap;
+pub use coverage_bot::CoverageBotPlanner;
+pub use danger::DangerMap;
 pub use exploration::Exploration;
 pub use exploration::State as ExplorationState;
+pub use frontier::Frontier;
+pub use frontier::FrontierGoal;
+pub use incremental_navigation::Estimate;
+pub use incremental_navigation::IncrementalNavigation;
+pub use items::ItemMemory;
+pub use items::PickupPlan;
+pub use items::PickupPlanner;
 pub use map::Map;
 pub use measure::DistanceBotStab;
 pub use measure::DistanceBotWalk;
@@ -28,7 +55,20 @@ pub use measure::DistanceMin;
 pub use measure::distance_walk_with_rotation;
 pub use navigation::Navigation;
 pub use navigation::State as NavigationState;
+pub use pose::Confidence;
+pub use pose::Contradiction;
+pub use pose::PoseTracker;
+pub use serial_writer::Overflow;
+pub use serial_writer::SerialWriter;
 pub use stats::StatsDog;
 pub use terrain::ChunkTerrain;
 pub use terrain::Terrain;
 pub use terrain::update_chunk_map;
+pub use terrain::update_chunk_map_reporting;
+pub use tracker::BotTracker;
+pub use tracker::TrackedBot;
+pub use weighted_navigation::Move;
+pub use weighted_navigation::MoveCosts;
+pub use weighted_navigation::Pose;
+pub use weighted_navigation::PoseDistances;
+pub use weighted_navigation::WeightedNavigation;
@@ -198,19 +198,32 @@ pub enum State<T> {
     Error(OutOfMemory),
     Impossible(T),
 }
-impl<const N: usize> State<Progress<N>> {
-    fn strip_data(&self) -> State<NavigationTask> {
+/// Progress of a computation, the data of [`State`] without the computation internals is the task.
+pub trait HasTask {
+    type Task: Copy;
+    fn task(&self) -> Self::Task;
+}
+
+impl<const N: usize> HasTask for Progress<N> {
+    type Task = NavigationTask;
+    fn task(&self) -> NavigationTask {
+        self.task
+    }
+}
+
+impl<P: HasTask> State<P> {
+    pub(crate) fn strip_data(&self) -> State<P::Task> {
         match self {
             State::Ready => State::Ready,
-            State::Running(progress) => State::Running(progress.task),
-            State::Success(progress) => State::Success(progress.task),
+            State::Running(progress) => State::Running(progress.task()),
+            State::Success(progress) => State::Success(progress.task()),
             State::Error(err) => State::Error(*err),
-            State::Impossible(progress) => State::Impossible(progress.task),
+            State::Impossible(progress) => State::Impossible(progress.task()),
         }
     }
 }
-impl State<NavigationTask> {
-    pub fn task(&self) -> Option<NavigationTask> {
+impl<T: Copy> State<T> {
+    pub fn task(&self) -> Option<T> {
         match self {
             State::Ready => None,
             State::Running(task) => Some(*task),
@@ -224,14 +237,14 @@ impl State<NavigationTask> {
     }
 }
 
-impl<const N: usize> State<Progress<N>> {
+impl<P> State<P> {
     fn with(&mut self, f: impl FnOnce(Self) -> Self) {
         // This seems to optimize the stack allocation away for simple functions, but is it not
         // guaranteed. Invalidate self, take ownership, perform mutation and set self again.
         *self = f(core::mem::replace(self, Self::Error(OutOfMemory)));
     }
     /// change state to [`State::Success`] without heap allocations
-    fn success(&mut self) {
+    pub(crate) fn success(&mut self) {
         self.with(|state| match state {
             State::Running(progress) => State::Success(progress),
             State::Impossible(progress) => State::Success(progress),
@@ -239,7 +252,7 @@ impl<const N: usize> State<Progress<N>> {
         });
     }
     /// change state to [`State::Running`] without heap allocations
-    fn running(&mut self) {
+    pub(crate) fn running(&mut self) {
         self.with(|state| match state {
             State::Success(progress) => State::Running(progress),
             State::Impossible(progress) => State::Running(progress),
@@ -247,14 +260,14 @@ impl<const N: usize> State<Progress<N>> {
         });
     }
     /// change state to [`State::Impossible`] without heap allocations
-    fn impossible(&mut self) {
+    pub(crate) fn impossible(&mut self) {
         self.with(|state| match state {
             State::Success(progress) => State::Impossible(progress),
             State::Running(progress) => State::Impossible(progress),
             _ => state,
         });
     }
-    fn error(&mut self, err: OutOfMemory) {
+    pub(crate) fn error(&mut self, err: OutOfMemory) {
         *self = State::Error(err);
     }
 }
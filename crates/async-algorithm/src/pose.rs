@@ -0,0 +1,239 @@
+use core::fmt::Display;
+
+use async_kartoffel_generic::{Direction, Position, PositionAnchor, Transform};
+
+/// How much is known about the pose of the bot.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Confidence {
+    /// no compass reading or fix yet, only the movement since the start is known
+    Unknown,
+    /// facing is known, the position is known relative to the local origin (start position)
+    RelativeOnly,
+    /// position is known in the anchor frame as well
+    Global,
+}
+
+impl Display for Confidence {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// A measurement did not fit the tracked pose. The measurement is adopted, but the pose was wrong
+/// for an unknown time, e.g. because a step was blocked by another bot without noticing.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Contradiction<A: PositionAnchor> {
+    Facing {
+        expected: Direction,
+        measured: Direction,
+    },
+    Position {
+        expected: Position<A>,
+        measured: Position<A>,
+    },
+}
+
+/// Dead reckoning of position and facing from motor transforms, corrected by compass readings and
+/// GPS fixes.
+///
+/// Positions are available in two frames: the local frame with the start position as
+/// (0, 0), and the frame of anchor `A` (e.g. `GpsAnchor`) once a fix was received. Both use the
+/// real directions, so the local frame is only usable once the facing is known.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PoseTracker<A: PositionAnchor> {
+    /// movement since the start, only used while the facing is unknown
+    since_start: Transform,
+    facing: Option<Direction>,
+    /// valid if `facing` is known
+    local: Position,
+    /// position of the local origin in the anchor frame
+    origin: Option<Position<A>>,
+}
+
+impl<A: PositionAnchor> Default for PoseTracker<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: PositionAnchor> PoseTracker<A> {
+    pub fn new() -> Self {
+        Self {
+            since_start: Transform::identity(),
+            facing: None,
+            local: Position::default(),
+            origin: None,
+        }
+    }
+
+    /// Forget everything, the current position becomes the new local origin.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn confidence(&self) -> Confidence {
+        match (self.facing, self.origin) {
+            (None, _) => Confidence::Unknown,
+            (Some(_), None) => Confidence::RelativeOnly,
+            (Some(_), Some(_)) => Confidence::Global,
+        }
+    }
+
+    pub fn facing(&self) -> Option<Direction> {
+        self.facing
+    }
+
+    /// position relative to the start position
+    pub fn local_position(&self) -> Option<Position> {
+        self.facing.map(|_| self.local)
+    }
+
+    pub fn global_position(&self) -> Option<Position<A>> {
+        self.to_global(self.local_position()?)
+    }
+
+    pub fn local_pose(&self) -> Option<(Position, Direction)> {
+        Some((self.local_position()?, self.facing?))
+    }
+
+    pub fn global_pose(&self) -> Option<(Position<A>, Direction)> {
+        Some((self.global_position()?, self.facing?))
+    }
+
+    /// convert from the local frame to the anchor frame
+    pub fn to_global(&self, pos: Position) -> Option<Position<A>> {
+        Some(self.origin? + pos.subtract_anchor())
+    }
+
+    /// convert from the anchor frame to the local frame
+    pub fn to_local(&self, pos: Position<A>) -> Option<Position> {
+        Some(Position::add_to_anchor(pos - self.origin?))
+    }
+
+    /// The bot moved by `transform`, as returned by the motor actions.
+    pub fn moved(&mut self, transform: Transform) {
+        match self.facing {
+            Some(facing) => {
+                let (local, facing) = transform.apply(self.local, facing);
+                self.local = local;
+                self.facing = Some(facing);
+            }
+            None => self.since_start = self.since_start.chain(transform),
+        }
+    }
+
+    /// The compass measured the facing of the bot.
+    pub fn compass(&mut self, measured: Direction) -> Result<(), Contradiction<A>> {
+        match self.facing {
+            None => {
+                // replay the movement since the start, now that the start facing is known
+                let start_facing = measured - self.since_start.rotation();
+                self.local =
+                    Position::default() + self.since_start.translation().global(start_facing);
+                self.since_start = Transform::identity();
+                self.facing = Some(measured);
+                Ok(())
+            }
+            Some(expected) if expected == measured => Ok(()),
+            Some(expected) => {
+                self.facing = Some(measured);
+                Err(Contradiction::Facing { expected, measured })
+            }
+        }
+    }
+
+    /// GPS fix: the bot is at `pos` with `facing` in the anchor frame. A contradicting facing is
+    /// reported before a contradicting position.
+    pub fn fix(&mut self, pos: Position<A>, facing: Direction) -> Result<(), Contradiction<A>> {
+        let facing_result = self.compass(facing);
+        let expected = self.global_position();
+        self.origin = Some(pos - self.local.subtract_anchor());
+        facing_result?;
+        match expected {
+            Some(expected) if expected != pos => Err(Contradiction::Position {
+                expected,
+                measured: pos,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The global position is no longer trusted, e.g. after a contradiction that can't be
+    /// resolved. The local frame is kept.
+    pub fn lose_global(&mut self) {
+        self.origin = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::{Rotation, Vec2};
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum TestAnchor {}
+    impl PositionAnchor for TestAnchor {}
+
+    fn pos<A: PositionAnchor>(east: i16, south: i16) -> Position<A> {
+        Position::add_to_anchor(Vec2::new_east_south(east, south))
+    }
+
+    #[test]
+    fn replay_after_compass() {
+        let mut tracker = PoseTracker::<TestAnchor>::new();
+        tracker.moved(Vec2::new_front(1).into());
+        tracker.moved(Rotation::Right.into());
+        tracker.moved(Vec2::new_front(2).into());
+        assert_eq!(tracker.confidence(), Confidence::Unknown);
+        assert_eq!(tracker.local_position(), None);
+
+        // started facing north, so now facing east
+        assert_eq!(tracker.compass(Direction::East), Ok(()));
+        assert_eq!(tracker.confidence(), Confidence::RelativeOnly);
+        assert_eq!(tracker.local_pose(), Some((pos(2, -1), Direction::East)));
+
+        tracker.moved(Vec2::new_back(1).into());
+        assert_eq!(tracker.local_pose(), Some((pos(1, -1), Direction::East)));
+        assert_eq!(tracker.global_pose(), None);
+    }
+
+    #[test]
+    fn fix_and_contradictions() {
+        let mut tracker = PoseTracker::<TestAnchor>::new();
+        assert_eq!(tracker.fix(pos(10, 10), Direction::South), Ok(()));
+        assert_eq!(tracker.confidence(), Confidence::Global);
+
+        tracker.moved(Vec2::new_front(3).into());
+        assert_eq!(tracker.local_position(), Some(pos(0, 3)));
+        assert_eq!(tracker.global_position(), Some(pos(10, 13)));
+        assert_eq!(tracker.to_local(pos(10, 10)), Some(pos(0, 0)));
+        assert_eq!(tracker.fix(pos(10, 13), Direction::South), Ok(()));
+
+        // one of the steps was blocked
+        assert_eq!(
+            tracker.fix(pos(10, 12), Direction::South),
+            Err(Contradiction::Position {
+                expected: pos(10, 13),
+                measured: pos(10, 12)
+            })
+        );
+        assert_eq!(tracker.global_position(), Some(pos(10, 12)));
+        // the local frame is kept, the origin moves instead
+        assert_eq!(tracker.local_position(), Some(pos(0, 3)));
+
+        assert_eq!(
+            tracker.compass(Direction::West),
+            Err(Contradiction::Facing {
+                expected: Direction::South,
+                measured: Direction::West
+            })
+        );
+        assert_eq!(tracker.facing(), Some(Direction::West));
+
+        tracker.lose_global();
+        assert_eq!(tracker.confidence(), Confidence::RelativeOnly);
+        tracker.reset();
+        assert_eq!(tracker.confidence(), Confidence::Unknown);
+    }
+}
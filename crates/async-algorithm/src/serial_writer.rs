@@ -0,0 +1,398 @@
+use core::{
+    cell::RefCell,
+    fmt::Write,
+    future::poll_fn,
+    task::{Poll, Waker},
+};
+
+use heapless::Deque;
+
+use super::Breakpoint;
+
+/// What [`SerialWriter`] does with a write that doesn't fit into its buffer. Either way a write is
+/// kept or dropped as a whole, so a telemetry frame written with a single `write_str` can't be cut.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Overflow {
+    /// make room by discarding the oldest complete writes
+    DropOldest,
+    /// discard the new write
+    DropNewest,
+}
+
+/// Marks the end of a write in the buffer, it is not valid UTF-8 and never sent.
+const END_OF_WRITE: u8 = 0xff;
+
+/// Buffers text for the serial port, so that logging doesn't block the task that logs.
+///
+/// Text is written with [`core::fmt::Write`], which is implemented for `&SerialWriter`, into a
+/// ring buffer of `N` bytes. A task moves it to the actual serial port (e.g.
+/// `async_kartoffel::Serial`) with [`Self::run`], a chunk at a time. If the buffer is full, writes
+/// are dropped according to the [`Overflow`] policy and counted, unless the text is written with
+/// [`Self::write_waiting`], which waits for space instead.
+///
+/// Every `write_str` is dropped as a whole, but `write!` with arguments writes its pieces
+/// separately. Text that has to stay together, like a telemetry frame, is formatted into a buffer
+/// first.
+pub struct SerialWriter<const N: usize> {
+    state: RefCell<State<N>>,
+}
+
+struct State<const N: usize> {
+    /// UTF-8, characters are only added and removed as a whole. With [`Overflow::DropOldest`] every
+    /// write ends with [`END_OF_WRITE`].
+    buffer: Deque<u8, N>,
+    overflow: Overflow,
+    /// number of [`END_OF_WRITE`] in the buffer
+    complete: usize,
+    /// [`SerialWriter::write_waiting`] is writing a text longer than the buffer a character at a
+    /// time, other writes must not land in the middle of it
+    piecewise: bool,
+    dropped: u32,
+    /// the task draining the buffer, waiting for text
+    reader: Option<Waker>,
+    /// a task waiting for space in the buffer
+    writer: Option<Waker>,
+}
+
+impl<const N: usize> State<N> {
+    fn free(&self) -> usize {
+        N - self.buffer.len()
+    }
+
+    /// bytes needed to buffer `s` as one write
+    fn needed(&self, s: &str) -> usize {
+        s.len() + usize::from(self.overflow == Overflow::DropOldest)
+    }
+
+    fn push(&mut self, s: &str) {
+        for &byte in s.as_bytes() {
+            // can't fail, the callers made sure there is space
+            _ = self.buffer.push_back(byte);
+        }
+        wake(&mut self.reader);
+    }
+
+    fn end_write(&mut self) {
+        // only fails for a canceled `write_waiting`, it is then dropped together with the next write
+        if self.overflow == Overflow::DropOldest && self.buffer.push_back(END_OF_WRITE).is_ok() {
+            self.complete += 1;
+        }
+    }
+
+    /// length in bytes of the next character
+    fn next_len(&mut self) -> Option<usize> {
+        while self.buffer.front() == Some(&END_OF_WRITE) {
+            self.buffer.pop_front();
+            self.complete -= 1;
+        }
+        Some(match *self.buffer.front()? {
+            ..0x80 => 1,
+            0xc0..0xe0 => 2,
+            0xe0..0xf0 => 3,
+            _ => 4,
+        })
+    }
+
+    fn pop(&mut self) -> Option<char> {
+        let len = self.next_len()?;
+        let mut bytes = [0; 4];
+        for byte in &mut bytes[..len] {
+            *byte = self.buffer.pop_front()?;
+        }
+        wake(&mut self.writer);
+        core::str::from_utf8(&bytes[..len]).ok()?.chars().next()
+    }
+
+    /// Discard the oldest complete write.
+    fn drop_oldest(&mut self) -> bool {
+        if self.complete == 0 {
+            return false;
+        }
+        while let Some(byte) = self.buffer.pop_front() {
+            if byte == END_OF_WRITE {
+                self.complete -= 1;
+                break;
+            }
+        }
+        self.dropped += 1;
+        wake(&mut self.writer);
+        true
+    }
+}
+
+impl<const N: usize> SerialWriter<N> {
+    pub fn new(overflow: Overflow) -> Self {
+        // every character and the end of its write have to fit
+        assert!(N >= 4 + usize::from(overflow == Overflow::DropOldest));
+        Self {
+            state: RefCell::new(State {
+                buffer: Deque::new(),
+                overflow,
+                complete: 0,
+                piecewise: false,
+                dropped: 0,
+                reader: None,
+                writer: None,
+            }),
+        }
+    }
+
+    /// number of writes dropped because the buffer was full
+    pub fn dropped(&self) -> u32 {
+        self.state.borrow().dropped
+    }
+
+    /// number of buffered bytes
+    pub fn len(&self) -> usize {
+        self.state.borrow().buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().buffer.is_empty()
+    }
+
+    fn push_or_drop(&self, s: &str) {
+        let mut state = self.state.borrow_mut();
+        let needed = state.needed(s);
+        if state.piecewise {
+            state.dropped += 1;
+            return;
+        }
+        if state.overflow == Overflow::DropOldest && needed <= N {
+            while state.free() < needed && state.drop_oldest() {}
+        }
+        if state.free() < needed {
+            state.dropped += 1;
+        } else {
+            state.push(s);
+            state.end_write();
+        }
+    }
+
+    /// Write `s` without dropping anything, waits until there is space in the buffer. Text longer
+    /// than the buffer is written a character at a time, other writes are dropped or wait until it
+    /// is complete.
+    pub async fn write_waiting(&self, s: &str) {
+        let needed = self.state.borrow().needed(s);
+        if needed <= N {
+            self.wait_for(needed, false).await;
+            let mut state = self.state.borrow_mut();
+            state.push(s);
+            state.end_write();
+            return;
+        }
+
+        self.wait_for(0, false).await;
+        self.state.borrow_mut().piecewise = true;
+        let _piecewise = Piecewise(&self.state);
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            let mut bytes = [0; 4];
+            let c = c.encode_utf8(&mut bytes);
+            // the end of the write has to fit after the last character
+            let needed = match chars.peek() {
+                Some(_) => c.len(),
+                None => self.state.borrow().needed(c),
+            };
+            self.wait_for(needed, true).await;
+            self.state.borrow_mut().push(c);
+        }
+    }
+
+    /// Wait until `needed` bytes are free and no text is written a character at a time, unless it
+    /// is `ours`.
+    async fn wait_for(&self, needed: usize, ours: bool) {
+        poll_fn(|cx| {
+            let mut state = self.state.borrow_mut();
+            if (state.piecewise && !ours) || state.free() < needed {
+                register(&mut state.writer, cx.waker());
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+    }
+
+    /// Wait until there is text in the buffer, then move up to `chunk` bytes to `sink`, at least one
+    /// character.
+    pub async fn drain(&self, sink: &mut impl Write, chunk: usize) -> core::fmt::Result {
+        poll_fn(|cx| {
+            let mut state = self.state.borrow_mut();
+            if state.buffer.is_empty() {
+                register(&mut state.reader, cx.waker());
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+        let mut written = 0;
+        loop {
+            // the buffer isn't borrowed while the sink writes
+            let c = {
+                let mut state = self.state.borrow_mut();
+                match state.next_len() {
+                    Some(len) if written == 0 || written + len <= chunk => state.pop(),
+                    _ => None,
+                }
+            };
+            let Some(c) = c else {
+                break;
+            };
+            written += c.len_utf8();
+            sink.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    /// Drains the buffer into `sink` forever. Has to be spawned as a task.
+    ///
+    /// The task shares the cooperative executor with the others, so it has no lower priority: it
+    /// runs whenever it is polled. To bound the delay of the other tasks, every poll writes at most
+    /// `chunk` bytes, followed by a [`Breakpoint`].
+    pub async fn run(&self, mut sink: impl Write, chunk: usize) -> ! {
+        loop {
+            // nothing to do about a failing sink
+            _ = self.drain(&mut sink, chunk).await;
+            Breakpoint::new().await;
+        }
+    }
+}
+
+impl<const N: usize> Write for &SerialWriter<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if !s.is_empty() {
+            self.push_or_drop(s);
+        }
+        Ok(())
+    }
+}
+
+/// Ends a write of [`SerialWriter::write_waiting`] a character at a time, also if it is canceled.
+struct Piecewise<'a, const N: usize>(&'a RefCell<State<N>>);
+
+impl<const N: usize> Drop for Piecewise<'_, N> {
+    fn drop(&mut self) {
+        let mut state = self.0.borrow_mut();
+        state.piecewise = false;
+        state.end_write();
+        wake(&mut state.writer);
+    }
+}
+
+/// Replace the waker in `slot`, a different task waiting there before is woken, so that it can
+/// register again.
+fn register(slot: &mut Option<Waker>, waker: &Waker) {
+    match slot {
+        Some(current) if current.will_wake(waker) => {}
+        _ => {
+            if let Some(previous) = slot.replace(waker.clone()) {
+                previous.wake();
+            }
+        }
+    }
+}
+
+fn wake(slot: &mut Option<Waker>) {
+    if let Some(waker) = slot.take() {
+        waker.wake();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::{block_on, join::join};
+    use heapless::String;
+
+    use super::*;
+
+    fn drained<const N: usize>(writer: &SerialWriter<N>) -> String<64> {
+        let mut out = String::new();
+        block_on(writer.drain(&mut out, 64)).unwrap();
+        out
+    }
+
+    #[test]
+    fn overflow_policies() {
+        // `write!` writes "abcdef" and "4242" separately, the second one is dropped as a whole
+        let newest = SerialWriter::<8>::new(Overflow::DropNewest);
+        let number = 4242;
+        write!(&newest, "abcdef{number}").unwrap();
+        assert_eq!((newest.len(), newest.dropped()), (6, 1));
+        assert_eq!(drained(&newest), "abcdef");
+        assert!(newest.is_empty());
+
+        // every write takes an extra byte for its end
+        let oldest = SerialWriter::<8>::new(Overflow::DropOldest);
+        write!(&oldest, "abc").unwrap();
+        write!(&oldest, "def").unwrap();
+        write!(&oldest, "gh").unwrap();
+        assert_eq!(oldest.dropped(), 1);
+        // longer than the buffer, the others are kept
+        write!(&oldest, "123456789").unwrap();
+        assert_eq!(oldest.dropped(), 2);
+        assert_eq!(drained(&oldest), "defgh");
+        assert!(oldest.is_empty());
+
+        let newest = SerialWriter::<5>::new(Overflow::DropNewest);
+        write!(&newest, "é€").unwrap();
+        write!(&newest, "a").unwrap();
+        assert_eq!((drained(&newest).as_str(), newest.dropped()), ("é€", 1));
+    }
+
+    #[test]
+    fn chunks_and_backpressure() {
+        let writer = SerialWriter::<4>::new(Overflow::DropNewest);
+        let mut out = String::<64>::new();
+        let mut longest_chunk = 0;
+        block_on(join(writer.write_waiting("hello world"), async {
+            while out.len() < 11 {
+                let before = out.len();
+                writer.drain(&mut out, 3).await.unwrap();
+                longest_chunk = longest_chunk.max(out.len() - before);
+            }
+        }));
+        // the writer waited for space instead of dropping
+        assert_eq!(out, "hello world");
+        assert_eq!((longest_chunk, writer.dropped()), (3, 0));
+
+        // chunks are counted in bytes, but contain at least one character
+        let writer = SerialWriter::<16>::new(Overflow::DropNewest);
+        write!(&writer, "aé€b").unwrap();
+        let mut chunks = String::<64>::new();
+        for chunk in [2, 1, 3, 1] {
+            let mut out = String::<64>::new();
+            block_on(writer.drain(&mut out, chunk)).unwrap();
+            write!(chunks, "{out}|").unwrap();
+        }
+        assert_eq!(chunks, "a|é|€|b|");
+
+        // a waiting write isn't dropped to make room for others
+        let writer = SerialWriter::<8>::new(Overflow::DropOldest);
+        write!(&writer, "abc").unwrap();
+        write!(&writer, "def").unwrap();
+        let mut out = String::<64>::new();
+        block_on(join(writer.write_waiting("xyz"), async {
+            while out.len() < 9 {
+                writer.drain(&mut out, 2).await.unwrap();
+            }
+        }));
+        assert_eq!((out.as_str(), writer.dropped()), ("abcdefxyz", 0));
+
+        // nothing lands in the middle of a waiting write longer than the buffer, and it isn't
+        // dropped to make room
+        let writer = SerialWriter::<8>::new(Overflow::DropOldest);
+        write!(&writer, "ab").unwrap();
+        let mut out = String::<64>::new();
+        block_on(join(writer.write_waiting("0123456789"), async {
+            write!(&writer, "xy").unwrap();
+            while out.len() < 12 {
+                writer.drain(&mut out, 2).await.unwrap();
+            }
+        }));
+        assert_eq!((out.as_str(), writer.dropped()), ("ab0123456789", 1));
+        write!(&writer, "xy").unwrap();
+        assert_eq!(drained(&writer), "xy");
+    }
+}
@@ -96,6 +96,12 @@ impl<C: ClockBackend> StatsDog<C> {
     pub fn total(&self) -> Duration<C> {
         self.sum_duration
     }
+    pub fn min(&self) -> Duration<C> {
+        self.min_duration
+    }
+    pub fn max(&self) -> Duration<C> {
+        self.max_duration
+    }
     /// empirical standard deviation
     pub fn std(&self) -> u32 {
         // std = 1 / (N - 1) * sum((x - µ)^2)
@@ -0,0 +1,408 @@
+//! Compact telemetry records, written to the serial port by the bot and decoded from a captured
+//! serial log on the host (see the `kartoffel-telemetry` crate).
+//!
+//! Every [`Event`] is written as one frame: `<`, the base64 encoding (`A-Za-z0-9+/`, without
+//! padding) of the payload and a checksum byte, then `>`. Whitespace inside a frame is ignored, so
+//! frames may be wrapped by the serial display, and text printed in between frames is skipped.
+//!
+//! The payload is little endian, it starts with the ticks of the event (`u32`) and the tag of the
+//! record (`u8`), followed by the fields of the record:
+//!
+//! | tag | record                 | fields                                                    |
+//! |-----|------------------------|-----------------------------------------------------------|
+//! | 0   | [`Record::Pose`]       | `east: i16, south: i16, facing: u8`                       |
+//! | 1   | [`Record::MapDelta`]   | `east: i16, south: i16, terrain: u8`                      |
+//! | 2   | [`Record::Stats`]      | `count, total, min, mean, max, std: u32`                  |
+//! | 3   | [`Record::Navigation`] | `status: u8`, then `east: i16, south: i16` of from and to |
+//! | 4   | [`Record::Chunk`]      | `east: i16, south: i16` of the north-west corner, then the |
+//! |     |                        | 16 bytes of [`ChunkTerrain::to_bytes`]                    |
+//!
+//! The checksum is the lowest byte of the FNV-1a hash of the payload.
+
+use core::fmt::Write;
+
+use async_kartoffel_generic::{ClockBackend, Direction, Position, Vec2};
+use heapless::Vec;
+
+use super::{
+    ChunkLocation, NavigationState, StatsDog,
+    navigation::NavigationTask,
+    terrain::{ChunkTerrain, Terrain},
+};
+
+/// Maximum length of a payload, including the checksum byte
+const MAX_PAYLOAD: usize = 32;
+
+/// Maximum length of a frame in characters, e.g. to format it into a buffer and write it at once
+pub const MAX_FRAME: usize = 2 + (MAX_PAYLOAD * 4).div_ceil(3);
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub const FRAME_START: char = '<';
+pub const FRAME_END: char = '>';
+
+/// [`NavigationState`] without the data
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum NavigationStatus {
+    Ready,
+    Running,
+    Success,
+    Error,
+    Impossible,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Record {
+    Pose {
+        pos: Position,
+        facing: Direction,
+    },
+    /// a tile of the map has changed, e.g. reported by
+    /// [`update_chunk_map_reporting`](crate::update_chunk_map_reporting)
+    MapDelta {
+        pos: Position,
+        terrain: Terrain,
+    },
+    /// snapshot of a [`StatsDog`], in ticks
+    Stats {
+        count: u32,
+        total: u32,
+        min: u32,
+        mean: u32,
+        max: u32,
+        std: u32,
+    },
+    Navigation {
+        status: NavigationStatus,
+        from: Position,
+        to: Position,
+    },
+    /// all tiles of a chunk of the map, to reconstruct the map on the host
+    Chunk {
+        location: ChunkLocation,
+        terrain: ChunkTerrain,
+    },
+}
+
+impl<C: ClockBackend> From<&StatsDog<C>> for Record {
+    fn from(dog: &StatsDog<C>) -> Self {
+        let count = dog.count();
+        Record::Stats {
+            count,
+            total: dog.total().as_ticks(),
+            min: dog.min().as_ticks(),
+            mean: if count > 0 { dog.mean() } else { 0 },
+            max: dog.max().as_ticks(),
+            std: if count > 1 { dog.std() } else { 0 },
+        }
+    }
+}
+
+impl From<NavigationState<NavigationTask>> for Record {
+    fn from(state: NavigationState<NavigationTask>) -> Self {
+        let status = match state {
+            NavigationState::Ready => NavigationStatus::Ready,
+            NavigationState::Running(_) => NavigationStatus::Running,
+            NavigationState::Success(_) => NavigationStatus::Success,
+            NavigationState::Error(_) => NavigationStatus::Error,
+            NavigationState::Impossible(_) => NavigationStatus::Impossible,
+        };
+        let (from, to) = state
+            .task()
+            .map_or(Default::default(), |task| (task.from, task.to));
+        Record::Navigation { status, from, to }
+    }
+}
+
+/// A [`Record`] and when it happened.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Event {
+    pub ticks: u32,
+    pub record: Record,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DecodeError {
+    /// a character that is neither base64 nor whitespace
+    Character(char),
+    /// the payload is too short or too long for its record
+    Length,
+    Checksum,
+    Tag(u8),
+    /// a field has an invalid value, e.g. a direction
+    Value,
+}
+
+/// Write `event` as a single frame.
+pub fn write(w: &mut impl Write, event: &Event) -> core::fmt::Result {
+    let mut payload = Vec::<u8, MAX_PAYLOAD>::new();
+    // can't fail, every record fits into MAX_PAYLOAD
+    _ = payload.extend_from_slice(&event.ticks.to_le_bytes());
+    match event.record {
+        Record::Pose { pos, facing } => {
+            _ = payload.push(0);
+            push_pos(&mut payload, pos);
+            _ = payload.push(direction_to_u8(facing));
+        }
+        Record::MapDelta { pos, terrain } => {
+            _ = payload.push(1);
+            push_pos(&mut payload, pos);
+            _ = payload.push(terrain.to_last_bits());
+        }
+        Record::Stats {
+            count,
+            total,
+            min,
+            mean,
+            max,
+            std,
+        } => {
+            _ = payload.push(2);
+            for value in [count, total, min, mean, max, std] {
+                _ = payload.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        Record::Navigation { status, from, to } => {
+            _ = payload.push(3);
+            _ = payload.push(status as u8);
+            push_pos(&mut payload, from);
+            push_pos(&mut payload, to);
+        }
+        Record::Chunk { location, terrain } => {
+            _ = payload.push(4);
+            push_pos(&mut payload, location.north_west_pos());
+            _ = payload.extend_from_slice(&terrain.to_bytes());
+        }
+    }
+    _ = payload.push(checksum(&payload));
+
+    w.write_char(FRAME_START)?;
+    for group in payload.chunks(3) {
+        let bits = group.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..=group.len() {
+            let index = (bits >> (18 - 6 * i)) & 0b11_1111;
+            w.write_char(ALPHABET[index as usize].into())?;
+        }
+    }
+    w.write_char(FRAME_END)
+}
+
+fn push_pos(payload: &mut Vec<u8, MAX_PAYLOAD>, pos: Position) {
+    let vec = pos.subtract_anchor();
+    _ = payload.extend_from_slice(&vec.east().to_le_bytes());
+    _ = payload.extend_from_slice(&vec.south().to_le_bytes());
+}
+
+/// Decode the content of a frame, without [`FRAME_START`] and [`FRAME_END`].
+pub fn decode(frame: &str) -> Result<Event, DecodeError> {
+    let mut payload = Vec::<u8, MAX_PAYLOAD>::new();
+    let (mut bits, mut n_bits) = (0u32, 0);
+    for c in frame.chars().filter(|c| !c.is_whitespace()) {
+        let value = ALPHABET
+            .iter()
+            .position(|&a| char::from(a) == c)
+            .ok_or(DecodeError::Character(c))?;
+        bits = (bits << 6) | value as u32;
+        n_bits += 6;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            payload
+                .push((bits >> n_bits) as u8)
+                .map_err(|_| DecodeError::Length)?;
+        }
+    }
+
+    let (&check, payload) = payload.split_last().ok_or(DecodeError::Length)?;
+    if checksum(payload) != check {
+        return Err(DecodeError::Checksum);
+    }
+    let mut reader = Reader { payload };
+    let ticks = reader.u32()?;
+    let record = match reader.u8()? {
+        0 => Record::Pose {
+            pos: reader.pos()?,
+            facing: direction_from_u8(reader.u8()?)?,
+        },
+        1 => Record::MapDelta {
+            pos: reader.pos()?,
+            terrain: match reader.u8()? {
+                bits @ 0..4 => Terrain::from_last_bits(bits),
+                _ => return Err(DecodeError::Value),
+            },
+        },
+        2 => Record::Stats {
+            count: reader.u32()?,
+            total: reader.u32()?,
+            min: reader.u32()?,
+            mean: reader.u32()?,
+            max: reader.u32()?,
+            std: reader.u32()?,
+        },
+        3 => Record::Navigation {
+            status: match reader.u8()? {
+                0 => NavigationStatus::Ready,
+                1 => NavigationStatus::Running,
+                2 => NavigationStatus::Success,
+                3 => NavigationStatus::Error,
+                4 => NavigationStatus::Impossible,
+                _ => return Err(DecodeError::Value),
+            },
+            from: reader.pos()?,
+            to: reader.pos()?,
+        },
+        4 => {
+            let corner = reader.pos()?;
+            let location = ChunkLocation::containing(corner);
+            if location.north_west_pos() != corner {
+                return Err(DecodeError::Value);
+            }
+            Record::Chunk {
+                location,
+                terrain: ChunkTerrain::from_bytes(reader.take()?),
+            }
+        }
+        tag => return Err(DecodeError::Tag(tag)),
+    };
+    if !reader.payload.is_empty() {
+        return Err(DecodeError::Length);
+    }
+    Ok(Event { ticks, record })
+}
+
+struct Reader<'a> {
+    payload: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let (bytes, rest) = self
+            .payload
+            .split_first_chunk::<N>()
+            .ok_or(DecodeError::Length)?;
+        self.payload = rest;
+        Ok(*bytes)
+    }
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take::<1>()?[0])
+    }
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+    fn pos(&mut self) -> Result<Position, DecodeError> {
+        let east = i16::from_le_bytes(self.take()?);
+        let south = i16::from_le_bytes(self.take()?);
+        Ok(Position::add_to_anchor(Vec2::new_east_south(east, south)))
+    }
+}
+
+fn direction_to_u8(direction: Direction) -> u8 {
+    match direction {
+        Direction::North => 0,
+        Direction::East => 1,
+        Direction::South => 2,
+        Direction::West => 3,
+    }
+}
+
+fn direction_from_u8(value: u8) -> Result<Direction, DecodeError> {
+    match value {
+        0 => Ok(Direction::North),
+        1 => Ok(Direction::East),
+        2 => Ok(Direction::South),
+        3 => Ok(Direction::West),
+        _ => Err(DecodeError::Value),
+    }
+}
+
+/// lowest byte of FNV-1a
+fn checksum(bytes: &[u8]) -> u8 {
+    let hash = bytes.iter().fold(0x811c9dc5u32, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    });
+    hash as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use heapless::String;
+
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{ChunkMapHash, Map};
+
+    fn round_trip(record: Record) -> String<MAX_FRAME> {
+        let event = Event {
+            ticks: 0x0102_0304,
+            record,
+        };
+        let mut frame = String::new();
+        write(&mut frame, &event).unwrap();
+        let content = &frame[1..frame.len() - 1];
+        assert_eq!(decode(content), Ok(event));
+        frame
+    }
+
+    #[test]
+    fn records() {
+        let frame = round_trip(Record::Pose {
+            pos: pos(-3, 700),
+            facing: Direction::West,
+        });
+        assert!(frame.starts_with(FRAME_START) && frame.ends_with(FRAME_END));
+        round_trip(Record::MapDelta {
+            pos: pos(12, -1),
+            terrain: Terrain::Reachable,
+        });
+        let frame = round_trip(Record::Stats {
+            count: 5,
+            total: 1000,
+            min: 1,
+            mean: 200,
+            max: u32::MAX,
+            std: 17,
+        });
+        // fits into a line of the serial display
+        assert!(frame.len() <= 42);
+        round_trip(Record::Navigation {
+            status: NavigationStatus::Impossible,
+            from: pos(1, 2),
+            to: pos(-3, -4),
+        });
+
+        let mut map = ChunkMapHash::<4, Terrain, ChunkTerrain>::new();
+        map.set(pos(-9, 17), Terrain::Blocked).unwrap();
+        map.set(pos(-16, 23), Terrain::Walkable).unwrap();
+        let location = ChunkLocation::containing(pos(-9, 17));
+        let terrain = *map.chunk(location).unwrap();
+        let frame = round_trip(Record::Chunk { location, terrain });
+        assert!(frame.len() <= 42);
+    }
+
+    #[test]
+    fn corrupted() {
+        let frame = round_trip(Record::Pose {
+            pos: pos(1, 1),
+            facing: Direction::North,
+        });
+        let content = &frame[1..frame.len() - 1];
+
+        // wrapped by the serial display
+        let mut wrapped = String::<64>::new();
+        wrapped.push_str(&content[..4]).unwrap();
+        wrapped.push('\n').unwrap();
+        wrapped.push_str(&content[4..]).unwrap();
+        assert!(decode(&wrapped).is_ok());
+
+        let mut flipped = String::<64>::new();
+        flipped
+            .push(if content.starts_with('A') { 'B' } else { 'A' })
+            .unwrap();
+        flipped.push_str(&content[1..]).unwrap();
+        assert_eq!(decode(&flipped), Err(DecodeError::Checksum));
+
+        assert_eq!(decode("AB.C"), Err(DecodeError::Character('.')));
+        assert_eq!(decode(""), Err(DecodeError::Length));
+    }
+}
@@ -4,7 +4,7 @@ use async_kartoffel_generic::{
     Direction, Global, Position, RadarScanTrait, RadarSize, Rotation, Vec2,
 };
 
-use crate::{Breakpoint, chunk_map::to_chunk_pos};
+use crate::{Breakpoint, IterInChunk, chunk_map::to_chunk_pos};
 
 use super::{
     chunk_map::{Chunk, ChunkIndex, ChunkLocation, ChunkMap},
@@ -55,7 +55,7 @@ impl Terrain {
             Terrain::Reachable => true,
         }
     }
-    fn from_last_bits(byte: u8) -> Self {
+    pub(crate) fn from_last_bits(byte: u8) -> Self {
         match byte {
             0b00 => Self::Unknown,
             0b01 => Self::Blocked,
@@ -64,7 +64,7 @@ impl Terrain {
             _ => unreachable!(),
         }
     }
-    fn to_last_bits(self) -> u8 {
+    pub(crate) fn to_last_bits(self) -> u8 {
         match self {
             Self::Unknown => 0b00,
             Self::Blocked => 0b01,
@@ -84,16 +84,14 @@ impl Terrain {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 /// memory efficient Chunk for Terrain
 pub struct ChunkTerrain {
     value: [u8; 16],
 }
 impl Chunk<Terrain> for ChunkTerrain {
     fn get(&self, index: ChunkIndex) -> Terrain {
-        let i1 = index.index64().div_euclid(4);
-        let i2 = index.index64().rem_euclid(4);
-        Terrain::get_in_byte(self.value[i1 as usize], i2)
+        ChunkTerrain::get(self, index)
     }
 
     fn set(&mut self, index: ChunkIndex, item: Terrain) {
@@ -111,9 +109,22 @@ impl Chunk<Terrain> for ChunkTerrain {
 }
 
 impl ChunkTerrain {
+    pub fn get(&self, index: ChunkIndex) -> Terrain {
+        let i1 = index.index64().div_euclid(4);
+        let i2 = index.index64().rem_euclid(4);
+        Terrain::get_in_byte(self.value[i1 as usize], i2)
+    }
+    /// 2 bits per tile, in the order of [`ChunkIndex::index64`]
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.value
+    }
+    pub fn from_bytes(value: [u8; 16]) -> Self {
+        Self { value }
+    }
     /// center: relative to north west corner (0, 0 - corner in in_chunk coords)
     /// Fails if a tile would be changed from an already known state. This can happen, if we tried
     /// to walked into another bot, and is probably really annoying to repair.
+    /// `Motor::step_fw_checked` reports whether a step actually happened and helps to avoid this.
     async fn update_from_radar<Size: RadarSize>(
         &mut self,
         radar: &impl RadarScanTrait<Size>,
@@ -121,7 +132,7 @@ impl ChunkTerrain {
         direction: Direction,
     ) -> Result<Self, MapInconsistent> {
         let r: i16 = Size::R as i16;
-        let mut new_self = self.clone();
+        let mut new_self = *self;
         let mut map_changed = false;
         for east in (center.east() - r).clamp(0, 7)..=(center.east() + r).clamp(0, 7) {
             for south in (center.south() - r).clamp(0, 7)..=(center.south() + r).clamp(0, 7) {
@@ -154,6 +165,20 @@ pub async fn update_chunk_map<M: ChunkMap<Terrain, ChunkTerrain>, Size: RadarSiz
     radar: &impl RadarScanTrait<Size>,
     pos: Position,
     direction: Direction,
+) -> Result<(), MapError> {
+    update_chunk_map_reporting(map, radar, pos, direction, |_| {}).await
+}
+
+/// Like [`update_chunk_map`], `changed` is called for every tile that was unknown before and is
+/// known now. Known tiles never change, a scan that contradicts them fails with
+/// [`MapInconsistent`]. Used to repair a navigation incrementally, e.g. with
+/// [`IncrementalNavigation::update_tile`](crate::IncrementalNavigation::update_tile).
+pub async fn update_chunk_map_reporting<M: ChunkMap<Terrain, ChunkTerrain>, Size: RadarSize>(
+    map: &mut M,
+    radar: &impl RadarScanTrait<Size>,
+    pos: Position,
+    direction: Direction,
+    mut changed: impl FnMut(Position),
 ) -> Result<(), MapError> {
     let vec = Vec2::new_east_south(Size::R as i16, Size::R as i16);
     // unique chunks, since maximum scan size is 9 the scan is guaranteed to fit into 4 chunks
@@ -177,7 +202,49 @@ pub async fn update_chunk_map<M: ChunkMap<Terrain, ChunkTerrain>, Size: RadarSiz
     // only write updates once we are sure they are consistent with the map
     for (&location, update) in locations.into_iter().zip(results) {
         // unwrap(): we already ensured it exists
-        *map.get_chunk_mut_or_new(location).unwrap() = update;
+        let chunk = map.get_chunk_mut_or_new(location).unwrap();
+        for index in IterInChunk::new() {
+            if chunk.get(index) == Terrain::Unknown && update.get(index) != Terrain::Unknown {
+                changed(index + location);
+            }
+        }
+        *chunk = update;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::Tile;
+    use embassy_futures::block_on;
+
+    use super::*;
+    use crate::{ChunkMapHash, Map, test_util::TestScan};
+
+    #[test]
+    fn report_unknown_tiles() {
+        let mut map = ChunkMapHash::<4, Terrain, ChunkTerrain>::default();
+        let pos = Position::default();
+        let update = |map: &mut ChunkMapHash<4, Terrain, ChunkTerrain>, scan: &TestScan| {
+            let mut changed = Vec::<Position, 16>::new();
+            block_on(update_chunk_map_reporting(
+                map,
+                scan,
+                pos,
+                Direction::North,
+                |pos| changed.push(pos).unwrap(),
+            ))
+            .map(|()| changed)
+        };
+
+        let scan = TestScan::default();
+        assert_eq!(update(&mut map, &scan).unwrap().len(), 9);
+        map.set(pos, Terrain::Reachable).unwrap();
+        assert_eq!(update(&mut map, &scan).unwrap(), []);
+
+        // a wall where the map knows walkable terrain
+        let scan = TestScan::default().with_item(Vec2::new_front(1), Tile::WallCave);
+        assert!(update(&mut map, &scan).is_err());
+        assert_eq!(map.get(pos), Some(Terrain::Reachable));
+    }
+}
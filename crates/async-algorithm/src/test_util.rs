@@ -0,0 +1,75 @@
+//! Fixtures shared by the tests of the modules.
+
+use core::num::NonZeroU64;
+
+use async_kartoffel_generic::{
+    ClockBackend, D3, Instant, Local, Position, RadarScanTrait, Tile, Vec2,
+};
+
+pub(crate) enum TestClock {}
+impl ClockBackend for TestClock {
+    fn now() -> u32 {
+        0
+    }
+    fn ticks_per_milli() -> u32 {
+        64
+    }
+}
+
+/// 3x3 scan, indexed by [front + 1][right + 1]
+#[derive(Default)]
+pub(crate) struct TestScan {
+    bots: [[Option<u64>; 3]; 3],
+    items: [[Option<Tile>; 3]; 3],
+}
+
+impl TestScan {
+    pub(crate) fn with_bot(mut self, vec: Vec2<Local>, id: u64) -> Self {
+        self.bots[(vec.front() + 1) as usize][(vec.right() + 1) as usize] = Some(id);
+        self
+    }
+    pub(crate) fn with_item(mut self, vec: Vec2<Local>, tile: Tile) -> Self {
+        self.items[(vec.front() + 1) as usize][(vec.right() + 1) as usize] = Some(tile);
+        self
+    }
+    fn entry(&self, vec: Vec2<Local>) -> Option<u64> {
+        self.bots[(vec.front() + 1) as usize][(vec.right() + 1) as usize]
+    }
+}
+
+impl RadarScanTrait<D3> for TestScan {
+    fn contains(&self, vec: Vec2<Local>) -> bool {
+        vec.front().abs() <= 1 && vec.right().abs() <= 1
+    }
+    fn at(&self, vec: Vec2<Local>) -> Option<Tile> {
+        let tile = match self.entry(vec) {
+            _ if vec == Vec2::zero() => Tile::Bot,
+            Some(_) => Tile::Bot,
+            None => self.items[(vec.front() + 1) as usize][(vec.right() + 1) as usize]
+                .unwrap_or(Tile::Empty),
+        };
+        self.contains(vec).then_some(tile)
+    }
+    fn bot_at(&self, vec: Vec2<Local>) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.entry(vec)?)
+    }
+    fn iter_tile(&self, tile: Tile) -> impl Iterator<Item = Vec2<Local>> + use<'_> {
+        self.iter()
+            .filter(move |&(_vec, t)| t == tile)
+            .map(|(vec, _tile)| vec)
+    }
+    fn iter(&self) -> impl Iterator<Item = (Vec2<Local>, Tile)> + use<'_> {
+        (-1..=1)
+            .flat_map(|front| (-1..=1).map(move |right| Vec2::new_front_right(front, right)))
+            .filter(|&vec| vec != Vec2::zero())
+            .map(|vec| (vec, self.at(vec).unwrap()))
+    }
+}
+
+pub(crate) fn pos(east: i16, south: i16) -> Position {
+    Position::add_to_anchor(Vec2::new_east_south(east, south))
+}
+
+pub(crate) fn at(ticks: u32) -> Instant<TestClock> {
+    Instant::from_ticks(ticks)
+}
@@ -0,0 +1,250 @@
+use core::num::NonZeroU64;
+
+use async_kartoffel_generic::{
+    ClockBackend, Direction, Duration, Global, Instant, Position, RadarScanTrait, RadarSize, Tile,
+    Vec2,
+};
+use heapless::{FnvIndexMap, Vec};
+
+use super::{DistanceBotStab, DistanceManhattan, DistanceMeasure};
+
+/// History of an enemy bot, see [`BotTracker`].
+#[derive(Debug)]
+pub struct TrackedBot<C: ClockBackend> {
+    pub id: NonZeroU64,
+    /// latest known position
+    pub pos: Position,
+    /// when the bot was last seen at [`Self::pos`]
+    pub seen: Instant<C>,
+    /// when the bot was first seen at [`Self::pos`]
+    arrived: Instant<C>,
+    /// the position before [`Self::pos`] and when the bot was first seen there
+    previous: Option<(Position, Instant<C>)>,
+}
+
+impl<C: ClockBackend> TrackedBot<C> {
+    fn new(id: NonZeroU64, pos: Position, now: Instant<C>) -> Self {
+        Self {
+            id,
+            pos,
+            seen: now,
+            arrived: now,
+            previous: None,
+        }
+    }
+
+    fn update(&mut self, pos: Position, now: Instant<C>) {
+        if pos != self.pos {
+            self.previous = Some((self.pos, self.arrived));
+            self.pos = pos;
+            self.arrived = now;
+        }
+        self.seen = now;
+    }
+
+    /// The last movement of the bot and the time between the arrivals at both positions. The
+    /// bot may have stopped since then, see [`Self::is_moving`].
+    pub fn last_move(&self) -> Option<(Vec2<Global>, Duration<C>)> {
+        let (previous_pos, previous_arrived) = self.previous?;
+        Some((self.pos - previous_pos, (self.arrived - previous_arrived)?))
+    }
+
+    /// The direction of the last movement, the larger component if it was diagonal.
+    pub fn heading(&self) -> Option<Direction> {
+        let (vec, _duration) = self.last_move()?;
+        match vec.directions() {
+            [direction] => Some(*direction),
+            &[first, second] => match vec.east().abs() >= vec.south().abs() {
+                true => Some(first),
+                false => Some(second),
+            },
+            _ => None,
+        }
+    }
+
+    /// Ticks per tile of the last movement.
+    pub fn ticks_per_tile(&self) -> Option<u32> {
+        let (vec, duration) = self.last_move()?;
+        Some(duration.as_ticks() / u32::from(DistanceManhattan::measure(vec)))
+    }
+
+    /// Whether the bot is expected to keep moving: it was not seen at its position for longer
+    /// than its last movement took.
+    pub fn is_moving(&self) -> bool {
+        self.last_move()
+            .and_then(|(_vec, duration)| Some((self.seen - self.arrived)? < duration))
+            .unwrap_or(false)
+    }
+
+    /// Expected position at `at`, continuing the last movement at the same speed if the bot is
+    /// still moving.
+    pub fn predict(&self, at: Instant<C>) -> Position {
+        let Some((vec, duration)) = self.last_move().filter(|_| self.is_moving()) else {
+            return self.pos;
+        };
+        let elapsed = (at - self.arrived).map_or(0, |elapsed| elapsed.as_ticks());
+        let scale = |component: i16| {
+            let scaled = i64::from(component) * i64::from(elapsed) / i64::from(duration.as_ticks());
+            scaled.clamp(i16::MIN.into(), i16::MAX.into()) as i16
+        };
+        self.pos + Vec2::new_east_south(scale(vec.east()), scale(vec.south()))
+    }
+}
+
+/// Associates the bots of consecutive radar scans by their id to estimate their heading and
+/// speed. Up to `N` bots are tracked, the one not seen for the longest time is replaced. Bots
+/// without an id are ignored.
+pub struct BotTracker<C: ClockBackend, const N: usize> {
+    bots: FnvIndexMap<NonZeroU64, TrackedBot<C>, N>,
+}
+
+impl<C: ClockBackend, const N: usize> Default for BotTracker<C, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: ClockBackend, const N: usize> BotTracker<C, N> {
+    pub fn new() -> Self {
+        Self {
+            bots: FnvIndexMap::new(),
+        }
+    }
+
+    /// Record the bots of a radar scan taken at `pos` facing `direction`.
+    pub fn observe<Size: RadarSize>(
+        &mut self,
+        scan: &impl RadarScanTrait<Size>,
+        pos: Position,
+        direction: Direction,
+        now: Instant<C>,
+    ) {
+        let sightings = || {
+            scan.iter_tile(Tile::Bot)
+                .filter_map(|vec| Some((scan.bot_at(vec)?, pos + vec.global(direction))))
+        };
+        // update the known bots first, so they are not replaced by new ones
+        for (id, bot_pos) in sightings() {
+            if let Some(bot) = self.bots.get_mut(&id) {
+                bot.update(bot_pos, now);
+            }
+        }
+        for (id, bot_pos) in sightings() {
+            if self.bots.contains_key(&id) {
+                continue;
+            }
+            if self.bots.len() == N {
+                self.forget_oldest();
+            }
+            // can't fail, there is space left
+            _ = self.bots.insert(id, TrackedBot::new(id, bot_pos, now));
+        }
+    }
+
+    fn forget_oldest(&mut self) {
+        let oldest = self
+            .bots
+            .values()
+            .min_by(|a, b| a.seen.cmp_wrapping(&b.seen))
+            .map(|bot| bot.id);
+        if let Some(id) = oldest {
+            self.bots.remove(&id);
+        }
+    }
+
+    /// Remove the bots that were last seen more than `max_age` ago.
+    pub fn forget_older_than(&mut self, now: Instant<C>, max_age: Duration<C>) {
+        self.bots
+            .retain(|_id, bot| (now - bot.seen).is_none_or(|age| age <= max_age));
+    }
+
+    pub fn get(&self, id: NonZeroU64) -> Option<&TrackedBot<C>> {
+        self.bots.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TrackedBot<C>> + use<'_, C, N> {
+        self.bots.values()
+    }
+
+    /// Tracked bots with their [`DistanceBotStab`] from `pos` to their predicted position at
+    /// `now`, closest first.
+    pub fn threats(&self, pos: Position, now: Instant<C>) -> Vec<(&TrackedBot<C>, u16), N> {
+        let mut threats: Vec<_, N> = self
+            .bots
+            .values()
+            .map(|bot| (bot, DistanceBotStab::measure(bot.predict(now) - pos)))
+            .collect();
+        threats.sort_unstable_by_key(|&(bot, dist)| (dist, bot.id));
+        threats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::Vec2;
+
+    use super::*;
+    use crate::test_util::{TestClock, TestScan, at, pos};
+
+    fn id(id: u64) -> NonZeroU64 {
+        NonZeroU64::new(id).unwrap()
+    }
+
+    #[test]
+    fn heading_speed_and_prediction() {
+        let mut tracker = BotTracker::<TestClock, 4>::new();
+        let facing = Direction::East;
+        let scan = TestScan::default().with_bot(Vec2::new_front_right(1, -1), 5);
+        tracker.observe(&scan, pos(0, 0), facing, at(0));
+        let bot = tracker.get(id(5)).unwrap();
+        assert_eq!(bot.pos, pos(1, -1));
+        assert_eq!(bot.heading(), None);
+        assert_eq!(bot.predict(at(1000)), pos(1, -1));
+
+        let scan = TestScan::default().with_bot(Vec2::new_front(1), 5);
+        tracker.observe(&scan, pos(0, 0), facing, at(100));
+        let bot = tracker.get(id(5)).unwrap();
+        assert_eq!(bot.heading(), Some(Direction::South));
+        assert_eq!(bot.ticks_per_tile(), Some(100));
+        assert!(bot.is_moving());
+        assert_eq!(bot.predict(at(150)), pos(1, 0));
+        assert_eq!(bot.predict(at(300)), pos(1, 2));
+
+        // standing still for longer than a step took
+        tracker.observe(&scan, pos(0, 0), facing, at(250));
+        let bot = tracker.get(id(5)).unwrap();
+        assert!(!bot.is_moving());
+        assert_eq!(bot.predict(at(300)), pos(1, 0));
+        assert_eq!(bot.heading(), Some(Direction::South));
+    }
+
+    #[test]
+    fn threats_and_capacity() {
+        let mut tracker = BotTracker::<TestClock, 2>::new();
+        let scan = TestScan::default()
+            .with_bot(Vec2::new_front_right(1, 1), 5)
+            .with_bot(Vec2::new_front(1), 6)
+            // bots without id are not tracked
+            .with_bot(Vec2::new_back(1), 0);
+        tracker.observe(&scan, pos(0, 0), Direction::North, at(0));
+        let threats: Vec<_, 2> = tracker
+            .threats(pos(0, 0), at(0))
+            .iter()
+            .map(|&(bot, dist)| (bot.id, dist))
+            .collect();
+        assert_eq!(threats, [(id(6), 0), (id(5), 5)]);
+
+        // the bot seen longest ago is replaced
+        let scan = TestScan::default()
+            .with_bot(Vec2::new_front(1), 6)
+            .with_bot(Vec2::new_right(1), 7);
+        tracker.observe(&scan, pos(0, 0), Direction::North, at(10));
+        assert!(tracker.get(id(5)).is_none());
+        assert_eq!(tracker.get(id(7)).unwrap().pos, pos(1, 0));
+
+        tracker.forget_older_than(at(100), Duration::from_ticks(90));
+        assert_eq!(tracker.iter().count(), 2);
+        tracker.forget_older_than(at(101), Duration::from_ticks(90));
+        assert_eq!(tracker.iter().count(), 0);
+    }
+}
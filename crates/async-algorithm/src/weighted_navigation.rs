@@ -0,0 +1,614 @@
+use core::{marker::PhantomData, num::NonZeroU16};
+
+use async_kartoffel_generic::{Direction, Position, Rotation, Vec2};
+use heapless::{FnvIndexMap, Vec};
+
+use super::{
+    DistanceMeasure, Map,
+    breakpoint::Breakpoint,
+    error::{NoDestination, OutOfMemory},
+    navigation::{HasTask, State},
+};
+
+/// Position and facing of the bot.
+pub type Pose = (Position, Direction);
+
+/// Distance to the destination for every facing at a position, see [`WeightedNavigation`].
+pub type PoseDistances = [Option<NonZeroU16>; 4];
+
+/// Cost of the motor actions, in 5k clock cycles like [`DistanceBotWalk`](crate::DistanceBotWalk).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct MoveCosts {
+    pub forward: u16,
+    pub backward: u16,
+    pub turn: u16,
+}
+
+impl MoveCosts {
+    /// cooldowns of the motor of the real bot
+    pub const BOT: Self = Self {
+        forward: 4,
+        backward: 6,
+        turn: 5,
+    };
+}
+
+impl Default for MoveCosts {
+    fn default() -> Self {
+        Self::BOT
+    }
+}
+
+/// A single motor action.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Move {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+}
+
+impl Move {
+    pub fn all() -> [Self; 4] {
+        [
+            Self::Forward,
+            Self::Backward,
+            Self::TurnLeft,
+            Self::TurnRight,
+        ]
+    }
+
+    pub fn cost(self, costs: &MoveCosts) -> u16 {
+        match self {
+            Move::Forward => costs.forward,
+            Move::Backward => costs.backward,
+            Move::TurnLeft | Move::TurnRight => costs.turn,
+        }
+    }
+
+    /// pose after the move
+    pub fn apply(self, (pos, facing): Pose) -> Pose {
+        match self {
+            Move::Forward => (pos + Vec2::new_in_direction(facing, 1), facing),
+            Move::Backward => (pos + Vec2::new_in_direction(facing, -1), facing),
+            Move::TurnLeft => (pos, facing + Rotation::Left),
+            Move::TurnRight => (pos, facing + Rotation::Right),
+        }
+    }
+
+    /// pose before the move, if it ended at `pose`
+    pub(crate) fn revert(self, (pos, facing): Pose) -> Pose {
+        match self {
+            Move::Forward => (pos + Vec2::new_in_direction(facing, -1), facing),
+            Move::Backward => (pos + Vec2::new_in_direction(facing, 1), facing),
+            Move::TurnLeft => (pos, facing + Rotation::Right),
+            Move::TurnRight => (pos, facing + Rotation::Left),
+        }
+    }
+}
+
+/// cost of `step` ending at `after`, steps onto a tile additionally cost `tile_cost` of the tile
+fn step_cost(
+    step: Move,
+    after: Pose,
+    costs: &MoveCosts,
+    tile_cost: &impl Fn(Position) -> u16,
+) -> u16 {
+    match step {
+        Move::Forward | Move::Backward => step.cost(costs).saturating_add(tile_cost(after.0)),
+        Move::TurnLeft | Move::TurnRight => step.cost(costs),
+    }
+}
+
+fn index(facing: Direction) -> usize {
+    match facing {
+        Direction::East => 0,
+        Direction::South => 1,
+        Direction::West => 2,
+        Direction::North => 3,
+    }
+}
+
+/// handles Option<NonZeroU16> and addition of 1, like the distances of
+/// [`Navigation`](crate::Navigation)
+pub(crate) fn distances_set(
+    distances: &mut impl Map<PoseDistances>,
+    (pos, facing): Pose,
+    distance: u16,
+) -> Result<(), OutOfMemory> {
+    let mut at = distances.get(pos).unwrap_or_default();
+    at[index(facing)] = NonZeroU16::new(distance.saturating_add(1));
+    distances.set(pos, at).map_err(|_| OutOfMemory)
+}
+pub(crate) fn distances_get(
+    distances: &impl Map<PoseDistances>,
+    (pos, facing): Pose,
+) -> Option<u16> {
+    let dist = distances.get(pos)?[index(facing)]?;
+    Some(u16::from(dist) - 1)
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct PoseTask {
+    pub from: Pose,
+    pub to: Position,
+}
+
+impl core::fmt::Display for PoseTask {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} {:?} -> {}", self.from.0, self.from.1, self.to)
+    }
+}
+
+#[derive(Debug)]
+struct Progress<H: DistanceMeasure, const N: usize> {
+    /// Active poses with cost [`Self::cost_current`]
+    active_current: Vec<Pose, N>,
+
+    /// Active poses with cost larger than [`Self::cost_current`]
+    active_next: FnvIndexMap<Pose, u16, N>,
+
+    /// The cost for every item in [`Self::active_current`]: the cost of the moves from the pose to
+    /// [`PoseTask::to`], plus the heuristic `H` between the pose and [`PoseTask::from`].
+    cost_current: u16,
+
+    task: PoseTask,
+    _heuristic: PhantomData<H>,
+}
+
+enum NavigationResult {
+    Impossible,
+    Success,
+}
+
+impl<H: DistanceMeasure, const N: usize> HasTask for Progress<H, N> {
+    type Task = PoseTask;
+    fn task(&self) -> PoseTask {
+        self.task
+    }
+}
+
+impl<H: DistanceMeasure, const N: usize> Progress<H, N> {
+    fn heuristic(&self, pos: Position) -> u16 {
+        H::measure(pos - self.task.from.0)
+    }
+
+    /// move the items with minimal cost from `active_next` to the empty `active_current`
+    fn next_cost(&mut self) -> Option<u16> {
+        debug_assert!(self.active_current.is_empty());
+        let cost_minimum = self.active_next.values().copied().min()?;
+        self.cost_current = cost_minimum;
+        for (&pose, &cost) in &self.active_next {
+            if cost == cost_minimum {
+                // unwrap: same size for both vecs
+                self.active_current.push(pose).unwrap();
+            }
+        }
+        self.active_next
+            .retain(|_pose, &mut cost| cost > cost_minimum);
+        Some(cost_minimum)
+    }
+
+    /// Whether the path from the start is known to be the shortest: every other path is at least
+    /// as expensive as the active poses.
+    fn is_done(&self, distances: &impl Map<PoseDistances>) -> bool {
+        distances_get(distances, self.task.from).is_some_and(|dist| {
+            (self.active_current.is_empty() || self.cost_current >= dist)
+                && self.active_next.values().all(|&cost| cost >= dist)
+        })
+    }
+
+    /// Recompute the costs of the active poses for the heuristic to `new_start`. The distances
+    /// don't depend on the start, only the order in which the poses are handled.
+    fn change_start(
+        &mut self,
+        distances: &impl Map<PoseDistances>,
+        new_start: Pose,
+    ) -> Result<(), OutOfMemory> {
+        if new_start == self.task.from {
+            return Ok(());
+        }
+        let mut active: Vec<Pose, N> = Vec::new();
+        for &pose in self.active_next.keys() {
+            // unwrap: same size for both vecs
+            active.push(pose).unwrap();
+        }
+        for &pose in &self.active_current {
+            active.push(pose).map_err(|_| OutOfMemory)?;
+        }
+
+        self.task.from = new_start;
+        self.active_current.clear();
+        self.active_next.clear();
+        for pose in active {
+            // unwrap: every active pose has a distance
+            let cost = distances_get(distances, pose)
+                .unwrap()
+                .saturating_add(self.heuristic(pose.0));
+            self.active_next
+                .insert(pose, cost)
+                .map_err(|_| OutOfMemory)?;
+        }
+        self.next_cost();
+        Ok(())
+    }
+
+    async fn run(
+        &mut self,
+        distances: &mut impl Map<PoseDistances>,
+        costs: &MoveCosts,
+        can_go: impl Fn(Position) -> bool,
+        tile_cost: impl Fn(Position) -> u16,
+    ) -> Result<NavigationResult, OutOfMemory> {
+        if !can_go(self.task.to) {
+            return Ok(NavigationResult::Impossible);
+        }
+        loop {
+            while let Some(pose) = self.active_current.pop() {
+                // unwrap: every active pose has a distance
+                let distance_current = distances_get(distances, pose).unwrap();
+                for step in Move::all() {
+                    let before = step.revert(pose);
+                    if before.0 != self.task.from.0 && !can_go(before.0) {
+                        continue;
+                    }
+                    let distance_before =
+                        distance_current.saturating_add(step_cost(step, pose, costs, &tile_cost));
+                    if distances_get(distances, before).is_some_and(|dist| dist <= distance_before)
+                    {
+                        continue;
+                    }
+                    distances_set(distances, before, distance_before)?;
+                    let cost_before = distance_before.saturating_add(self.heuristic(before.0));
+                    // The heuristic may drop by more than the cost of a move (e.g. the turn of
+                    // `DistanceBotWalk`), these poses are handled with the current ones.
+                    if cost_before <= self.cost_current {
+                        if !self.active_current.contains(&before) {
+                            self.active_current.push(before).map_err(|_| OutOfMemory)?;
+                        }
+                        self.active_next.remove(&before);
+                    } else {
+                        self.active_next
+                            .insert(before, cost_before)
+                            .map_err(|_| OutOfMemory)?;
+                    }
+                }
+                // future is cancellable here
+                Breakpoint::new().await;
+            }
+
+            // active_current is now empty
+
+            if self.is_done(distances) {
+                return Ok(NavigationResult::Success);
+            }
+            if self.next_cost().is_none() {
+                return Ok(NavigationResult::Impossible);
+            }
+        }
+    }
+}
+
+/// A interruptable navigation computation to a fixed destination, like
+/// [`Navigation`](crate::Navigation), but over position and facing. Moves have the [`MoveCosts`],
+/// so the path avoids unnecessary turns and backward steps.
+///
+/// `H` estimates the cost between two positions and must never overestimate it, e.g.
+/// [`DistanceBotWalk`](crate::DistanceBotWalk) for [`MoveCosts::BOT`] or
+/// [`DistanceManhattan`](crate::DistanceManhattan) for costs of at least 1.
+pub struct WeightedNavigation<T: Map<PoseDistances>, H: DistanceMeasure, const N: usize> {
+    /// Cost of the moves from a pose to the destination, plus one.
+    distances: T,
+    costs: MoveCosts,
+    state: State<Progress<H, N>>,
+}
+
+impl<T: Map<PoseDistances> + Default, H: DistanceMeasure, const N: usize> Default
+    for WeightedNavigation<T, H, N>
+{
+    fn default() -> Self {
+        Self::new(Default::default(), MoveCosts::default())
+    }
+}
+
+impl<T: Map<PoseDistances>, H: DistanceMeasure, const N: usize> WeightedNavigation<T, H, N> {
+    pub fn new(distances: T, costs: MoveCosts) -> Self {
+        assert!(N >= 4);
+        Self {
+            distances,
+            costs,
+            state: State::Ready,
+        }
+    }
+
+    pub fn costs(&self) -> &MoveCosts {
+        &self.costs
+    }
+
+    /// The destination is reached with any facing.
+    pub fn initialize(&mut self, from: Pose, to: Position) {
+        // Initialize active_current as default to prevent allocating it on the stack.
+        self.state = State::Running(Progress {
+            active_current: Default::default(),
+            active_next: Default::default(),
+            cost_current: H::measure(from.0 - to),
+            task: PoseTask { from, to },
+            _heuristic: PhantomData,
+        });
+        let State::Running(ref mut progress) = self.state else {
+            panic!()
+        };
+        self.distances.clear();
+        for facing in Direction::all() {
+            // unwrap: we checked N >= 4
+            progress.active_current.push((to, facing)).unwrap();
+            distances_set(&mut self.distances, (to, facing), 0)
+                .expect("map should always allow the initial entry to succeed");
+        }
+        if from.0 == to {
+            self.state.success();
+        }
+    }
+
+    pub fn get_state(&self) -> State<PoseTask> {
+        self.state.strip_data()
+    }
+
+    /// The bot moved to `new_start`. The computation continues from the current state, it is
+    /// done as soon as the shortest path from the new start is known.
+    pub fn update_start(&mut self, new_start: Pose) -> Result<(), NoDestination> {
+        let progress = match &mut self.state {
+            State::Running(progress) | State::Success(progress) | State::Impossible(progress) => {
+                progress
+            }
+            _ => return Err(NoDestination),
+        };
+        match progress.change_start(&self.distances, new_start) {
+            Ok(()) if progress.is_done(&self.distances) => self.state.success(),
+            Ok(()) => self.state.running(),
+            Err(err) => self.state.error(err),
+        }
+        Ok(())
+    }
+
+    pub async fn run(&mut self, can_go: impl Fn(Position) -> bool) {
+        self.run_with_cost(can_go, |_| 0).await
+    }
+
+    /// Like [`Self::run`], stepping onto a tile additionally costs `tile_cost`, e.g. the
+    /// [`DangerMap::cost_at`](crate::DangerMap::cost_at) of recently seen bots. Unlike `can_go`
+    /// this is a soft cost: an expensive tile is avoided if there is a cheaper detour, but it still
+    /// can be used.
+    ///
+    /// `tile_cost` should not change during the computation. Since the extra cost is never
+    /// negative, the heuristic `H` stays valid.
+    pub async fn run_with_cost(
+        &mut self,
+        can_go: impl Fn(Position) -> bool,
+        tile_cost: impl Fn(Position) -> u16,
+    ) {
+        if let State::Running(progress) = &mut self.state {
+            match progress
+                .run(&mut self.distances, &self.costs, can_go, tile_cost)
+                .await
+            {
+                Ok(NavigationResult::Impossible) => self.state.impossible(),
+                Ok(NavigationResult::Success) => self.state.success(),
+                Err(err) => self.state.error(err),
+            }
+        }
+    }
+
+    /// The first move of the cheapest known path from `pose`, None if there is no known path or
+    /// the destination is reached.
+    pub fn next_move(&self, pose: Pose) -> Option<Move> {
+        self.next_move_with_cost(pose, |_| 0)
+    }
+
+    /// Like [`Self::next_move`] for distances computed by [`Self::run_with_cost`] with the same
+    /// `tile_cost`.
+    pub fn next_move_with_cost(
+        &self,
+        pose: Pose,
+        tile_cost: impl Fn(Position) -> u16,
+    ) -> Option<Move> {
+        let dist_at = self.get_dist_at(pose)?;
+        Move::all()
+            .into_iter()
+            .filter_map(|step| {
+                let after = step.apply(pose);
+                let dist = self.get_dist_at(after)?;
+                let total = dist.saturating_add(step_cost(step, after, &self.costs, &tile_cost));
+                (dist < dist_at && total <= dist_at).then_some((total, step))
+            })
+            .min_by_key(|&(total, _)| total)
+            .map(|(_, step)| step)
+    }
+
+    /// cost of the moves from `pose` to the destination, if known
+    pub fn get_dist_at(&self, pose: Pose) -> Option<u16> {
+        distances_get(&self.distances, pose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embassy_futures::{block_on, select::select, yield_now};
+
+    use super::*;
+    use crate::test_util::pos;
+    use crate::{ChunkMapHash, DistanceBotWalk, NavigationState};
+
+    type TestNav = WeightedNavigation<
+        ChunkMapHash<16, PoseDistances, [[PoseDistances; 8]; 8]>,
+        DistanceBotWalk,
+        256,
+    >;
+
+    /// follow the moves to the destination, returns the total cost and the number of turns
+    fn walk(nav: &TestNav, mut pose: Pose, to: Position) -> (u16, usize) {
+        let (mut cost, mut turns) = (0, 0);
+        while let Some(step) = nav.next_move(pose) {
+            cost += step.cost(nav.costs());
+            turns += usize::from(matches!(step, Move::TurnLeft | Move::TurnRight));
+            pose = step.apply(pose);
+        }
+        assert_eq!(pose.0, to);
+        (cost, turns)
+    }
+
+    fn navigate(from: Pose, to: Position, can_go: impl Fn(Position) -> bool) -> TestNav {
+        let mut nav = TestNav::default();
+        nav.initialize(from, to);
+        block_on(nav.run(can_go));
+        nav
+    }
+
+    #[test]
+    fn turns_and_backward_steps() {
+        let open = |pos: Position| {
+            let vec = pos.subtract_anchor();
+            vec.east().abs() < 8 && vec.south().abs() < 8
+        };
+
+        let from = (pos(0, 0), Direction::North);
+        let nav = navigate(from, pos(2, 0), open);
+        assert!(nav.get_state().is_success());
+        assert_eq!(nav.get_dist_at(from), Some(5 + 2 * 4));
+        assert_eq!(nav.next_move(from), Some(Move::TurnRight));
+
+        let from = (pos(0, 0), Direction::West);
+        let nav = navigate(from, pos(1, 0), open);
+        assert_eq!(nav.get_dist_at(from), Some(6));
+        assert_eq!(nav.next_move(from), Some(Move::Backward));
+
+        // a single turn instead of a staircase
+        let from = (pos(0, 0), Direction::East);
+        let nav = navigate(from, pos(3, 3), open);
+        assert_eq!(
+            nav.get_dist_at(from),
+            Some(DistanceBotWalk::measure(pos(3, 3) - from.0))
+        );
+        assert_eq!(walk(&nav, from, pos(3, 3)), (29, 1));
+    }
+
+    #[test]
+    fn detour() {
+        // wall between the bot and the destination, with a gap at the south end
+        let can_go = |pos: Position| {
+            let vec = pos.subtract_anchor();
+            (0..=4).contains(&vec.east())
+                && (0..=3).contains(&vec.south())
+                && (vec.east() != 2 || vec.south() == 3)
+        };
+        let from = (pos(0, 0), Direction::East);
+        let nav = navigate(from, pos(4, 0), can_go);
+        assert!(nav.get_state().is_success());
+        let dist = nav.get_dist_at(from).unwrap();
+        // turn, 3 steps south, turn, 4 steps east, turn, 3 steps north
+        assert_eq!(dist, 3 * 5 + 10 * 4);
+        assert_eq!(walk(&nav, from, pos(4, 0)), (dist, 3));
+
+        // moving along the path keeps it valid
+        let next = nav.next_move(from).unwrap().apply(from);
+        let mut nav = nav;
+        nav.update_start(next).unwrap();
+        assert!(nav.get_state().is_success());
+        assert_eq!(walk(&nav, next, pos(4, 0)).0, dist - 5);
+
+        let blocked = pos(2, 3);
+        let nav = navigate(from, pos(4, 0), |p| can_go(p) && p != blocked);
+        assert!(matches!(nav.get_state(), NavigationState::Impossible(_)));
+        assert_eq!(nav.next_move(from), None);
+    }
+
+    #[test]
+    fn start_changed_mid_search() {
+        let can_go = |pos: Position| {
+            let vec = pos.subtract_anchor();
+            (0..=6).contains(&vec.east())
+                && (0..=5).contains(&vec.south())
+                && (vec.east() != 3 || vec.south() == 5)
+        };
+        let from = (pos(0, 0), Direction::East);
+        let to = pos(6, 0);
+        let starts = [
+            (pos(0, 1), Direction::East),
+            (pos(1, 0), Direction::South),
+            (pos(0, 4), Direction::North),
+            (pos(5, 5), Direction::West),
+        ];
+        for new_start in starts {
+            let fresh = navigate(new_start, to, can_go);
+            let expected = fresh.get_dist_at(new_start).unwrap();
+            for breakpoints in 0..64 {
+                let mut nav = TestNav::default();
+                nav.initialize(from, to);
+                // interrupt the search after some breakpoints
+                block_on(select(nav.run(can_go), async {
+                    for _ in 0..breakpoints {
+                        yield_now().await;
+                    }
+                }));
+                nav.update_start(new_start).unwrap();
+                block_on(nav.run(can_go));
+                assert!(nav.get_state().is_success());
+                assert_eq!(nav.get_dist_at(new_start), Some(expected));
+                assert_eq!(walk(&nav, new_start, to).0, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn soft_cost() {
+        // two lanes, the bot is in the northern one
+        let can_go = |pos: Position| {
+            let vec = pos.subtract_anchor();
+            (0..=4).contains(&vec.east()) && (0..=1).contains(&vec.south())
+        };
+        let from = (pos(0, 0), Direction::East);
+        let danger = pos(2, 0);
+        let navigate_with = |cost: u16| {
+            let mut nav = TestNav::default();
+            nav.initialize(from, pos(4, 0));
+            let tile_cost = move |p: Position| if p == danger { cost } else { 0 };
+            block_on(nav.run_with_cost(can_go, tile_cost));
+            assert!(nav.get_state().is_success());
+            let mut pose = from;
+            let mut path = Vec::<Position, 32>::new();
+            while let Some(step) = nav.next_move_with_cost(pose, tile_cost) {
+                pose = step.apply(pose);
+                path.push(pose.0).unwrap();
+            }
+            assert_eq!(pose.0, pos(4, 0));
+            (nav.get_dist_at(from).unwrap(), path.contains(&danger))
+        };
+
+        // cheaper to walk through than the detour with 3 turns
+        assert_eq!(navigate_with(5), (4 * 4 + 5, true));
+        // the detour through the southern lane
+        assert_eq!(navigate_with(100), (3 * 5 + 6 * 4, false));
+    }
+
+    #[test]
+    fn saturated_cost() {
+        let can_go = |pos: Position| {
+            let vec = pos.subtract_anchor();
+            (0..=4).contains(&vec.east()) && (0..=1).contains(&vec.south())
+        };
+        let from = (pos(0, 0), Direction::East);
+        let mut nav = TestNav::default();
+        nav.initialize(from, pos(4, 0));
+        // the costs saturate instead of overflowing
+        block_on(nav.run_with_cost(can_go, |_| u16::MAX));
+        assert!(nav.get_state().is_success());
+        assert!(nav.get_dist_at(from).is_some());
+
+        // also when the start changes
+        let next = (pos(1, 0), Direction::East);
+        nav.update_start(next).unwrap();
+        block_on(nav.run_with_cost(can_go, |_| u16::MAX));
+        assert!(nav.get_state().is_success());
+        assert!(nav.get_dist_at(next).is_some());
+    }
+}
@@ -0,0 +1,80 @@
+use core::task::Waker;
+
+use crate::{ClockBackend, reactor::ReactorState};
+
+/// Hardware abstraction for the bot peripherals.
+///
+/// Like [`ClockBackend`], all functions are associated functions without receiver, because the
+/// hardware is global state. The firmware implementation lives in `async-kartoffel`, a simulated
+/// one for running on the host in `host-kartoffel`.
+pub trait BotBackend: 'static {
+    type Clock: ClockBackend;
+
+    fn is_motor_ready() -> bool;
+    fn motor_step_fw();
+    fn motor_step_bw();
+    fn motor_turn_left();
+    fn motor_turn_right();
+
+    fn is_arm_ready() -> bool;
+    fn arm_stab();
+    fn arm_pick();
+    fn arm_drop(idx: u8);
+
+    fn is_radar_ready() -> bool;
+    /// start a scan of size `d` by `d`
+    fn radar_scan(d: u8);
+    /// `dx` points to the right, `dy` to the back, `z == 0` is the tile char, `z == 1` and `z == 2`
+    /// are the upper and lower half of the bot id
+    fn radar_read(d: u8, dx: i8, dy: i8, z: u8) -> u32;
+
+    /// 0 if not ready, otherwise north, east, south, west as 1 to 4
+    fn compass_dir() -> u32;
+
+    /// Access to the state shared by all peripherals. Implementations have to make sure `f` has
+    /// exclusive access, e.g. with a critical section.
+    fn with_state<T>(f: impl FnOnce(&mut BackendState) -> T) -> T;
+}
+
+/// Global state shared by the peripherals of a [`BotBackend`]. It is defined here, but stored by
+/// the backend, because generic statics are not possible.
+#[derive(Debug)]
+pub struct BackendState {
+    pub(crate) bot_taken: bool,
+    pub(crate) radar_guard: RadarGuard,
+    pub(crate) reactor: ReactorState,
+}
+
+impl BackendState {
+    pub const fn new() -> Self {
+        Self {
+            bot_taken: false,
+            radar_guard: RadarGuard::new(),
+            reactor: ReactorState::new(),
+        }
+    }
+}
+
+impl Default for BackendState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps track of living radar scans, so that new scans can't overwrite old ones.
+#[derive(Debug)]
+pub(crate) struct RadarGuard {
+    pub(crate) active_uuid: u32,
+    pub(crate) n_scans: u32,
+    pub(crate) waker: Option<Waker>,
+}
+
+impl RadarGuard {
+    const fn new() -> Self {
+        Self {
+            active_uuid: 0,
+            n_scans: 0,
+            waker: None,
+        }
+    }
+}
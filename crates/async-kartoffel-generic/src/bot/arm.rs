@@ -0,0 +1,81 @@
+use core::marker::PhantomData;
+
+use crate::{BotBackend, Peripheral, Reactor, cooldown};
+
+use super::error::NotReady;
+
+#[non_exhaustive]
+pub struct Arm<B: BotBackend> {
+    _phantom: PhantomData<B>,
+}
+
+impl<B: BotBackend> Arm<B> {
+    pub(super) fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_ready(&self) -> bool {
+        B::is_arm_ready()
+    }
+
+    #[inline(always)]
+    pub fn wait_blocking(&self) {
+        while !self.is_ready() {
+            //
+        }
+    }
+
+    pub async fn wait(&self) {
+        Reactor::<B>::wait(Peripheral::Arm, || self.is_ready()).await;
+    }
+
+    pub fn try_stab(&mut self) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::arm_stab();
+            Reactor::<B>::started(Peripheral::Arm, cooldown::ARM_STAB);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn stab(&mut self) {
+        self.wait().await;
+        B::arm_stab();
+        Reactor::<B>::started(Peripheral::Arm, cooldown::ARM_STAB);
+    }
+
+    pub fn try_pick(&mut self) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::arm_pick();
+            Reactor::<B>::started(Peripheral::Arm, cooldown::ARM_PICK);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn pick(&mut self) {
+        self.wait().await;
+        B::arm_pick();
+        Reactor::<B>::started(Peripheral::Arm, cooldown::ARM_PICK);
+    }
+
+    /// drop the item at index `idx` of the inventory, see [`Inventory`](crate::Inventory) to keep
+    /// track of the indices
+    pub fn try_drop(&mut self, idx: u8) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::arm_drop(idx);
+            Reactor::<B>::started(Peripheral::Arm, cooldown::ARM_DROP);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn drop(&mut self, idx: u8) {
+        self.wait().await;
+        B::arm_drop(idx);
+        Reactor::<B>::started(Peripheral::Arm, cooldown::ARM_DROP);
+    }
+}
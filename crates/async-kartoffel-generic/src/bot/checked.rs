@@ -0,0 +1,148 @@
+use crate::{BotBackend, Local, RadarScanTrait, RadarSize, Rotation, Tile, Transform, Vec2};
+
+use super::{Motor, Radar, RadarScan, error::NotReady};
+
+/// Result of a motor action, determined by comparing radar scans from before and after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StepOutcome {
+    /// the action happened
+    Moved,
+    /// the bot stayed where it was, e.g. because another bot was in the way
+    Blocked,
+    /// the scans fit both or neither, e.g. in a featureless corridor or if the terrain changed
+    Unknown,
+}
+
+/// What happened during a checked motor action, see [`Motor::step_fw_checked`].
+pub struct CheckedStep<B: BotBackend, Size: RadarSize> {
+    pub outcome: StepOutcome,
+    /// `Some` unless the outcome is [`StepOutcome::Unknown`]
+    pub transform: Option<Transform>,
+    /// the scan taken after the action
+    pub scan: RadarScan<B, Size>,
+}
+
+/// copy of a radar scan, so that the next scan is not blocked
+struct Snapshot {
+    r: i16,
+    tiles: [[Tile; 9]; 9],
+}
+
+impl Snapshot {
+    fn new<Size: RadarSize>(scan: &impl RadarScanTrait<Size>) -> Self {
+        let mut tiles = [[Tile::Void; 9]; 9];
+        for (vec, tile) in scan.iter() {
+            tiles[(vec.front() + 4) as usize][(vec.right() + 4) as usize] = tile;
+        }
+        Self {
+            r: Size::R.into(),
+            tiles,
+        }
+    }
+
+    fn at(&self, vec: Vec2<Local>) -> Option<Tile> {
+        (vec.front().abs() <= self.r && vec.right().abs() <= self.r && vec != Vec2::zero())
+            .then(|| self.tiles[(vec.front() + 4) as usize][(vec.right() + 4) as usize])
+    }
+}
+
+/// Number of tiles contradicting the assumption that the bot moved by `transform` between the two
+/// scans. Bots are ignored, they move on their own.
+fn mismatches<Size: RadarSize>(
+    before: &Snapshot,
+    after: &impl RadarScanTrait<Size>,
+    transform: Transform,
+) -> usize {
+    after
+        .iter()
+        .filter(|(vec, tile)| {
+            let vec_before = transform.chain(Transform::from(*vec)).translation();
+            match before.at(vec_before) {
+                Some(tile_before) => {
+                    !tile.is_bot() && !tile_before.is_bot() && tile_before != *tile
+                }
+                None => false,
+            }
+        })
+        .count()
+}
+
+fn outcome<Size: RadarSize>(
+    before: &Snapshot,
+    after: &impl RadarScanTrait<Size>,
+    transform: Transform,
+) -> StepOutcome {
+    let target = transform.translation();
+    if target != Vec2::zero()
+        && before
+            .at(target)
+            .is_some_and(|tile| !tile.is_walkable_terrain())
+    {
+        // the firmware does not walk into walls
+        return StepOutcome::Blocked;
+    }
+    let moved = mismatches(before, after, transform);
+    let stayed = mismatches(before, after, Transform::identity());
+    match (moved, stayed) {
+        (0, 1..) => StepOutcome::Moved,
+        (1.., 0) => StepOutcome::Blocked,
+        _ => StepOutcome::Unknown,
+    }
+}
+
+impl<B: BotBackend> Motor<B> {
+    /// Step forward and check with radar scans before and after whether the step happened. Larger
+    /// scans take longer, but are less often [`StepOutcome::Unknown`].
+    pub async fn step_fw_checked<Size: RadarSize>(
+        &mut self,
+        radar: &mut Radar<B>,
+    ) -> CheckedStep<B, Size> {
+        self.checked(radar, Vec2::new_front(1).into(), Self::try_step_fw)
+            .await
+    }
+    pub async fn step_bw_checked<Size: RadarSize>(
+        &mut self,
+        radar: &mut Radar<B>,
+    ) -> CheckedStep<B, Size> {
+        self.checked(radar, Vec2::new_back(1).into(), Self::try_step_bw)
+            .await
+    }
+    /// Turning can't be blocked, but this still detects if the turn was lost.
+    pub async fn turn_left_checked<Size: RadarSize>(
+        &mut self,
+        radar: &mut Radar<B>,
+    ) -> CheckedStep<B, Size> {
+        self.checked(radar, Rotation::Left.into(), Self::try_turn_left)
+            .await
+    }
+    pub async fn turn_right_checked<Size: RadarSize>(
+        &mut self,
+        radar: &mut Radar<B>,
+    ) -> CheckedStep<B, Size> {
+        self.checked(radar, Rotation::Right.into(), Self::try_turn_right)
+            .await
+    }
+
+    async fn checked<Size: RadarSize>(
+        &mut self,
+        radar: &mut Radar<B>,
+        transform: Transform,
+        action: fn(&mut Self) -> Result<(), NotReady>,
+    ) -> CheckedStep<B, Size> {
+        self.wait().await;
+        let before = Snapshot::new(&radar.scan::<Size>().await);
+        // unwrap: waited above, and the motor is borrowed mutably since
+        action(self).unwrap();
+        let scan = radar.scan::<Size>().await;
+        let outcome = outcome(&before, &scan, transform);
+        CheckedStep {
+            outcome,
+            transform: match outcome {
+                StepOutcome::Moved => Some(transform),
+                StepOutcome::Blocked => Some(Transform::identity()),
+                StepOutcome::Unknown => None,
+            },
+            scan,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use core::{future::poll_fn, marker::PhantomData, task::Poll};
+
+use crate::{BotBackend, Direction, Peripheral, Reactor, cooldown};
+
+use super::error::NotReady;
+
+#[non_exhaustive]
+pub struct Compass<B: BotBackend> {
+    _phantom: PhantomData<B>,
+}
+
+impl<B: BotBackend> Compass<B> {
+    pub(super) fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+
+    pub async fn direction(&mut self) -> Direction {
+        poll_fn(|cx| match self.try_direction() {
+            Ok(direction) => Poll::Ready(direction),
+            Err(NotReady) => {
+                Reactor::<B>::register(Peripheral::Compass, cx.waker());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+    pub fn try_direction(&mut self) -> Result<Direction, NotReady> {
+        let result = B::compass_dir();
+        let direction = match result {
+            0 => return Err(NotReady),
+            1 => Direction::North,
+            2 => Direction::East,
+            3 => Direction::South,
+            4 => Direction::West,
+            _ => unreachable!(),
+        };
+        Reactor::<B>::started(Peripheral::Compass, cooldown::COMPASS);
+        Ok(direction)
+    }
+}
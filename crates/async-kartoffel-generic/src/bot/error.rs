@@ -0,0 +1,43 @@
+#[derive(Debug)]
+pub struct NotReady;
+
+#[derive(Debug)]
+pub struct AccessDenied;
+
+#[derive(Debug, PartialEq)]
+pub enum RadarError {
+    NotReady,
+    AccessBlocked,
+}
+impl From<NotReady> for RadarError {
+    fn from(_: NotReady) -> Self {
+        RadarError::NotReady
+    }
+}
+impl From<AccessDenied> for RadarError {
+    fn from(_: AccessDenied) -> Self {
+        RadarError::AccessBlocked
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryError {
+    NotReady,
+    /// no item in front of the bot
+    NoItem,
+    /// the inventory is at its capacity
+    Full,
+    /// the tile in front of the bot is not empty, nothing can be dropped there
+    Occupied,
+    /// no item of this kind in the inventory
+    NotInInventory,
+    /// the previous pick or drop has not been verified yet
+    Unverified,
+    /// the scan after the pick or drop shows that it did not happen
+    Failed,
+}
+impl From<NotReady> for InventoryError {
+    fn from(_: NotReady) -> Self {
+        InventoryError::NotReady
+    }
+}
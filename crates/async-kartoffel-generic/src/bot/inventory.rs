@@ -0,0 +1,214 @@
+use crate::{BotBackend, D3, ItemKind, RadarScanTrait, RadarSize, Tile, Vec2};
+
+use super::{Arm, Radar, error::InventoryError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pending {
+    Pick(ItemKind),
+    Drop { idx: u8, kind: ItemKind },
+}
+
+/// Keeps track of the items the bot carries, with capacity `N` (at most 256, the firmware indexes
+/// the inventory with a `u8`).
+///
+/// The firmware does not report whether picking or dropping worked, so every pick or drop is
+/// pending until it is verified with a radar scan taken afterwards (see [`Inventory::verify`]),
+/// without moving or turning in between. The order mirrors the inventory of the firmware: picked
+/// items are appended, dropping removes an index. Items are only dropped onto an empty tile, so a
+/// drop is verified by the item appearing there.
+#[derive(Clone, Debug)]
+pub struct Inventory<const N: usize> {
+    items: [ItemKind; N],
+    len: usize,
+    pending: Option<Pending>,
+}
+
+impl<const N: usize> Default for Inventory<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Inventory<N> {
+    pub const fn new() -> Self {
+        const { assert!(N <= 256) };
+        Self {
+            items: [ItemKind::Diamond; N],
+            len: 0,
+            pending: None,
+        }
+    }
+
+    /// verified items, in the order of the firmware inventory
+    pub fn items(&self) -> &[ItemKind] {
+        &self.items[..self.len]
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+    pub fn count(&self, kind: ItemKind) -> usize {
+        self.items().iter().filter(|&&item| item == kind).count()
+    }
+    pub fn contains(&self, kind: ItemKind) -> bool {
+        self.items().contains(&kind)
+    }
+    /// a pick or drop is waiting for [`Inventory::verify`]
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Pick the item in front of the bot, which `scan` has to show.
+    pub fn try_pick<B: BotBackend, Size: RadarSize>(
+        &mut self,
+        arm: &mut Arm<B>,
+        scan: &impl RadarScanTrait<Size>,
+    ) -> Result<ItemKind, InventoryError> {
+        let kind = self.check_pick(scan)?;
+        arm.try_pick()?;
+        self.pending = Some(Pending::Pick(kind));
+        Ok(kind)
+    }
+    pub async fn pick<B: BotBackend, Size: RadarSize>(
+        &mut self,
+        arm: &mut Arm<B>,
+        scan: &impl RadarScanTrait<Size>,
+    ) -> Result<ItemKind, InventoryError> {
+        let kind = self.check_pick(scan)?;
+        arm.pick().await;
+        self.pending = Some(Pending::Pick(kind));
+        Ok(kind)
+    }
+
+    /// Drop an item of this kind in front of the bot, where `scan` has to show an empty tile.
+    pub fn try_drop<B: BotBackend, Size: RadarSize>(
+        &mut self,
+        arm: &mut Arm<B>,
+        scan: &impl RadarScanTrait<Size>,
+        kind: ItemKind,
+    ) -> Result<(), InventoryError> {
+        let idx = self.check_drop(scan, kind)?;
+        arm.try_drop(idx)?;
+        self.pending = Some(Pending::Drop { idx, kind });
+        Ok(())
+    }
+    pub async fn drop<B: BotBackend, Size: RadarSize>(
+        &mut self,
+        arm: &mut Arm<B>,
+        scan: &impl RadarScanTrait<Size>,
+        kind: ItemKind,
+    ) -> Result<(), InventoryError> {
+        let idx = self.check_drop(scan, kind)?;
+        arm.drop(idx).await;
+        self.pending = Some(Pending::Drop { idx, kind });
+        Ok(())
+    }
+
+    /// Confirm or discard the pending pick or drop, using a scan taken after it. Returns `None` if
+    /// nothing was pending, otherwise whether the action succeeded.
+    pub fn verify<Size: RadarSize>(&mut self, scan: &impl RadarScanTrait<Size>) -> Option<bool> {
+        let front = scan.at(Vec2::new_front(1));
+        let success = match self.pending.take()? {
+            Pending::Pick(kind) => {
+                let success = front != Some(kind.to_tile());
+                if success {
+                    self.items[self.len] = kind;
+                    self.len += 1;
+                }
+                success
+            }
+            Pending::Drop { idx, kind } => {
+                // the tile was empty before the drop, see `check_drop`
+                let success = front == Some(kind.to_tile());
+                if success {
+                    self.items
+                        .copy_within(usize::from(idx) + 1..self.len, idx.into());
+                    self.len -= 1;
+                }
+                success
+            }
+        };
+        Some(success)
+    }
+
+    /// Scan, pick the item in front and verify with another scan.
+    pub async fn pick_verified<B: BotBackend>(
+        &mut self,
+        arm: &mut Arm<B>,
+        radar: &mut Radar<B>,
+    ) -> Result<ItemKind, InventoryError> {
+        let scan = radar.scan::<D3>().await;
+        let kind = self.pick(arm, &scan).await?;
+        drop(scan);
+        self.verify_with(radar, kind).await
+    }
+
+    /// Scan, drop an item of this kind and verify with another scan.
+    pub async fn drop_verified<B: BotBackend>(
+        &mut self,
+        arm: &mut Arm<B>,
+        radar: &mut Radar<B>,
+        kind: ItemKind,
+    ) -> Result<ItemKind, InventoryError> {
+        let scan = radar.scan::<D3>().await;
+        self.drop(arm, &scan, kind).await?;
+        drop(scan);
+        self.verify_with(radar, kind).await
+    }
+
+    async fn verify_with<B: BotBackend>(
+        &mut self,
+        radar: &mut Radar<B>,
+        kind: ItemKind,
+    ) -> Result<ItemKind, InventoryError> {
+        let scan = radar.scan::<D3>().await;
+        match self.verify(&scan) {
+            Some(true) => Ok(kind),
+            _ => Err(InventoryError::Failed),
+        }
+    }
+
+    fn check_pick<Size: RadarSize>(
+        &self,
+        scan: &impl RadarScanTrait<Size>,
+    ) -> Result<ItemKind, InventoryError> {
+        if self.is_pending() {
+            return Err(InventoryError::Unverified);
+        }
+        if self.is_full() {
+            return Err(InventoryError::Full);
+        }
+        scan.at(Vec2::new_front(1))
+            .and_then(ItemKind::from_tile)
+            .ok_or(InventoryError::NoItem)
+    }
+
+    fn check_drop<Size: RadarSize>(
+        &self,
+        scan: &impl RadarScanTrait<Size>,
+        kind: ItemKind,
+    ) -> Result<u8, InventoryError> {
+        if self.is_pending() {
+            return Err(InventoryError::Unverified);
+        }
+        if scan.at(Vec2::new_front(1)) != Some(Tile::Empty) {
+            return Err(InventoryError::Occupied);
+        }
+        // the last one, so fewer items have to be shifted
+        let idx = self
+            .items()
+            .iter()
+            .rposition(|&item| item == kind)
+            .ok_or(InventoryError::NotInInventory)?;
+        // unwrap: N is at most 256
+        Ok(u8::try_from(idx).unwrap())
+    }
+}
@@ -0,0 +1,37 @@
+mod arm;
+mod checked;
+mod compass;
+pub mod error;
+mod inventory;
+mod motor;
+mod radar;
+
+pub use arm::Arm;
+pub use checked::{CheckedStep, StepOutcome};
+pub use compass::Compass;
+pub use inventory::Inventory;
+pub use motor::Motor;
+pub use radar::{Radar, RadarScan, RadarScanWeak};
+
+use crate::BotBackend;
+
+pub struct Bot<B: BotBackend> {
+    pub motor: Motor<B>,
+    pub radar: Radar<B>,
+    pub arm: Arm<B>,
+    pub compass: Compass<B>,
+}
+
+impl<B: BotBackend> Bot<B> {
+    /// can be taken exactly once
+    pub fn take() -> Self {
+        let taken = B::with_state(|state| core::mem::replace(&mut state.bot_taken, true));
+        assert!(!taken, "bot can only be taken once");
+        Self {
+            motor: Motor::new(),
+            arm: Arm::new(),
+            compass: Compass::new(),
+            radar: Radar::new(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use core::marker::PhantomData;
+
+use crate::{BotBackend, Peripheral, Reactor, cooldown};
+
+use super::error::NotReady;
+
+#[non_exhaustive]
+pub struct Motor<B: BotBackend> {
+    _phantom: PhantomData<B>,
+}
+
+impl<B: BotBackend> Motor<B> {
+    pub(super) fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        B::is_motor_ready()
+    }
+
+    pub async fn wait(&self) {
+        Reactor::<B>::wait(Peripheral::Motor, || self.is_ready()).await;
+    }
+
+    #[inline(always)]
+    pub fn wait_blocking(&self) {
+        while !self.is_ready() {
+            //
+        }
+    }
+
+    pub fn try_step_fw(&mut self) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::motor_step_fw();
+            Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_STEP_FW);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn step_fw(&mut self) {
+        self.wait().await;
+        B::motor_step_fw();
+        Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_STEP_FW);
+    }
+
+    pub fn try_step_bw(&mut self) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::motor_step_bw();
+            Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_STEP_BW);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn step_bw(&mut self) {
+        self.wait().await;
+        B::motor_step_bw();
+        Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_STEP_BW);
+    }
+
+    pub fn try_turn_left(&mut self) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::motor_turn_left();
+            Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_TURN);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn turn_left(&mut self) {
+        self.wait().await;
+        B::motor_turn_left();
+        Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_TURN);
+    }
+
+    pub fn try_turn_right(&mut self) -> Result<(), NotReady> {
+        if self.is_ready() {
+            B::motor_turn_right();
+            Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_TURN);
+            Ok(())
+        } else {
+            Err(NotReady)
+        }
+    }
+    pub async fn turn_right(&mut self) {
+        self.wait().await;
+        B::motor_turn_right();
+        Reactor::<B>::started(Peripheral::Motor, cooldown::MOTOR_TURN);
+    }
+}
@@ -0,0 +1,262 @@
+use core::{future::poll_fn, marker::PhantomData, num::NonZeroU64, task::Poll};
+
+use crate::{
+    BotBackend, Local, Peripheral, RadarScanTrait, RadarSize, Reactor, Tile, Vec2,
+    backend::RadarGuard, cooldown,
+};
+
+use super::error::RadarError;
+
+#[non_exhaustive]
+pub struct Radar<B: BotBackend> {
+    _phantom: PhantomData<B>,
+}
+
+impl<B: BotBackend> Radar<B> {
+    pub(super) fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+    pub fn is_ready(&self) -> bool {
+        B::is_radar_ready()
+    }
+    pub fn wait_blocking(&self) {
+        while !self.is_ready() {
+            //
+        }
+    }
+    pub async fn wait(&self) {
+        Reactor::<B>::wait(Peripheral::Radar, || self.is_ready()).await;
+    }
+    pub fn try_scan<Size: RadarSize>(&mut self) -> Result<RadarScan<B, Size>, RadarError> {
+        Guard::<B>::try_execute_scan::<Size>()?;
+        Reactor::<B>::started(Peripheral::Radar, cooldown::radar(Size::D));
+        Ok(Guard::<B>::create_active::<Size>())
+    }
+    pub async fn scan<Size: RadarSize>(&mut self) -> RadarScan<B, Size> {
+        self.wait().await;
+        Guard::<B>::wait_unlocked().await;
+        Guard::<B>::try_execute_scan::<Size>().unwrap();
+        Reactor::<B>::started(Peripheral::Radar, cooldown::radar(Size::D));
+        Guard::<B>::create_active::<Size>()
+    }
+}
+
+struct Guard<B: BotBackend>(PhantomData<B>);
+
+impl<B: BotBackend> Guard<B> {
+    fn with_critical_section<T>(f: impl FnOnce(&mut RadarGuard) -> T) -> T {
+        B::with_state(|state| f(&mut state.radar_guard))
+    }
+
+    async fn wait_unlocked() {
+        poll_fn(|cx| {
+            Self::with_critical_section(|guard| {
+                if guard.n_scans == 0 {
+                    Poll::Ready(())
+                } else {
+                    guard.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+
+    // caller has to ensure size is correct
+    fn try_create<Size: RadarSize>(uuid: u32) -> Option<RadarScan<B, Size>> {
+        Self::with_critical_section(|guard| {
+            if uuid == guard.active_uuid {
+                guard.n_scans += 1;
+                Some(RadarScan(PhantomData))
+            } else {
+                None
+            }
+        })
+    }
+    // caller has to ensure size is correct
+    fn create_active<Size: RadarSize>() -> RadarScan<B, Size> {
+        Self::with_critical_section(|guard| {
+            guard.n_scans += 1;
+            RadarScan(PhantomData)
+        })
+    }
+    fn decrease_count() {
+        Self::with_critical_section(|guard| {
+            assert!(guard.n_scans > 0);
+            guard.n_scans -= 1;
+            if guard.n_scans == 0
+                && let Some(waker) = guard.waker.take()
+            {
+                waker.wake()
+            }
+        })
+    }
+    fn try_execute_scan<Size: RadarSize>() -> Result<(), RadarError> {
+        Self::with_critical_section(|guard| {
+            if guard.n_scans == 0 {
+                if B::is_radar_ready() {
+                    B::radar_scan(Size::D);
+
+                    // can fail after u32::MAX iterations
+                    guard.active_uuid = guard.active_uuid.wrapping_add(1);
+                    Ok(())
+                } else {
+                    Err(RadarError::NotReady)
+                }
+            } else {
+                Err(RadarError::AccessBlocked)
+            }
+        })
+    }
+    fn get_active_uuid() -> u32 {
+        Self::with_critical_section(|guard| guard.active_uuid)
+    }
+}
+
+#[non_exhaustive]
+pub struct RadarScan<B: BotBackend, Size: RadarSize>(PhantomData<(B, Size)>);
+
+impl<B: BotBackend, Size: RadarSize> RadarScan<B, Size> {
+    #[inline(always)]
+    fn radar_indices(vec: Vec2<Local>) -> Option<(i8, i8)> {
+        let (dx, dy) = (vec.right(), vec.back());
+        (dx.unsigned_abs() <= Size::R.into() && dy.unsigned_abs() <= Size::R.into())
+            .then_some((dx as i8, dy as i8))
+    }
+    #[inline(always)]
+    fn to_vec(dx: i8, dy: i8) -> Vec2<Local> {
+        Vec2::new_front_right((-dy).into(), dx.into())
+    }
+
+    #[inline(always)]
+    pub fn at_unchecked(&self, dx: i8, dy: i8) -> char {
+        B::radar_read(Size::D, dx, dy, 0) as u8 as char
+    }
+
+    /// generate weak (does not block new scans) reference
+    pub fn weak(&self) -> RadarScanWeak<B, Size> {
+        RadarScanWeak {
+            uuid: Guard::<B>::get_active_uuid(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> RadarScanTrait<Size> for RadarScan<B, Size> {
+    fn contains(&self, vec: Vec2<Local>) -> bool {
+        Self::radar_indices(vec).is_some()
+    }
+
+    fn at(&self, vec: Vec2<Local>) -> Option<Tile> {
+        if let Some((dx, dy)) = Self::radar_indices(vec) {
+            // unwrap: unknown tile means error
+            Some(Tile::from_char(self.at_unchecked(dx, dy)).expect("encountered unknown tile"))
+        } else {
+            None
+        }
+    }
+
+    fn bot_at(&self, vec: Vec2<Local>) -> Option<NonZeroU64> {
+        if let Some((dx, dy)) = Self::radar_indices(vec) {
+            let d1 = B::radar_read(Size::D, dx, dy, 1) as u64;
+            let d2 = B::radar_read(Size::D, dx, dy, 2) as u64;
+            NonZeroU64::new((d1 << 32) | d2)
+        } else {
+            None
+        }
+    }
+
+    /// Scanned tiles matching tile excluding (0, 0), this is e.g. useful to find only enemy bots
+    fn iter_tile(&self, tile: Tile) -> impl Iterator<Item = Vec2<Local>> + use<'_, B, Size> {
+        Size::range().flat_map(move |dx| {
+            Size::range()
+                .filter(move |dy| {
+                    self.at_unchecked(dx, *dy) == tile.to_char() && !(dx == 0 && *dy == 0)
+                })
+                .map(move |dy| Self::to_vec(dx, dy))
+        })
+    }
+
+    /// iterate over scanned tiles excluding (0, 0)
+    fn iter(&self) -> impl Iterator<Item = (Vec2<Local>, Tile)> + use<'_, B, Size> {
+        Size::range().flat_map(move |dx| {
+            Size::range()
+                .filter(move |dy| !(dx == 0 && *dy == 0))
+                .map(move |dy| {
+                    (
+                        Self::to_vec(dx, dy),
+                        Tile::from_char(self.at_unchecked(dx, dy)).unwrap(),
+                    )
+                })
+        })
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> Clone for RadarScan<B, Size> {
+    fn clone(&self) -> Self {
+        Guard::<B>::create_active()
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> Drop for RadarScan<B, Size> {
+    fn drop(&mut self) {
+        Guard::<B>::decrease_count();
+    }
+}
+
+/// A version of a radar scan that does not block new scans, but can't be used directly. Instead,
+/// you can try to upgrade it to a full [`RadarScan`]
+pub struct RadarScanWeak<B: BotBackend, Size: RadarSize> {
+    uuid: u32,
+    _phantom: PhantomData<(B, Size)>,
+}
+
+impl<B: BotBackend, Size: RadarSize> Clone for RadarScanWeak<B, Size> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> Copy for RadarScanWeak<B, Size> {}
+
+impl<B: BotBackend, Size: RadarSize> Eq for RadarScanWeak<B, Size> {}
+
+impl<B: BotBackend, Size: RadarSize> PartialEq for RadarScanWeak<B, Size> {
+    fn eq(&self, other: &Self) -> bool {
+        self.uuid == other.uuid
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> PartialOrd for RadarScanWeak<B, Size> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> Ord for RadarScanWeak<B, Size> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.uuid.cmp(&other.uuid)
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> core::hash::Hash for RadarScanWeak<B, Size> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.uuid.hash(state);
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> core::fmt::Debug for RadarScanWeak<B, Size> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RadarScanWeak")
+            .field("uuid", &self.uuid)
+            .finish()
+    }
+}
+
+impl<B: BotBackend, Size: RadarSize> RadarScanWeak<B, Size> {
+    pub fn upgrade(&self) -> Option<RadarScan<B, Size>> {
+        Guard::<B>::try_create(self.uuid)
+    }
+}
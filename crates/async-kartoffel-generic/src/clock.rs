@@ -10,6 +10,14 @@ pub trait ClockBackend {
     fn ticks_per_milli() -> u32;
 }
 
+/// A point in time of the clock `C`.
+///
+/// The tick counter of the clock has 32 bits and wraps around, e.g. after about 18.6 hours on the
+/// bot with 64 ticks per millisecond. All comparisons and arithmetic are wrapping-aware, they are
+/// correct as long as the involved instants are less than [`Instant::MAX_SPAN`] ticks apart.
+///
+/// This is not a total order, so `Instant` doesn't implement [`Ord`]. Use the explicit
+/// [`Instant::cmp_wrapping`] and [`Instant::is_after`] instead.
 pub struct Instant<C: ClockBackend> {
     ticks: u32,
     _phantom: PhantomData<C>,
@@ -45,18 +53,6 @@ impl<C: ClockBackend> PartialEq for Instant<C> {
     }
 }
 
-impl<C: ClockBackend> PartialOrd for Instant<C> {
-    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl<C: ClockBackend> Ord for Instant<C> {
-    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        self.ticks.cmp(&other.ticks)
-    }
-}
-
 impl<C: ClockBackend> core::fmt::Display for Instant<C> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "t={}", self.ticks)
@@ -64,12 +60,36 @@ impl<C: ClockBackend> core::fmt::Display for Instant<C> {
 }
 
 impl<C: ClockBackend> Instant<C> {
+    /// Maximum distance in ticks between two instants that can be compared
+    pub const MAX_SPAN: u32 = i32::MAX as u32;
+
     pub fn now() -> Self {
+        Self::from_ticks(C::now())
+    }
+
+    pub fn from_ticks(ticks: u32) -> Self {
         Self {
-            ticks: C::now(),
+            ticks,
             _phantom: PhantomData,
         }
     }
+
+    pub fn as_ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Compares across the wrap-around of the clock: `self` is later than `other` if it is less
+    /// than [`Self::MAX_SPAN`] ticks ahead. This is only consistent for instants within
+    /// [`Self::MAX_SPAN`] of each other, e.g. it is not transitive for instants spread over the
+    /// whole range of the clock.
+    pub fn cmp_wrapping(&self, other: &Self) -> core::cmp::Ordering {
+        (self.ticks.wrapping_sub(other.ticks) as i32).cmp(&0)
+    }
+
+    /// `self` is strictly later than `other`, see [`Self::cmp_wrapping`]
+    pub fn is_after(&self, other: &Self) -> bool {
+        self.cmp_wrapping(other).is_gt()
+    }
 }
 
 pub struct Duration<C: ClockBackend> {
@@ -180,7 +200,7 @@ impl<C: ClockBackend> Add<Duration<C>> for Instant<C> {
 
     fn add(self, rhs: Duration<C>) -> Self::Output {
         Instant {
-            ticks: self.ticks + rhs.ticks,
+            ticks: self.ticks.wrapping_add(rhs.ticks),
             _phantom: PhantomData,
         }
     }
@@ -192,13 +212,14 @@ impl<C: ClockBackend> AddAssign<Duration<C>> for Instant<C> {
     }
 }
 
+/// `None` if `rhs` is later than `self`
 impl<C: ClockBackend> Sub for Instant<C> {
     type Output = Option<Duration<C>>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        if self.ticks >= rhs.ticks {
+        if !rhs.is_after(&self) {
             Some(Duration {
-                ticks: self.ticks - rhs.ticks,
+                ticks: self.ticks.wrapping_sub(rhs.ticks),
                 _phantom: PhantomData,
             })
         } else {
@@ -277,7 +298,7 @@ impl<C: ClockBackend> Timer<C> {
     }
 
     pub fn wait_blocking(self) {
-        while Instant::now() < self.expires_at {
+        while self.expires_at.is_after(&Instant::now()) {
             //
         }
     }
@@ -288,7 +309,7 @@ impl<C: ClockBackend> Unpin for Timer<C> {}
 impl<C: ClockBackend> Future for Timer<C> {
     type Output = ();
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if self.yielded_once && self.expires_at <= Instant::now() {
+        if self.yielded_once && !self.expires_at.is_after(&Instant::now()) {
             Poll::Ready(())
         } else {
             // TODO currently instantly schedule another poll, schedule wake further into future
@@ -298,3 +319,97 @@ impl<C: ClockBackend> Future for Timer<C> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        future::Future,
+        pin::pin,
+        sync::atomic::{AtomicU32, Ordering},
+        task::{Context, Poll, Waker},
+    };
+
+    use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+    use super::*;
+
+    static NOW: AtomicU32 = AtomicU32::new(0);
+
+    enum TestClock {}
+    impl ClockBackend for TestClock {
+        fn now() -> u32 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn ticks_per_milli() -> u32 {
+            64
+        }
+    }
+
+    fn instant(ticks: u32) -> Instant<TestClock> {
+        Instant::from_ticks(ticks)
+    }
+
+    #[test]
+    fn ordering_across_wrap() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..100_000 {
+            // bias towards the boundary
+            let base = if rng.random() {
+                u32::MAX - rng.random_range(0..1 << 20)
+            } else {
+                rng.random()
+            };
+            let a = rng.random_range(0..1 << 30);
+            let b = rng.random_range(0..1 << 30);
+            let (ia, ib) = (instant(base.wrapping_add(a)), instant(base.wrapping_add(b)));
+
+            assert_eq!(ia.cmp_wrapping(&ib), a.cmp(&b));
+            assert_eq!(ib.is_after(&ia), a < b);
+            if a >= b {
+                assert_eq!((ia - ib).map(|d| d.as_ticks()), Some(a - b));
+            } else {
+                assert_eq!(ia - ib, None);
+            }
+
+            let d = Duration::from_ticks(b);
+            assert_eq!(ia + d - ia, Some(d));
+            assert!(!ia.is_after(&(ia + d)));
+        }
+    }
+
+    #[test]
+    fn not_transitive() {
+        // a third of the clock apart each, so every instant is after the previous one
+        let third = u32::MAX / 3;
+        let (a, b, c) = (instant(0), instant(third), instant(2 * third));
+        assert!(b.is_after(&a) && c.is_after(&b) && a.is_after(&c));
+    }
+
+    #[test]
+    fn timer_near_wrap() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut cx = Context::from_waker(Waker::noop());
+        for _ in 0..1_000 {
+            let start = u32::MAX - rng.random_range(0..100_000);
+            let after = rng.random_range(0..200_000);
+            NOW.store(start, Ordering::Relaxed);
+
+            let mut timer = pin!(Timer::<TestClock>::after_ticks(after));
+            // always yields once
+            assert_eq!(timer.as_mut().poll(&mut cx), Poll::Pending);
+            loop {
+                let elapsed = TestClock::now().wrapping_sub(start);
+                let poll = timer.as_mut().poll(&mut cx);
+                assert_eq!(
+                    poll.is_ready(),
+                    elapsed >= after,
+                    "{start} {after} {elapsed}"
+                );
+                if poll.is_ready() {
+                    break;
+                }
+                NOW.fetch_add(rng.random_range(1..5_000), Ordering::Relaxed);
+            }
+        }
+    }
+}
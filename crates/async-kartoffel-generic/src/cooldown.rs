@@ -0,0 +1,33 @@
+//! Nominal cooldowns of the bot peripherals in timer ticks. The actual cooldowns of the game vary
+//! randomly by about ±15%.
+
+pub const MOTOR_STEP_FW: u32 = 20_000;
+pub const MOTOR_STEP_BW: u32 = 30_000;
+pub const MOTOR_TURN: u32 = 25_000;
+
+pub const ARM_STAB: u32 = 60_000;
+pub const ARM_PICK: u32 = 60_000;
+pub const ARM_DROP: u32 = 60_000;
+
+pub const RADAR_D3: u32 = 10_000;
+pub const RADAR_D5: u32 = 15_000;
+pub const RADAR_D7: u32 = 22_000;
+pub const RADAR_D9: u32 = 30_000;
+
+/// the compass provides a new direction every so often
+pub const COMPASS: u32 = 128_000;
+
+/// Shortest possible duration of a `cooldown`, given the random variation.
+pub const fn min(cooldown: u32) -> u32 {
+    cooldown / 100 * 85
+}
+
+/// radar cooldown for a scan of size `d` by `d`
+pub const fn radar(d: u8) -> u32 {
+    match d {
+        3 => RADAR_D3,
+        5 => RADAR_D5,
+        7 => RADAR_D7,
+        _ => RADAR_D9,
+    }
+}
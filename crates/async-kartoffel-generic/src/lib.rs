@@ -1,12 +1,22 @@
 #![no_std]
 
+mod backend;
+mod bot;
 mod clock;
+pub mod cooldown;
 mod radar;
+mod reactor;
 mod world;
 
+pub use backend::{BackendState, BotBackend};
+pub use bot::error::{AccessDenied, InventoryError, NotReady, RadarError};
+pub use bot::{
+    Arm, Bot, CheckedStep, Compass, Inventory, Motor, Radar, RadarScan, RadarScanWeak, StepOutcome,
+};
 pub use clock::{ClockBackend, Duration, Instant, Timer};
 pub use radar::{D3, D5, D7, D9, RadarScanTrait, RadarSize};
+pub use reactor::{MAX_WAITERS, Peripheral, RECHECK, Reactor};
 pub use world::{
-    AnchorDefault, Coords, Direction, Global, Local, Position, PositionAnchor, Rotation, Tile,
-    Transform, Vec2,
+    AnchorDefault, Coords, Direction, Global, ItemKind, Local, Position, PositionAnchor, Rotation,
+    Tile, Transform, Vec2,
 };
@@ -0,0 +1,245 @@
+use core::{
+    future::poll_fn,
+    marker::PhantomData,
+    task::{Poll, Waker},
+};
+
+use crate::{BotBackend, ClockBackend, cooldown};
+
+/// Interval in ticks in which a peripheral is polled again once its expected ready time has passed,
+/// but it is still not ready.
+pub const RECHECK: u32 = 1_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Peripheral {
+    Motor,
+    Arm,
+    Radar,
+    Compass,
+}
+
+impl Peripheral {
+    const fn index(self) -> usize {
+        match self {
+            Peripheral::Motor => 0,
+            Peripheral::Arm => 1,
+            Peripheral::Radar => 2,
+            Peripheral::Compass => 3,
+        }
+    }
+}
+
+/// Maximum number of tasks waiting for the same peripheral at the same time. If there are more,
+/// further wakers are woken immediately, which is correct but causes extra polls.
+pub const MAX_WAITERS: usize = 4;
+
+#[derive(Debug)]
+struct Slot {
+    /// earliest tick at which the peripheral might be ready
+    ready_at: Option<u32>,
+    /// tick at which `wakers` should be woken
+    deadline: u32,
+    wakers: [Option<Waker>; MAX_WAITERS],
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            ready_at: None,
+            deadline: 0,
+            wakers: [const { None }; MAX_WAITERS],
+        }
+    }
+
+    fn is_awaited(&self) -> bool {
+        self.wakers.iter().any(Option::is_some)
+    }
+
+    /// Add `waker`, unless it is registered already. Returns it back if there is no space left.
+    fn add(&mut self, waker: &Waker) -> Option<Waker> {
+        if self.wakers.iter().flatten().any(|w| w.will_wake(waker)) {
+            return None;
+        }
+        match self.wakers.iter_mut().find(|w| w.is_none()) {
+            Some(free) => {
+                *free = Some(waker.clone());
+                None
+            }
+            None => Some(waker.clone()),
+        }
+    }
+}
+
+/// Readiness state of all peripherals, stored in [`BackendState`](crate::BackendState).
+#[derive(Debug)]
+pub(crate) struct ReactorState {
+    slots: [Slot; 4],
+    /// the task in [`Reactor::sleep_until`]
+    sleeper: Option<Waker>,
+    /// deadline of the sleeping task, `None` if it sleeps until it is interrupted
+    sleep_deadline: Option<u32>,
+    /// an earlier deadline was registered during the sleep
+    interrupted: bool,
+}
+
+impl ReactorState {
+    pub(crate) const fn new() -> Self {
+        Self {
+            slots: [Slot::new(), Slot::new(), Slot::new(), Slot::new()],
+            sleeper: None,
+            sleep_deadline: None,
+            interrupted: false,
+        }
+    }
+
+    /// Interrupt the sleep if `deadline` is earlier than its deadline, returns the waker to wake.
+    fn interrupt(&mut self, deadline: u32) -> Option<Waker> {
+        if self.sleeper.is_some()
+            && self
+                .sleep_deadline
+                .is_none_or(|sleep_deadline| !reached(deadline, sleep_deadline))
+        {
+            self.interrupted = true;
+            self.sleeper.take()
+        } else {
+            None
+        }
+    }
+}
+
+/// `a` is at or after `b`, correct across the wrap of the clock as long as both are less than
+/// `u32::MAX / 2` apart
+#[inline(always)]
+fn reached(a: u32, b: u32) -> bool {
+    a.wrapping_sub(b) as i32 >= 0
+}
+
+/// Wakes futures waiting for peripherals once their cooldown has passed.
+///
+/// The futures of [`Motor`](crate::Motor), [`Arm`](crate::Arm), [`Radar`](crate::Radar) and
+/// [`Compass`](crate::Compass) don't wake themselves while they wait. Instead, every action records
+/// when the peripheral will be ready again (the nominal cooldown minus the random variation, see
+/// [`cooldown`]) and the future registers its waker with that deadline. Several futures may wait
+/// for the same peripheral (up to [`MAX_WAITERS`]). A task of the bot crate (e.g.
+/// `async_kartoffel::drive`) has to call [`Reactor::wake_expired`] and [`Reactor::sleep_until`] in
+/// a loop, so the registered futures are woken when their deadline is reached.
+pub struct Reactor<B: BotBackend>(PhantomData<B>);
+
+impl<B: BotBackend> Reactor<B> {
+    /// Record that an action with the nominal `cooldown` was just started on `peripheral`.
+    pub fn started(peripheral: Peripheral, cooldown: u32) {
+        let ready_at = B::Clock::now().wrapping_add(cooldown::min(cooldown));
+        B::with_state(|state| state.reactor.slots[peripheral.index()].ready_at = Some(ready_at));
+    }
+
+    /// Register `waker` to be woken once `peripheral` is expected to be ready.
+    pub fn register(peripheral: Peripheral, waker: &Waker) {
+        let now = B::Clock::now();
+        let (overflow, sleeper) = B::with_state(|state| {
+            let slot = &mut state.reactor.slots[peripheral.index()];
+            slot.deadline = match slot.ready_at {
+                Some(ready_at) if !reached(now, ready_at) => ready_at,
+                // cooldown unknown or longer than expected
+                _ => now.wrapping_add(RECHECK),
+            };
+            let deadline = slot.deadline;
+            (slot.add(waker), state.reactor.interrupt(deadline))
+        });
+        // wake outside of with_state, the waker might access the state as well
+        for waker in [overflow, sleeper].into_iter().flatten() {
+            waker.wake();
+        }
+    }
+
+    /// Wake all futures whose deadline has been reached. Returns the earliest deadline still
+    /// pending.
+    pub fn wake_expired() -> Option<u32> {
+        let now = B::Clock::now();
+        let mut next: Option<u32> = None;
+        for i in 0..4 {
+            let wakers = B::with_state(|state| {
+                let slot = &mut state.reactor.slots[i];
+                if slot.is_awaited() && reached(now, slot.deadline) {
+                    Some(core::mem::replace(
+                        &mut slot.wakers,
+                        [const { None }; MAX_WAITERS],
+                    ))
+                } else {
+                    if slot.is_awaited() && next.is_none_or(|next| reached(next, slot.deadline)) {
+                        next = Some(slot.deadline);
+                    }
+                    None
+                }
+            });
+            // wake outside of with_state, the waker might access the state as well
+            for waker in wakers.into_iter().flatten().flatten() {
+                waker.wake();
+            }
+        }
+        next
+    }
+
+    /// Wait until `deadline` is reached, or until a future registers an earlier one (see
+    /// [`Self::interrupt_sleep`]). Without a deadline, only the latter ends the sleep.
+    ///
+    /// The bot has no timer interrupt, so while waiting for a deadline the task still wakes itself
+    /// on every poll and reads the clock: the busy waiting of the peripheral futures is moved here,
+    /// not removed. The sleep lasts at most [`RECHECK`] ticks, after that the caller computes the
+    /// deadlines again. Without a deadline the task is not polled at all.
+    pub async fn sleep_until(deadline: Option<u32>) {
+        let chunk_end = B::Clock::now().wrapping_add(RECHECK);
+        let deadline = deadline.map(|deadline| match reached(deadline, chunk_end) {
+            true => chunk_end,
+            false => deadline,
+        });
+        B::with_state(|state| {
+            state.reactor.sleep_deadline = deadline;
+            state.reactor.interrupted = false;
+        });
+        poll_fn(|cx| {
+            if deadline.is_some_and(|deadline| reached(B::Clock::now(), deadline)) {
+                return Poll::Ready(());
+            }
+            let interrupted = B::with_state(|state| {
+                let reactor = &mut state.reactor;
+                match &reactor.sleeper {
+                    Some(sleeper) if sleeper.will_wake(cx.waker()) => {}
+                    _ if reactor.interrupted => {}
+                    _ => reactor.sleeper = Some(cx.waker().clone()),
+                }
+                reactor.interrupted
+            });
+            if interrupted {
+                Poll::Ready(())
+            } else {
+                if deadline.is_some() {
+                    cx.waker().wake_by_ref();
+                }
+                Poll::Pending
+            }
+        })
+        .await;
+        B::with_state(|state| state.reactor.sleeper = None);
+    }
+
+    /// End [`Self::sleep_until`] early if `deadline` comes before the one it sleeps until, e.g.
+    /// because another source of deadlines got a new one. Registering a waker does this already.
+    pub fn interrupt_sleep(deadline: u32) {
+        if let Some(sleeper) = B::with_state(|state| state.reactor.interrupt(deadline)) {
+            sleeper.wake();
+        }
+    }
+
+    /// Wait until `is_ready` returns true, registered as `peripheral`.
+    pub(crate) async fn wait(peripheral: Peripheral, is_ready: impl Fn() -> bool) {
+        poll_fn(|cx| {
+            if is_ready() {
+                Poll::Ready(())
+            } else {
+                Self::register(peripheral, cx.waker());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
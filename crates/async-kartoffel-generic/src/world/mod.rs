@@ -6,6 +6,6 @@ mod vec2;
 
 pub use direction::{Direction, Rotation};
 pub use position::{AnchorDefault, Position, PositionAnchor};
-pub use tile::Tile;
+pub use tile::{ItemKind, Tile};
 pub use transform::Transform;
 pub use vec2::{Coords, Global, Local, Vec2};
@@ -17,7 +17,10 @@ use super::{
 };
 
 /// anchor is used to differentiate between different definitions of the (0, 0) position
+///
+/// The layout is fixed (east, then south), so positions can be viewed in place in binary data.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Debug, Hash)]
+#[repr(C)]
 pub struct Position<Anchor: PositionAnchor = AnchorDefault> {
     east: i16,
     south: i16,
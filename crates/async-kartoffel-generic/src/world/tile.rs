@@ -57,3 +57,26 @@ impl Tile {
         matches!(self, Self::Diamond | Self::Flag)
     }
 }
+
+/// The tiles that can be picked up and end up in the inventory.
+#[derive(PartialEq, Clone, Ord, PartialOrd, Eq, Debug, Copy, Hash)]
+pub enum ItemKind {
+    Diamond,
+    Flag,
+}
+
+impl ItemKind {
+    pub const fn from_tile(tile: Tile) -> Option<Self> {
+        match tile {
+            Tile::Diamond => Some(Self::Diamond),
+            Tile::Flag => Some(Self::Flag),
+            _ => None,
+        }
+    }
+    pub const fn to_tile(self) -> Tile {
+        match self {
+            Self::Diamond => Tile::Diamond,
+            Self::Flag => Tile::Flag,
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use async_kartoffel_generic::{BackendState, BotBackend, Rotation, Vec2, cooldown};
+
+use crate::{HostClock, World, world::STATE};
+
+/// [`BotBackend`] operating on the [`World`] installed in the current thread.
+pub enum HostBackend {}
+
+impl BotBackend for HostBackend {
+    type Clock = HostClock;
+
+    fn is_motor_ready() -> bool {
+        World::with(|world| world.is_motor_ready())
+    }
+    fn motor_step_fw() {
+        World::with(|world| world.step(Vec2::new_front(1), cooldown::MOTOR_STEP_FW))
+    }
+    fn motor_step_bw() {
+        World::with(|world| world.step(Vec2::new_back(1), cooldown::MOTOR_STEP_BW))
+    }
+    fn motor_turn_left() {
+        World::with(|world| world.turn(Rotation::Left))
+    }
+    fn motor_turn_right() {
+        World::with(|world| world.turn(Rotation::Right))
+    }
+
+    fn is_arm_ready() -> bool {
+        World::with(|world| world.is_arm_ready())
+    }
+    fn arm_stab() {
+        World::with(|world| world.stab())
+    }
+    fn arm_pick() {
+        World::with(|world| world.pick())
+    }
+    fn arm_drop(idx: u8) {
+        World::with(|world| world.drop_item(idx))
+    }
+
+    fn is_radar_ready() -> bool {
+        World::with(|world| world.is_radar_ready())
+    }
+    fn radar_scan(d: u8) {
+        World::with(|world| world.radar_scan(d))
+    }
+    fn radar_read(d: u8, dx: i8, dy: i8, z: u8) -> u32 {
+        World::with(|world| world.radar_read(d, dx, dy, z))
+    }
+
+    fn compass_dir() -> u32 {
+        World::with(|world| world.compass_dir())
+    }
+
+    fn with_state<T>(f: impl FnOnce(&mut BackendState) -> T) -> T {
+        STATE.with(|state| f(&mut state.borrow_mut()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::{
+        D3, D5, D7, D9, Direction, RadarError, RadarScanTrait, RadarSize, Tile, Vec2,
+    };
+    use core::{
+        sync::atomic::{AtomicUsize, Ordering},
+        task::Waker,
+    };
+    use std::{sync::Arc, task::Wake};
+
+    use kartoffel_gps::pos::pos_east_south;
+    use kartoffel_gps_builder::map::Map;
+
+    use crate::{Bot, Radar, RadarScan, World, block_on};
+
+    /// counts how often its waker was woken
+    struct Counter(AtomicUsize);
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn counting_waker() -> (Arc<Counter>, Waker) {
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        (counter.clone(), Waker::from(counter))
+    }
+
+    /// open 9 by 9 area surrounded by walls
+    fn open_world() -> World {
+        let (width, height) = (11, 11);
+        let tiles = (0..width * height)
+            .map(|i| {
+                (1..width - 1).contains(&(i % width)) && (1..height - 1).contains(&(i / width))
+            })
+            .collect();
+        World::new(
+            Map {
+                tiles,
+                width,
+                height,
+            },
+            pos_east_south(5, 5),
+            Direction::North,
+        )
+    }
+
+    #[test]
+    fn guard() {
+        open_world().install();
+        let mut radar: Radar = Bot::take().radar;
+
+        radar.wait_blocking();
+        let scan: RadarScan<D3> = radar.try_scan().unwrap();
+
+        block_on(radar.wait());
+        assert_eq!(
+            radar.try_scan::<D3>().err(),
+            Some(RadarError::AccessBlocked)
+        );
+
+        drop(scan);
+        let scan: RadarScan<D3> = radar.try_scan().unwrap();
+
+        let weak = scan.weak();
+        drop(scan);
+        let scan = weak.upgrade().unwrap();
+        drop(scan);
+
+        block_on(radar.wait());
+        let scan: RadarScan<D3> = radar.try_scan().unwrap();
+        drop(scan);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn iterators() {
+        fn test_iter<Size: RadarSize>(radar: &mut Radar) {
+            let scan: RadarScan<Size> = block_on(radar.scan());
+            let n_tiles = (Size::D * Size::D - 1) as usize;
+            assert_eq!(scan.iter().count(), n_tiles);
+            let mut tiles = [[false; 9]; 9];
+            for (vec, _) in scan.iter() {
+                let dist_max = vec.front().unsigned_abs().max(vec.right().unsigned_abs());
+                assert!(dist_max <= Size::R.into());
+
+                tiles[(4 + vec.right()) as usize][(4 + vec.front()) as usize] = true;
+            }
+            assert_eq!(
+                tiles
+                    .iter()
+                    .map(|row| row.iter().filter(|&&b| b).count())
+                    .sum::<usize>(),
+                n_tiles
+            );
+        }
+
+        open_world().install();
+        let mut radar = Bot::take().radar;
+        test_iter::<D3>(&mut radar);
+        test_iter::<D5>(&mut radar);
+        test_iter::<D7>(&mut radar);
+        test_iter::<D9>(&mut radar);
+    }
+
+    #[test]
+    fn scan_and_move() {
+        let mut world = open_world();
+        let id = world.add_bot(pos_east_south(7, 3), Direction::South);
+        world.install();
+        let mut bot = Bot::take();
+
+        // walk into the north wall, facing stays north
+        block_on(async {
+            for _ in 0..5 {
+                bot.motor.step_fw().await;
+            }
+        });
+        assert_eq!(World::with(|world| world.me().pos), pos_east_south(5, 1));
+        assert_eq!(bot.compass.try_direction().unwrap(), Direction::North);
+
+        block_on(bot.motor.turn_right());
+        let scan = block_on(bot.radar.scan::<D5>());
+        assert_eq!(scan.at(Vec2::new_front(0)), Some(Tile::Bot));
+        assert_eq!(scan.at(Vec2::new_left(1)), Some(Tile::WallCave));
+        assert_eq!(scan.at(Vec2::new_front_right(2, 2)), Some(Tile::Bot));
+        assert_eq!(scan.bot_at(Vec2::new_front_right(2, 2)), Some(id));
+        assert_eq!(scan.iter_tile(Tile::Bot).count(), 1);
+    }
+
+    #[test]
+    fn reactor() {
+        use core::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll},
+        };
+
+        use async_kartoffel_generic::{RECHECK, Reactor, cooldown};
+
+        use crate::{HostBackend, HostClock};
+
+        open_world().install();
+        let mut bot = Bot::take();
+        let (counter, waker) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        bot.motor.try_step_fw().unwrap();
+        let mut wait = pin!(bot.motor.wait());
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 0, "must not wake itself");
+
+        let deadline = Reactor::<HostBackend>::wake_expired().unwrap();
+        assert_eq!(deadline, cooldown::min(cooldown::MOTOR_STEP_FW));
+
+        HostClock::set(deadline - 1);
+        Reactor::<HostBackend>::wake_expired();
+        assert_eq!(counter.0.load(Ordering::Relaxed), 0);
+
+        HostClock::set(deadline);
+        assert_eq!(Reactor::<HostBackend>::wake_expired(), None);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+
+        // cooldown longer than expected, check again later
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(
+            Reactor::<HostBackend>::wake_expired(),
+            Some(deadline + RECHECK)
+        );
+        HostClock::set(cooldown::MOTOR_STEP_FW);
+        Reactor::<HostBackend>::wake_expired();
+        assert_eq!(counter.0.load(Ordering::Relaxed), 2);
+        assert_eq!(wait.as_mut().poll(&mut cx), Poll::Ready(()));
+
+        // registering for another peripheral is independent
+        bot.compass.try_direction().unwrap();
+        let mut direction = pin!(bot.compass.direction());
+        assert!(direction.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(
+            Reactor::<HostBackend>::wake_expired(),
+            Some(cooldown::MOTOR_STEP_FW + cooldown::min(cooldown::COMPASS))
+        );
+    }
+
+    #[test]
+    fn reactor_waiters_and_sleep() {
+        use core::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll},
+        };
+
+        use async_kartoffel_generic::{RECHECK, Reactor, cooldown};
+
+        use crate::{HostBackend, HostClock};
+
+        let count = |counter: &Counter| counter.0.load(Ordering::Relaxed);
+
+        open_world().install();
+        let mut bot = Bot::take();
+        let (counter_a, waker_a) = counting_waker();
+        let (counter_b, waker_b) = counting_waker();
+        let (counter_sleep, waker_sleep) = counting_waker();
+        let mut cx_sleep = Context::from_waker(&waker_sleep);
+
+        // nothing is awaited, sleep without polling
+        let mut sleep = pin!(Reactor::<HostBackend>::sleep_until(None));
+        assert!(sleep.as_mut().poll(&mut cx_sleep).is_pending());
+        assert_eq!(count(&counter_sleep), 0, "must not wake itself");
+
+        // two tasks wait for the motor, the first deadline ends the sleep
+        bot.motor.try_step_fw().unwrap();
+        let mut wait_a = pin!(bot.motor.wait());
+        let mut wait_b = pin!(bot.motor.wait());
+        assert!(
+            wait_a
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker_a))
+                .is_pending()
+        );
+        assert_eq!(count(&counter_sleep), 1);
+        assert_eq!(sleep.as_mut().poll(&mut cx_sleep), Poll::Ready(()));
+        assert!(
+            wait_b
+                .as_mut()
+                .poll(&mut Context::from_waker(&waker_b))
+                .is_pending()
+        );
+
+        let deadline = Reactor::<HostBackend>::wake_expired().unwrap();
+        assert_eq!(deadline, cooldown::min(cooldown::MOTOR_STEP_FW));
+        // the sleep ends after RECHECK ticks, the deadline is further away
+        let mut sleep = pin!(Reactor::<HostBackend>::sleep_until(Some(deadline)));
+        assert!(sleep.as_mut().poll(&mut cx_sleep).is_pending());
+        HostClock::set(RECHECK);
+        assert_eq!(sleep.as_mut().poll(&mut cx_sleep), Poll::Ready(()));
+        HostClock::set(deadline - RECHECK / 2);
+        let mut sleep = pin!(Reactor::<HostBackend>::sleep_until(Some(deadline)));
+        assert!(sleep.as_mut().poll(&mut cx_sleep).is_pending());
+        HostClock::set(deadline);
+        assert_eq!(sleep.as_mut().poll(&mut cx_sleep), Poll::Ready(()));
+
+        // both waiters are woken
+        assert_eq!(Reactor::<HostBackend>::wake_expired(), None);
+        assert_eq!((count(&counter_a), count(&counter_b)), (1, 1));
+    }
+
+    #[test]
+    fn inventory() {
+        use async_kartoffel_generic::{Inventory, InventoryError, ItemKind};
+
+        let mut world = open_world();
+        world.set_item(pos_east_south(5, 4), Some(Tile::Diamond));
+        world.set_item(pos_east_south(6, 5), Some(Tile::Flag));
+        world.install();
+        let mut bot = Bot::take();
+        let mut inventory = Inventory::<2>::new();
+
+        let picked = block_on(inventory.pick_verified(&mut bot.arm, &mut bot.radar));
+        assert_eq!(picked, Ok(ItemKind::Diamond));
+        assert_eq!(
+            block_on(inventory.pick_verified(&mut bot.arm, &mut bot.radar)),
+            Err(InventoryError::NoItem)
+        );
+
+        block_on(bot.motor.turn_right());
+        let scan = block_on(bot.radar.scan::<D3>());
+        assert_eq!(
+            block_on(inventory.pick(&mut bot.arm, &scan)),
+            Ok(ItemKind::Flag)
+        );
+        assert_eq!(
+            inventory.try_drop(&mut bot.arm, &scan, ItemKind::Flag),
+            Err(InventoryError::Unverified)
+        );
+        drop(scan);
+        assert_eq!(inventory.items(), [ItemKind::Diamond]);
+        let scan = block_on(bot.radar.scan::<D3>());
+        assert_eq!(inventory.verify(&scan), Some(true));
+        assert_eq!(inventory.verify(&scan), None);
+        drop(scan);
+        assert_eq!(inventory.items(), [ItemKind::Diamond, ItemKind::Flag]);
+        assert!(inventory.is_full());
+        assert_eq!(inventory.count(ItemKind::Flag), 1);
+        assert_eq!(
+            World::with(|world| world.inventory().to_vec()),
+            [Tile::Diamond, Tile::Flag]
+        );
+
+        let dropped =
+            block_on(inventory.drop_verified(&mut bot.arm, &mut bot.radar, ItemKind::Diamond));
+        assert_eq!(dropped, Ok(ItemKind::Diamond));
+        assert_eq!(inventory.items(), [ItemKind::Flag]);
+        assert_eq!(
+            World::with(|world| world.inventory().to_vec()),
+            [Tile::Flag]
+        );
+        assert_eq!(
+            World::with(|world| world.items().get(&pos_east_south(6, 5)).copied()),
+            Some(Tile::Diamond)
+        );
+
+        // front is blocked by the diamond now
+        let dropped =
+            block_on(inventory.drop_verified(&mut bot.arm, &mut bot.radar, ItemKind::Flag));
+        assert_eq!(dropped, Err(InventoryError::Occupied));
+        // an item of the same kind in front doesn't count as dropped
+        World::with(|world| world.set_item(pos_east_south(6, 5), Some(Tile::Flag)));
+        let dropped =
+            block_on(inventory.drop_verified(&mut bot.arm, &mut bot.radar, ItemKind::Flag));
+        assert_eq!(dropped, Err(InventoryError::Occupied));
+        assert_eq!(inventory.items(), [ItemKind::Flag]);
+        assert_eq!(
+            World::with(|world| world.inventory().to_vec()),
+            [Tile::Flag]
+        );
+    }
+
+    #[test]
+    fn checked_steps() {
+        use async_kartoffel_generic::{Rotation, StepOutcome, Transform};
+
+        let mut world = open_world();
+        let blocker = world.add_bot(pos_east_south(5, 2), Direction::North);
+        world.install();
+        let mut bot = Bot::take();
+
+        // featureless surrounding
+        let step = block_on(bot.motor.step_fw_checked::<D3>(&mut bot.radar));
+        assert_eq!(step.outcome, StepOutcome::Unknown);
+        assert_eq!(step.transform, None);
+        drop(step);
+        assert_eq!(World::with(|world| world.me().pos), pos_east_south(5, 4));
+
+        // sees the wall
+        let step = block_on(bot.motor.step_fw_checked::<D9>(&mut bot.radar));
+        assert_eq!(step.outcome, StepOutcome::Moved);
+        assert_eq!(step.transform, Some(Transform::from(Vec2::new_front(1))));
+        drop(step);
+
+        let step = block_on(bot.motor.step_fw_checked::<D7>(&mut bot.radar));
+        assert_eq!(step.outcome, StepOutcome::Blocked);
+        assert_eq!(step.transform, Some(Transform::identity()));
+        drop(step);
+        assert_eq!(World::with(|world| world.me().pos), pos_east_south(5, 3));
+
+        World::with(|world| world.remove_bot(blocker));
+        block_on(bot.motor.step_fw());
+        block_on(bot.motor.step_fw());
+        let step = block_on(bot.motor.step_fw_checked::<D3>(&mut bot.radar));
+        assert_eq!(step.outcome, StepOutcome::Blocked);
+        drop(step);
+
+        let step = block_on(bot.motor.turn_right_checked::<D3>(&mut bot.radar));
+        assert_eq!(step.outcome, StepOutcome::Moved);
+        assert_eq!(step.transform, Some(Transform::from(Rotation::Right)));
+        drop(step);
+
+        // the west wall comes into sight
+        let step = block_on(bot.motor.step_bw_checked::<D9>(&mut bot.radar));
+        assert_eq!(step.outcome, StepOutcome::Moved);
+        assert_eq!(World::with(|world| world.me().pos), pos_east_south(4, 1));
+    }
+}
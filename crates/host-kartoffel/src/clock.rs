@@ -0,0 +1,31 @@
+use std::cell::Cell;
+
+use async_kartoffel_generic::ClockBackend;
+
+thread_local! {
+    static TICKS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Virtual clock with the same tick rate as the bot. Time only passes by calling
+/// [`HostClock::advance`], which the executor and the world do for you.
+pub enum HostClock {}
+
+impl HostClock {
+    pub fn advance(ticks: u32) {
+        TICKS.with(|t| t.set(t.get().wrapping_add(ticks)));
+    }
+
+    pub fn set(ticks: u32) {
+        TICKS.with(|t| t.set(ticks));
+    }
+}
+
+impl ClockBackend for HostClock {
+    fn now() -> u32 {
+        TICKS.with(Cell::get)
+    }
+
+    fn ticks_per_milli() -> u32 {
+        64
+    }
+}
@@ -0,0 +1,41 @@
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use async_kartoffel_generic::ClockBackend;
+
+use crate::HostClock;
+
+/// Ticks that pass on the [`HostClock`] every time the future returns [`Poll::Pending`]. This
+/// roughly models the time the bot spends executing code between two polls.
+pub const POLL_COST: u32 = 256;
+
+/// Poll the future to completion, advancing the [`HostClock`] by [`POLL_COST`] between polls.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        HostClock::advance(POLL_COST);
+    }
+}
+
+/// Like [`block_on`], but gives up after `ticks` have passed on the [`HostClock`].
+pub fn block_on_timeout<F: Future>(future: F, ticks: u32) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    let start = HostClock::now();
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+        if HostClock::now().wrapping_sub(start) >= ticks {
+            return None;
+        }
+        HostClock::advance(POLL_COST);
+    }
+}
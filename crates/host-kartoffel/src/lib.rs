@@ -0,0 +1,28 @@
+//! Run bot logic on the host. [`HostBackend`] implements
+//! [`BotBackend`](async_kartoffel_generic::BotBackend) on top of a simulated [`World`], and
+//! [`HostClock`] is a virtual [`ClockBackend`](async_kartoffel_generic::ClockBackend) that only
+//! advances when told to, so tests are deterministic.
+//!
+//! World and clock are thread local, so tests running in parallel don't interfere.
+
+mod backend;
+mod clock;
+mod executor;
+mod world;
+
+pub use backend::HostBackend;
+pub use clock::HostClock;
+pub use executor::{POLL_COST, block_on, block_on_timeout};
+pub use world::{World, WorldBot, WorldStats};
+
+pub type Bot = async_kartoffel_generic::Bot<HostBackend>;
+pub type Motor = async_kartoffel_generic::Motor<HostBackend>;
+pub type Arm = async_kartoffel_generic::Arm<HostBackend>;
+pub type Compass = async_kartoffel_generic::Compass<HostBackend>;
+pub type Radar = async_kartoffel_generic::Radar<HostBackend>;
+pub type RadarScan<Size> = async_kartoffel_generic::RadarScan<HostBackend, Size>;
+pub type RadarScanWeak<Size> = async_kartoffel_generic::RadarScanWeak<HostBackend, Size>;
+
+pub type Timer = async_kartoffel_generic::Timer<HostClock>;
+pub type Instant = async_kartoffel_generic::Instant<HostClock>;
+pub type Duration = async_kartoffel_generic::Duration<HostClock>;
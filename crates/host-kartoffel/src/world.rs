@@ -0,0 +1,335 @@
+use std::{cell::RefCell, collections::HashMap, num::NonZeroU64};
+
+use async_kartoffel_generic::{
+    BackendState, ClockBackend, Direction, Rotation, Tile, Vec2, cooldown,
+};
+use kartoffel_gps::GlobalPos;
+use kartoffel_gps_builder::map::Map;
+
+use crate::HostClock;
+
+thread_local! {
+    static WORLD: RefCell<Option<World>> = const { RefCell::new(None) };
+    pub(crate) static STATE: RefCell<BackendState> = const { RefCell::new(BackendState::new()) };
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct WorldBot {
+    pub id: NonZeroU64,
+    pub pos: GlobalPos,
+    pub facing: Direction,
+}
+
+#[derive(Clone, Debug)]
+struct ScanBuffer {
+    d: u8,
+    /// tile and bot id, indexed by `(dy + r) * d + (dx + r)`
+    tiles: Vec<(Tile, Option<NonZeroU64>)>,
+}
+
+/// What our own bot did so far.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct WorldStats {
+    /// successful steps, forward or backward
+    pub steps: u32,
+    /// steps that were blocked by terrain or bots
+    pub steps_blocked: u32,
+    pub turns: u32,
+    /// bots removed by stabbing
+    pub kills: u32,
+    pub diamonds_picked: u32,
+    pub flags_picked: u32,
+    pub drops: u32,
+    pub scans: u32,
+}
+
+/// A simulated arena: terrain from a [`Map`], our own bot and other bots, items lying around and
+/// the cooldowns of our peripherals.
+#[derive(Clone, Debug)]
+pub struct World {
+    map: Map,
+    me: WorldBot,
+    bots: Vec<WorldBot>,
+    items: HashMap<GlobalPos, Tile>,
+    inventory: Vec<Tile>,
+    next_id: u64,
+    motor_ready_at: u32,
+    arm_ready_at: u32,
+    radar_ready_at: u32,
+    compass_ready_at: u32,
+    scan: Option<ScanBuffer>,
+    stats: WorldStats,
+}
+
+impl World {
+    pub fn new(map: Map, pos: GlobalPos, facing: Direction) -> Self {
+        assert!(map.get(pos), "bot has to be placed on walkable terrain");
+        Self {
+            map,
+            me: WorldBot {
+                id: NonZeroU64::MIN,
+                pos,
+                facing,
+            },
+            bots: Vec::new(),
+            items: HashMap::new(),
+            inventory: Vec::new(),
+            next_id: 2,
+            motor_ready_at: 0,
+            arm_ready_at: 0,
+            radar_ready_at: 0,
+            compass_ready_at: 0,
+            scan: None,
+            stats: WorldStats::default(),
+        }
+    }
+
+    /// Make this the world of the current thread. This resets the [`HostClock`] to zero and the
+    /// peripherals, so [`Bot::take`](async_kartoffel_generic::Bot::take) can be called again.
+    pub fn install(self) {
+        HostClock::set(0);
+        STATE.with(|state| *state.borrow_mut() = BackendState::new());
+        WORLD.with(|world| *world.borrow_mut() = Some(self));
+    }
+
+    /// Remove the world from the current thread.
+    pub fn uninstall() -> Option<Self> {
+        WORLD.with(|world| world.borrow_mut().take())
+    }
+
+    /// Access the world of the current thread.
+    ///
+    /// # Panics
+    /// If no world has been installed.
+    pub fn with<T>(f: impl FnOnce(&mut Self) -> T) -> T {
+        WORLD.with(|world| {
+            f(world
+                .borrow_mut()
+                .as_mut()
+                .expect("no world installed, use World::install first"))
+        })
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn me(&self) -> &WorldBot {
+        &self.me
+    }
+
+    pub fn bots(&self) -> &[WorldBot] {
+        &self.bots
+    }
+
+    pub fn bots_mut(&mut self) -> &mut [WorldBot] {
+        &mut self.bots
+    }
+
+    pub fn add_bot(&mut self, pos: GlobalPos, facing: Direction) -> NonZeroU64 {
+        assert!(self.is_free(pos), "bot has to be placed on a free tile");
+        // unwrap: starts at 2 and only increases
+        let id = NonZeroU64::new(self.next_id).unwrap();
+        self.next_id += 1;
+        self.bots.push(WorldBot { id, pos, facing });
+        id
+    }
+
+    pub fn remove_bot(&mut self, id: NonZeroU64) -> Option<WorldBot> {
+        let index = self.bots.iter().position(|bot| bot.id == id)?;
+        Some(self.bots.remove(index))
+    }
+
+    pub fn bot_at(&self, pos: GlobalPos) -> Option<&WorldBot> {
+        core::iter::once(&self.me)
+            .chain(self.bots.iter())
+            .find(|bot| bot.pos == pos)
+    }
+
+    pub fn items(&self) -> &HashMap<GlobalPos, Tile> {
+        &self.items
+    }
+
+    pub fn set_item(&mut self, pos: GlobalPos, item: Option<Tile>) {
+        match item {
+            Some(item) => {
+                assert!(item.is_item());
+                self.items.insert(pos, item);
+            }
+            None => {
+                self.items.remove(&pos);
+            }
+        }
+    }
+
+    pub fn inventory(&self) -> &[Tile] {
+        &self.inventory
+    }
+
+    pub fn stats(&self) -> &WorldStats {
+        &self.stats
+    }
+
+    /// walkable terrain, without bots or items
+    pub fn is_free(&self, pos: GlobalPos) -> bool {
+        self.map.get(pos) && self.bot_at(pos).is_none() && !self.items.contains_key(&pos)
+    }
+
+    /// what the radar would see at this position
+    pub fn tile_at(&self, pos: GlobalPos) -> Tile {
+        let vec = pos - GlobalPos::default();
+        if vec.east() < 0
+            || vec.south() < 0
+            || vec.east() as usize >= self.map.width
+            || vec.south() as usize >= self.map.height
+        {
+            Tile::Void
+        } else if self.bot_at(pos).is_some() {
+            Tile::Bot
+        } else if let Some(&item) = self.items.get(&pos) {
+            item
+        } else if self.map.get(pos) {
+            Tile::Empty
+        } else {
+            Tile::WallCave
+        }
+    }
+
+    pub(crate) fn now() -> u32 {
+        HostClock::now()
+    }
+
+    pub(crate) fn is_motor_ready(&self) -> bool {
+        Self::now() >= self.motor_ready_at
+    }
+
+    pub(crate) fn is_arm_ready(&self) -> bool {
+        Self::now() >= self.arm_ready_at
+    }
+
+    pub(crate) fn is_radar_ready(&self) -> bool {
+        Self::now() >= self.radar_ready_at
+    }
+
+    pub(crate) fn step(&mut self, vec: Vec2<async_kartoffel_generic::Local>, cooldown: u32) {
+        if !self.is_motor_ready() {
+            return;
+        }
+        let target = self.me.pos + vec.global(self.me.facing);
+        if self.map.get(target) && self.bot_at(target).is_none() {
+            self.me.pos = target;
+            self.stats.steps += 1;
+        } else {
+            self.stats.steps_blocked += 1;
+        }
+        self.motor_ready_at = Self::now() + cooldown;
+    }
+
+    pub(crate) fn turn(&mut self, rotation: Rotation) {
+        if !self.is_motor_ready() {
+            return;
+        }
+        self.me.facing += rotation;
+        self.stats.turns += 1;
+        self.motor_ready_at = Self::now() + cooldown::MOTOR_TURN;
+    }
+
+    fn front(&self) -> GlobalPos {
+        self.me.pos + Vec2::new_in_direction(self.me.facing, 1)
+    }
+
+    pub(crate) fn stab(&mut self) {
+        if !self.is_arm_ready() {
+            return;
+        }
+        let front = self.front();
+        let n_bots = self.bots.len();
+        self.bots.retain(|bot| bot.pos != front);
+        self.stats.kills += (n_bots - self.bots.len()) as u32;
+        self.arm_ready_at = Self::now() + cooldown::ARM_STAB;
+    }
+
+    pub(crate) fn pick(&mut self) {
+        if !self.is_arm_ready() {
+            return;
+        }
+        if let Some(item) = self.items.remove(&self.front()) {
+            match item {
+                Tile::Diamond => self.stats.diamonds_picked += 1,
+                Tile::Flag => self.stats.flags_picked += 1,
+                _ => {}
+            }
+            self.inventory.push(item);
+        }
+        self.arm_ready_at = Self::now() + cooldown::ARM_PICK;
+    }
+
+    pub(crate) fn drop_item(&mut self, idx: u8) {
+        if !self.is_arm_ready() {
+            return;
+        }
+        let front = self.front();
+        if usize::from(idx) < self.inventory.len() && self.is_free(front) {
+            let item = self.inventory.remove(idx.into());
+            self.items.insert(front, item);
+            self.stats.drops += 1;
+        }
+        self.arm_ready_at = Self::now() + cooldown::ARM_DROP;
+    }
+
+    pub(crate) fn radar_scan(&mut self, d: u8) {
+        if !self.is_radar_ready() {
+            return;
+        }
+        let r = i16::from(d / 2);
+        let mut tiles = Vec::with_capacity(usize::from(d) * usize::from(d));
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let pos = self.me.pos + Vec2::new_front_right(-dy, dx).global(self.me.facing);
+                let bot = (dx != 0 || dy != 0)
+                    .then(|| self.bot_at(pos).map(|bot| bot.id))
+                    .flatten();
+                let tile = if dx == 0 && dy == 0 {
+                    Tile::Bot
+                } else {
+                    self.tile_at(pos)
+                };
+                tiles.push((tile, bot));
+            }
+        }
+        self.scan = Some(ScanBuffer { d, tiles });
+        self.stats.scans += 1;
+        self.radar_ready_at = Self::now() + cooldown::radar(d);
+    }
+
+    pub(crate) fn radar_read(&self, d: u8, dx: i8, dy: i8, z: u8) -> u32 {
+        let r = i16::from(d / 2);
+        let (dx, dy) = (i16::from(dx), i16::from(dy));
+        let entry = self
+            .scan
+            .as_ref()
+            .filter(|scan| scan.d == d && dx.abs() <= r && dy.abs() <= r)
+            .map(|scan| scan.tiles[((dy + r) * i16::from(d) + dx + r) as usize]);
+        match (entry, z) {
+            (Some((tile, _)), 0) => tile.to_char() as u32,
+            (Some((_, Some(id))), 1) => (id.get() >> 32) as u32,
+            (Some((_, Some(id))), 2) => id.get() as u32,
+            (None, 0) => Tile::Void.to_char() as u32,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn compass_dir(&mut self) -> u32 {
+        if Self::now() >= self.compass_ready_at {
+            self.compass_ready_at = Self::now() + cooldown::COMPASS;
+            match self.me.facing {
+                Direction::North => 1,
+                Direction::East => 2,
+                Direction::South => 3,
+                Direction::West => 4,
+            }
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::{env, path::Path};
+
+use kartoffel_gps_builder::{codegen::MapAssets, map::Map};
+
+/// small map with repeated pillars, so that the GPS has unique and ambiguous chunks
+const TEST_MAP: [&str; 9] = [
+    "####################",
+    "#..................#",
+    "#.#.#.#.#..##...#..#",
+    "#..........##......#",
+    "#.#.#.#.#......#...#",
+    "#...........#......#",
+    "#.##..#.#.#.#..###.#",
+    "#..................#",
+    "####################",
+];
+
+/// The assets of `TEST_MAP` as generated source and in the binary format, to check that they
+/// agree. The map is written next to them for the test.
+fn main() {
+    let map = Map {
+        tiles: TEST_MAP
+            .iter()
+            .flat_map(|line| line.chars().map(|c| c == '.'))
+            .collect(),
+        width: TEST_MAP[0].len(),
+        height: TEST_MAP.len(),
+    };
+    let out_dir = env::var("OUT_DIR").unwrap();
+    map.write_file(Path::new(&out_dir).join("test_map.txt"))
+        .unwrap();
+
+    let assets = MapAssets::new(map)
+        .gps_window(5)
+        .max_beacon_dist(3)
+        .ambiguous_positions(8);
+    assets.embed("test_map.rs").unwrap();
+    assets.embed_binary("test_map.bin").unwrap();
+}
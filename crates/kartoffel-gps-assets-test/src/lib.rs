@@ -0,0 +1,99 @@
+//! Cross-check of the two asset formats of `kartoffel-gps-builder`: the build script writes the map
+//! assets of a test map as generated Rust source and in the binary format, the tests compile the
+//! module, parse the binary assets and check that every lookup agrees. The crate has no other
+//! content.
+
+#[cfg(test)]
+mod tests {
+    use kartoffel_gps::{
+        assets::GpsTables,
+        binary::Assets,
+        const_graph::Graph,
+        gps::{MapSection, MapSectionTrait, TolerantIndex},
+        map::TrueMap,
+        pos::pos_east_south,
+    };
+    use kartoffel_gps_builder::map::Map;
+
+    // only the lookups are used
+    #[allow(dead_code)]
+    mod test_map {
+        include!(concat!(env!("OUT_DIR"), "/test_map.rs"));
+    }
+
+    static ASSETS: &[u8] =
+        kartoffel_gps::include_assets!(concat!(env!("OUT_DIR"), "/test_map.bin"));
+
+    fn map() -> Map {
+        Map::from_path(concat!(env!("OUT_DIR"), "/test_map.txt")).unwrap()
+    }
+
+    #[test]
+    fn metadata_and_map() {
+        let binary = Assets::parse(ASSETS).unwrap();
+        assert_eq!(binary.map_hash(), test_map::METADATA.map_hash);
+        assert_eq!(binary.gps_window(), test_map::CHUNK_SIZE);
+
+        let (source, binary) = (test_map::true_map(), binary.true_map());
+        assert_eq!(binary.width(), source.width());
+        assert_eq!(binary.height(), source.height());
+        for south in -1..=source.height() as i16 {
+            for east in -1..=source.width() as i16 {
+                let pos = pos_east_south(east, south);
+                assert_eq!(binary.get(pos), source.get(pos), "{pos:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn beacons() {
+        let binary = Assets::parse(ASSETS).unwrap();
+        assert_eq!(binary.beacons(), test_map::beacons());
+        assert_eq!(binary.beacon_info(), *test_map::beacon_info());
+
+        let (source, binary) = (test_map::beacon_graph(), binary.beacon_graph());
+        assert_eq!(binary.size(), source.size());
+        for node in 0..source.size() {
+            assert_eq!(binary.before(node), source.before(node));
+            assert_eq!(binary.after(node), source.after(node));
+        }
+    }
+
+    #[test]
+    fn gps() {
+        let map = map();
+        let binary = Assets::parse(ASSETS).unwrap();
+        let binary = binary.gps::<test_map::ChunkShape>().unwrap();
+        let source = test_map::gps();
+        assert_eq!(binary.n_unique(), source.n_unique());
+
+        // the chunk around every tile, also the ones that aren't walkable and can't be compressed
+        let mut n_ambiguous = 0;
+        for south in 0..map.height as i16 {
+            for east in 0..map.width as i16 {
+                let chunk: MapSection<5> = map.get_chunk(pos_east_south(east, south));
+                assert_eq!(binary.get_global_pos(&chunk), source.get_global_pos(&chunk));
+                assert!(
+                    binary
+                        .pos_candidates(&chunk)
+                        .eq(source.pos_candidates(&chunk)),
+                    "{east} {south}"
+                );
+                n_ambiguous += usize::from(source.pos_candidates(&chunk).count() > 1);
+                if let Some(compressed) = chunk.compress() {
+                    let compressed = compressed.as_ref();
+                    assert_eq!(
+                        binary.tables.rotation_unique(compressed),
+                        source.tables.rotation_unique(compressed)
+                    );
+                }
+            }
+        }
+
+        assert!(n_ambiguous > 0);
+
+        let TolerantIndex { n_blocks, sorted } = binary.tolerant_index;
+        assert_eq!(n_blocks, source.tolerant_index.n_blocks);
+        assert_eq!(sorted, source.tolerant_index.sorted);
+    }
+}
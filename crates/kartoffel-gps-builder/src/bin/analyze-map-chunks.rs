@@ -15,7 +15,7 @@ fn main() {
     let mut multiplicities: HashMap<usize, Vec<MapSection<CHUNK_SIZE>>> = HashMap::new();
     for (chunk, locations) in &chunks {
         let count = locations.len();
-        multiplicities.entry(count).or_default().push(chunk.clone());
+        multiplicities.entry(count).or_default().push(*chunk);
     }
 
     let mut counts = multiplicities.keys().collect::<Vec<_>>();
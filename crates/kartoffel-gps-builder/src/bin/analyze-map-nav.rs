@@ -2,7 +2,11 @@ use std::path::Path;
 
 use kartoffel_gps::const_graph::Graph as ConstGraph;
 use kartoffel_gps_builder::{
-    beacon_nav::{build_trivial_navigation_graph, find_beacons, get_beacon_info}, const_global_pos::ArrayBuilder, const_graph::ConstSparseGraphBuilder, graph::{Graph, GraphMappingSingleNode}, map::Map
+    beacon_nav::{build_trivial_navigation_graph, find_beacons, get_beacon_info},
+    const_global_pos::ArrayBuilder,
+    const_graph::ConstSparseGraphBuilder,
+    graph::{Graph, GraphMappingSingleNode},
+    map::Map,
 };
 
 fn main() {
@@ -20,7 +24,7 @@ fn main() {
     // The following calculation is only based on the graphs.
     // With graph matrix called A, initial one-hot position s, and destination one-hot position t, the
     // number of combinations each position is reachable after exactly n steps is given by A^n s.
-    
+
     let max_beacon_dist = 12;
 
     let beacon_indices = find_beacons(max_beacon_dist, &asymmetric_graph);
@@ -85,13 +89,16 @@ fn main() {
         println!("identical after {} chains", i);
     }
 
-    println!("{:?}", get_beacon_info(
-        &beacon_indices,
-        &positions,
-        &asymmetric_graph,
-        &beacon_graph,
-        max_beacon_dist
-    ));
+    println!(
+        "{:?}",
+        get_beacon_info(
+            &beacon_indices,
+            &positions,
+            &asymmetric_graph,
+            &beacon_graph,
+            max_beacon_dist
+        )
+    );
 
     println!("done");
 
@@ -118,1218 +125,478 @@ fn main() {
     }
 }
 
-const BEACON_GRAPH: ::kartoffel_gps::const_graph::ConstSparseGraph<83, 1124> = ::kartoffel_gps::const_graph::ConstSparseGraph {
-    nodes: [
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 0, mid: 10, end: 16 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 16, mid: 25, end: 33 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 33, mid: 40, end: 46 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 46, mid: 54, end: 66 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 66, mid: 72, end: 79 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 79, mid: 89, end: 101 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 101, mid: 112, end: 123 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 123, mid: 133, end: 139 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 139, mid: 145, end: 151 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 151, mid: 159, end: 172 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 172, mid: 181, end: 191 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 191, mid: 196, end: 201 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 201, mid: 204, end: 210 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 210, mid: 214, end: 219 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 219, mid: 230, end: 241 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 241, mid: 247, end: 254 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 254, mid: 269, end: 284 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 284, mid: 297, end: 308 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 308, mid: 315, end: 326 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 326, mid: 333, end: 339 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 339, mid: 352, end: 358 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 358, mid: 364, end: 374 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 374, mid: 380, end: 386 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 386, mid: 393, end: 398 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 398, mid: 411, end: 418 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 418, mid: 433, end: 444 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 444, mid: 452, end: 457 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 457, mid: 462, end: 468 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 468, mid: 474, end: 485 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 485, mid: 490, end: 497 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 497, mid: 502, end: 507 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 507, mid: 512, end: 517 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 517, mid: 525, end: 532 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 532, mid: 536, end: 540 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 540, mid: 544, end: 554 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 554, mid: 557, end: 560 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 560, mid: 565, end: 570 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 570, mid: 576, end: 580 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 580, mid: 584, end: 589 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 589, mid: 594, end: 599 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 599, mid: 603, end: 607 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 607, mid: 612, end: 618 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 618, mid: 627, end: 635 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 635, mid: 639, end: 647 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 647, mid: 653, end: 658 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 658, mid: 663, end: 667 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 667, mid: 674, end: 679 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 679, mid: 681, end: 689 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 689, mid: 691, end: 695 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 695, mid: 699, end: 702 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 702, mid: 716, end: 723 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 723, mid: 726, end: 729 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 729, mid: 736, end: 743 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 743, mid: 748, end: 752 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 752, mid: 759, end: 767 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 767, mid: 772, end: 785 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 785, mid: 789, end: 793 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 793, mid: 797, end: 801 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 801, mid: 806, end: 811 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 811, mid: 815, end: 820 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 820, mid: 830, end: 838 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 838, mid: 851, end: 860 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 860, mid: 867, end: 877 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 877, mid: 884, end: 890 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 890, mid: 897, end: 906 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 906, mid: 911, end: 915 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 915, mid: 922, end: 932 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 932, mid: 940, end: 946 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 946, mid: 958, end: 965 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 965, mid: 972, end: 978 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 978, mid: 982, end: 987 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 987, mid: 989, end: 991 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 991, mid: 998, end: 1007 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1007, mid: 1013, end: 1019 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1019, mid: 1024, end: 1032 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1032, mid: 1038, end: 1045 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1045, mid: 1047, end: 1049 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1049, mid: 1054, end: 1061 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1061, mid: 1067, end: 1070 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1070, mid: 1075, end: 1079 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1079, mid: 1089, end: 1097 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1097, mid: 1109, end: 1116 },
-        ::kartoffel_gps::const_graph::ConstSparseGraphNode { start: 1116, mid: 1121, end: 1124 },
-    ],
-    data: [        0,
-        1,
-        3,
-        11,
-        12,
-        31,
-        54,
-        58,
-        64,
-        67,
-        0,
-        1,
-        3,
-        11,
-        54,
-        67,
-        0,
-        1,
-        2,
-        3,
-        54,
-        55,
-        64,
-        67,
-        73,
-        0,
-        1,
-        2,
-        3,
-        54,
-        64,
-        67,
-        73,
-        1,
-        2,
-        54,
-        55,
-        64,
-        73,
-        79,
-        1,
-        2,
-        55,
-        64,
-        73,
-        79,
-        0,
-        1,
-        3,
-        4,
-        5,
-        54,
-        64,
-        67,
-        0,
-        1,
-        3,
-        4,
-        5,
-        6,
-        7,
-        8,
-        11,
-        54,
-        67,
-        78,
-        3,
-        4,
-        5,
-        7,
-        8,
-        78,
-        3,
-        4,
-        5,
-        6,
-        7,
-        8,
-        78,
-        3,
-        4,
-        5,
-        6,
-        7,
-        8,
-        9,
-        60,
-        72,
-        80,
-        3,
-        4,
-        5,
-        6,
-        7,
-        8,
-        9,
-        60,
-        61,
-        72,
-        78,
-        80,
-        3,
-        4,
-        5,
-        6,
-        7,
-        8,
-        9,
-        55,
-        60,
-        72,
-        80,
-        5,
-        6,
-        7,
-        9,
-        50,
-        60,
-        61,
-        68,
-        72,
-        80,
-        81,
-        3,
-        4,
-        5,
-        6,
-        7,
-        8,
-        9,
-        60,
-        72,
-        80,
-        4,
-        5,
-        6,
-        7,
-        8,
-        78,
-        3,
-        4,
-        5,
-        7,
-        8,
-        78,
-        4,
-        5,
-        6,
-        7,
-        8,
-        78,
-        5,
-        6,
-        9,
-        10,
-        60,
-        61,
-        62,
-        80,
-        5,
-        6,
-        7,
-        9,
-        10,
-        14,
-        50,
-        60,
-        61,
-        62,
-        72,
-        80,
-        81,
-        9,
-        10,
-        14,
-        16,
-        50,
-        61,
-        62,
-        68,
-        81,
-        9,
-        10,
-        14,
-        50,
-        60,
-        61,
-        62,
-        68,
-        80,
-        81,
-        0,
-        3,
-        11,
-        12,
-        67,
-        0,
-        11,
-        12,
-        63,
-        67,
-        11,
-        12,
-        13,
-        0,
-        11,
-        12,
-        13,
-        63,
-        67,
-        12,
-        13,
-        29,
-        56,
-        12,
-        13,
-        29,
-        56,
-        63,
-        9,
-        10,
-        14,
-        15,
-        16,
-        17,
-        50,
-        61,
-        62,
-        68,
-        81,
-        10,
-        14,
-        15,
-        16,
-        17,
-        20,
-        24,
-        25,
-        50,
-        61,
-        68,
-        14,
-        15,
-        16,
-        17,
-        18,
-        66,
-        14,
-        15,
-        16,
-        17,
-        20,
-        24,
-        25,
-        14,
-        15,
-        16,
-        17,
-        18,
-        19,
-        20,
-        21,
-        24,
-        25,
-        28,
-        34,
-        47,
-        66,
-        69,
-        10,
-        14,
-        15,
-        16,
-        17,
-        18,
-        19,
-        20,
-        24,
-        25,
-        50,
-        61,
-        66,
-        68,
-        69,
-        14,
-        15,
-        16,
-        17,
-        18,
-        20,
-        21,
-        24,
-        25,
-        28,
-        34,
-        47,
-        66,
-        14,
-        15,
-        16,
-        17,
-        18,
-        19,
-        20,
-        24,
-        25,
-        66,
-        69,
-        16,
-        17,
-        18,
-        19,
-        59,
-        66,
-        69,
-        15,
-        16,
-        17,
-        18,
-        19,
-        20,
-        24,
-        25,
-        59,
-        66,
-        69,
-        16,
-        17,
-        18,
-        19,
-        59,
-        66,
-        69,
-        16,
-        18,
-        19,
-        59,
-        66,
-        69,
-        14,
-        15,
-        16,
-        17,
-        18,
-        20,
-        21,
-        24,
-        25,
-        28,
-        34,
-        47,
-        66,
-        16,
-        17,
-        20,
-        21,
-        24,
-        25,
-        20,
-        21,
-        22,
-        23,
-        24,
-        75,
-        16,
-        17,
-        20,
-        21,
-        22,
-        23,
-        24,
-        25,
-        26,
-        75,
-        21,
-        22,
-        23,
-        26,
-        74,
-        75,
-        21,
-        22,
-        23,
-        26,
-        49,
-        75,
-        21,
-        22,
-        23,
-        27,
-        65,
-        74,
-        75,
-        21,
-        22,
-        23,
-        27,
-        65,
-        14,
-        15,
-        16,
-        17,
-        18,
-        20,
-        21,
-        24,
-        25,
-        28,
-        34,
-        47,
-        66,
-        16,
-        17,
-        20,
-        21,
-        24,
-        25,
-        39,
-        14,
-        15,
-        16,
-        17,
-        18,
-        20,
-        21,
-        24,
-        25,
-        28,
-        34,
-        46,
-        52,
-        66,
-        77,
-        16,
-        17,
-        20,
-        24,
-        25,
-        28,
-        32,
-        34,
-        46,
-        52,
-        77,
-        21,
-        22,
-        26,
-        27,
-        49,
-        70,
-        74,
-        75,
-        22,
-        26,
-        49,
-        74,
-        75,
-        23,
-        27,
-        65,
-        70,
-        74,
-        23,
-        26,
-        27,
-        65,
-        70,
-        74,
-        25,
-        28,
-        32,
-        46,
-        52,
-        77,
-        16,
-        17,
-        20,
-        24,
-        25,
-        28,
-        32,
-        46,
-        52,
-        77,
-        82,
-        13,
-        29,
-        30,
-        56,
-        63,
-        13,
-        29,
-        30,
-        31,
-        56,
-        58,
-        63,
-        29,
-        30,
-        31,
-        58,
-        63,
-        29,
-        30,
-        31,
-        58,
-        63,
-        29,
-        30,
-        31,
-        58,
-        63,
-        0,
-        30,
-        31,
-        54,
-        58,
-        25,
-        28,
-        32,
-        33,
-        34,
-        52,
-        53,
-        77,
-        28,
-        32,
-        33,
-        51,
-        52,
-        53,
-        77,
-        32,
-        33,
-        51,
-        53,
-        32,
-        33,
-        51,
-        53,
-        25,
-        34,
-        35,
-        36,
-        16,
-        17,
-        20,
-        24,
-        25,
-        32,
-        34,
-        35,
-        36,
-        46,
-        34,
-        35,
-        36,
-        34,
-        35,
-        36,
-        34,
-        35,
-        36,
-        37,
-        40,
-        34,
-        35,
-        36,
-        37,
-        40,
-        36,
-        37,
-        38,
-        39,
-        40,
-        41,
-        36,
-        37,
-        38,
-        40,
-        37,
-        38,
-        39,
-        41,
-        37,
-        38,
-        39,
-        45,
-        57,
-        24,
-        38,
-        39,
-        41,
-        47,
-        37,
-        38,
-        39,
-        41,
-        42,
-        36,
-        37,
-        40,
-        76,
-        36,
-        37,
-        40,
-        76,
-        39,
-        41,
-        42,
-        43,
-        47,
-        37,
-        38,
-        39,
-        41,
-        42,
-        47,
-        39,
-        41,
-        42,
-        43,
-        44,
-        45,
-        47,
-        48,
-        57,
-        41,
-        42,
-        43,
-        44,
-        45,
-        50,
-        68,
-        81,
-        42,
-        43,
-        44,
-        48,
-        41,
-        42,
-        43,
-        44,
-        48,
-        50,
-        68,
-        81,
-        42,
-        43,
-        44,
-        45,
-        48,
-        57,
-        42,
-        43,
-        44,
-        45,
-        57,
-        38,
-        42,
-        44,
-        45,
-        57,
-        42,
-        44,
-        45,
-        57,
-        25,
-        28,
-        34,
-        46,
-        52,
-        77,
-        82,
-        25,
-        28,
-        46,
-        52,
-        82,
-        41,
-        47,
-        16,
-        17,
-        20,
-        24,
-        39,
-        41,
-        42,
-        47,
-        43,
-        48,
-        42,
-        43,
-        44,
-        48,
-        22,
-        26,
-        49,
-        75,
-        26,
-        49,
-        75,
-        6,
-        9,
-        10,
-        14,
-        16,
-        42,
-        43,
-        50,
-        55,
-        61,
-        62,
-        68,
-        72,
-        81,
-        10,
-        14,
-        50,
-        61,
-        62,
-        68,
-        81,
-        32,
-        33,
-        51,
-        33,
-        51,
-        53,
-        25,
-        28,
-        32,
-        46,
-        52,
-        77,
-        82,
-        25,
-        28,
-        32,
-        46,
-        52,
-        77,
-        82,
-        32,
-        33,
-        51,
-        53,
-        71,
-        32,
-        33,
-        53,
-        71,
-        0,
-        1,
-        3,
-        31,
-        54,
-        58,
-        67,
-        0,
-        1,
-        2,
-        3,
-        54,
-        64,
-        67,
-        73,
-        2,
-        55,
-        64,
-        73,
-        79,
-        1,
-        2,
-        6,
-        50,
-        55,
-        60,
-        64,
-        68,
-        72,
-        73,
-        79,
-        80,
-        81,
-        13,
-        29,
-        56,
-        63,
-        13,
-        29,
-        56,
-        63,
-        38,
-        44,
-        45,
-        57,
-        42,
-        44,
-        45,
-        57,
-        29,
-        30,
-        31,
-        58,
-        63,
-        0,
-        30,
-        31,
-        54,
-        58,
-        18,
-        19,
-        59,
-        69,
-        18,
-        19,
-        59,
-        66,
-        69,
-        5,
-        6,
-        9,
-        10,
-        55,
-        60,
-        61,
-        62,
-        72,
-        80,
-        5,
-        6,
-        7,
-        9,
-        60,
-        61,
-        72,
-        80,
-        5,
-        6,
-        9,
-        10,
-        14,
-        16,
-        50,
-        60,
-        61,
-        62,
-        68,
-        80,
-        81,
-        9,
-        10,
-        14,
-        50,
-        60,
-        61,
-        62,
-        80,
-        81,
-        9,
-        10,
-        50,
-        61,
-        62,
-        68,
-        81,
-        9,
-        10,
-        14,
-        50,
-        60,
-        61,
-        62,
-        68,
-        80,
-        81,
-        11,
-        12,
-        13,
-        29,
-        30,
-        56,
-        63,
-        29,
-        30,
-        31,
-        56,
-        58,
-        63,
-        1,
-        2,
-        54,
-        55,
-        64,
-        73,
-        79,
-        0,
-        1,
-        2,
-        3,
-        55,
-        64,
-        67,
-        73,
-        79,
-        23,
-        27,
-        65,
-        70,
-        74,
-        23,
-        27,
-        65,
-        70,
-        16,
-        17,
-        18,
-        19,
-        59,
-        66,
-        69,
-        15,
-        16,
-        17,
-        18,
-        19,
-        20,
-        24,
-        25,
-        66,
-        69,
-        0,
-        1,
-        3,
-        11,
-        12,
-        54,
-        64,
-        67,
-        0,
-        1,
-        3,
-        11,
-        54,
-        67,
-        6,
-        10,
-        14,
-        16,
-        42,
-        43,
-        50,
-        55,
-        62,
-        68,
-        72,
-        81,
-        10,
-        14,
-        50,
-        61,
-        62,
-        68,
-        81,
-        16,
-        17,
-        18,
-        19,
-        59,
-        66,
-        69,
-        16,
-        18,
-        19,
-        59,
-        66,
-        69,
-        27,
-        65,
-        70,
-        74,
-        26,
-        27,
-        65,
-        70,
-        74,
-        53,
-        71,
-        53,
-        71,
-        5,
-        6,
-        9,
-        55,
-        60,
-        72,
-        80,
-        5,
-        6,
-        7,
-        50,
-        60,
-        68,
-        72,
-        80,
-        81,
-        1,
-        2,
-        54,
-        55,
-        64,
-        73,
-        1,
-        2,
-        55,
-        64,
-        73,
-        79,
-        26,
-        27,
-        70,
-        74,
-        75,
-        22,
-        23,
-        26,
-        27,
-        65,
-        70,
-        74,
-        75,
-        21,
-        22,
-        26,
-        49,
-        74,
-        75,
-        21,
-        22,
-        23,
-        26,
-        49,
-        74,
-        75,
-        40,
-        76,
-        40,
-        76,
-        25,
-        28,
-        32,
-        52,
-        77,
-        25,
-        28,
-        32,
-        46,
-        52,
-        77,
-        82,
-        3,
-        4,
-        5,
-        7,
-        8,
-        78,
-        4,
-        8,
-        78,
-        2,
-        55,
-        64,
-        73,
-        79,
-        2,
-        55,
-        64,
-        79,
-        5,
-        6,
-        9,
-        10,
-        55,
-        60,
-        61,
-        62,
-        72,
-        80,
-        5,
-        6,
-        7,
-        9,
-        60,
-        61,
-        72,
-        80,
-        6,
-        9,
-        10,
-        42,
-        43,
-        50,
-        55,
-        61,
-        62,
-        68,
-        72,
-        81,
-        10,
-        14,
-        50,
-        61,
-        62,
-        68,
-        81,
-        28,
-        46,
-        52,
-        77,
-        82,
-        46,
-        52,
-        82,
-    ]
-};
+const BEACON_GRAPH: ::kartoffel_gps::const_graph::ConstSparseGraph<83, 1124> =
+    ::kartoffel_gps::const_graph::ConstSparseGraph {
+        nodes: [
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 0,
+                mid: 10,
+                end: 16,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 16,
+                mid: 25,
+                end: 33,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 33,
+                mid: 40,
+                end: 46,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 46,
+                mid: 54,
+                end: 66,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 66,
+                mid: 72,
+                end: 79,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 79,
+                mid: 89,
+                end: 101,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 101,
+                mid: 112,
+                end: 123,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 123,
+                mid: 133,
+                end: 139,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 139,
+                mid: 145,
+                end: 151,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 151,
+                mid: 159,
+                end: 172,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 172,
+                mid: 181,
+                end: 191,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 191,
+                mid: 196,
+                end: 201,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 201,
+                mid: 204,
+                end: 210,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 210,
+                mid: 214,
+                end: 219,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 219,
+                mid: 230,
+                end: 241,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 241,
+                mid: 247,
+                end: 254,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 254,
+                mid: 269,
+                end: 284,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 284,
+                mid: 297,
+                end: 308,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 308,
+                mid: 315,
+                end: 326,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 326,
+                mid: 333,
+                end: 339,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 339,
+                mid: 352,
+                end: 358,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 358,
+                mid: 364,
+                end: 374,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 374,
+                mid: 380,
+                end: 386,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 386,
+                mid: 393,
+                end: 398,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 398,
+                mid: 411,
+                end: 418,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 418,
+                mid: 433,
+                end: 444,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 444,
+                mid: 452,
+                end: 457,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 457,
+                mid: 462,
+                end: 468,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 468,
+                mid: 474,
+                end: 485,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 485,
+                mid: 490,
+                end: 497,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 497,
+                mid: 502,
+                end: 507,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 507,
+                mid: 512,
+                end: 517,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 517,
+                mid: 525,
+                end: 532,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 532,
+                mid: 536,
+                end: 540,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 540,
+                mid: 544,
+                end: 554,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 554,
+                mid: 557,
+                end: 560,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 560,
+                mid: 565,
+                end: 570,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 570,
+                mid: 576,
+                end: 580,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 580,
+                mid: 584,
+                end: 589,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 589,
+                mid: 594,
+                end: 599,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 599,
+                mid: 603,
+                end: 607,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 607,
+                mid: 612,
+                end: 618,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 618,
+                mid: 627,
+                end: 635,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 635,
+                mid: 639,
+                end: 647,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 647,
+                mid: 653,
+                end: 658,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 658,
+                mid: 663,
+                end: 667,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 667,
+                mid: 674,
+                end: 679,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 679,
+                mid: 681,
+                end: 689,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 689,
+                mid: 691,
+                end: 695,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 695,
+                mid: 699,
+                end: 702,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 702,
+                mid: 716,
+                end: 723,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 723,
+                mid: 726,
+                end: 729,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 729,
+                mid: 736,
+                end: 743,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 743,
+                mid: 748,
+                end: 752,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 752,
+                mid: 759,
+                end: 767,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 767,
+                mid: 772,
+                end: 785,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 785,
+                mid: 789,
+                end: 793,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 793,
+                mid: 797,
+                end: 801,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 801,
+                mid: 806,
+                end: 811,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 811,
+                mid: 815,
+                end: 820,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 820,
+                mid: 830,
+                end: 838,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 838,
+                mid: 851,
+                end: 860,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 860,
+                mid: 867,
+                end: 877,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 877,
+                mid: 884,
+                end: 890,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 890,
+                mid: 897,
+                end: 906,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 906,
+                mid: 911,
+                end: 915,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 915,
+                mid: 922,
+                end: 932,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 932,
+                mid: 940,
+                end: 946,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 946,
+                mid: 958,
+                end: 965,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 965,
+                mid: 972,
+                end: 978,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 978,
+                mid: 982,
+                end: 987,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 987,
+                mid: 989,
+                end: 991,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 991,
+                mid: 998,
+                end: 1007,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1007,
+                mid: 1013,
+                end: 1019,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1019,
+                mid: 1024,
+                end: 1032,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1032,
+                mid: 1038,
+                end: 1045,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1045,
+                mid: 1047,
+                end: 1049,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1049,
+                mid: 1054,
+                end: 1061,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1061,
+                mid: 1067,
+                end: 1070,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1070,
+                mid: 1075,
+                end: 1079,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1079,
+                mid: 1089,
+                end: 1097,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1097,
+                mid: 1109,
+                end: 1116,
+            },
+            ::kartoffel_gps::const_graph::ConstSparseGraphNode {
+                start: 1116,
+                mid: 1121,
+                end: 1124,
+            },
+        ],
+        data: [
+            0, 1, 3, 11, 12, 31, 54, 58, 64, 67, 0, 1, 3, 11, 54, 67, 0, 1, 2, 3, 54, 55, 64, 67,
+            73, 0, 1, 2, 3, 54, 64, 67, 73, 1, 2, 54, 55, 64, 73, 79, 1, 2, 55, 64, 73, 79, 0, 1,
+            3, 4, 5, 54, 64, 67, 0, 1, 3, 4, 5, 6, 7, 8, 11, 54, 67, 78, 3, 4, 5, 7, 8, 78, 3, 4,
+            5, 6, 7, 8, 78, 3, 4, 5, 6, 7, 8, 9, 60, 72, 80, 3, 4, 5, 6, 7, 8, 9, 60, 61, 72, 78,
+            80, 3, 4, 5, 6, 7, 8, 9, 55, 60, 72, 80, 5, 6, 7, 9, 50, 60, 61, 68, 72, 80, 81, 3, 4,
+            5, 6, 7, 8, 9, 60, 72, 80, 4, 5, 6, 7, 8, 78, 3, 4, 5, 7, 8, 78, 4, 5, 6, 7, 8, 78, 5,
+            6, 9, 10, 60, 61, 62, 80, 5, 6, 7, 9, 10, 14, 50, 60, 61, 62, 72, 80, 81, 9, 10, 14,
+            16, 50, 61, 62, 68, 81, 9, 10, 14, 50, 60, 61, 62, 68, 80, 81, 0, 3, 11, 12, 67, 0, 11,
+            12, 63, 67, 11, 12, 13, 0, 11, 12, 13, 63, 67, 12, 13, 29, 56, 12, 13, 29, 56, 63, 9,
+            10, 14, 15, 16, 17, 50, 61, 62, 68, 81, 10, 14, 15, 16, 17, 20, 24, 25, 50, 61, 68, 14,
+            15, 16, 17, 18, 66, 14, 15, 16, 17, 20, 24, 25, 14, 15, 16, 17, 18, 19, 20, 21, 24, 25,
+            28, 34, 47, 66, 69, 10, 14, 15, 16, 17, 18, 19, 20, 24, 25, 50, 61, 66, 68, 69, 14, 15,
+            16, 17, 18, 20, 21, 24, 25, 28, 34, 47, 66, 14, 15, 16, 17, 18, 19, 20, 24, 25, 66, 69,
+            16, 17, 18, 19, 59, 66, 69, 15, 16, 17, 18, 19, 20, 24, 25, 59, 66, 69, 16, 17, 18, 19,
+            59, 66, 69, 16, 18, 19, 59, 66, 69, 14, 15, 16, 17, 18, 20, 21, 24, 25, 28, 34, 47, 66,
+            16, 17, 20, 21, 24, 25, 20, 21, 22, 23, 24, 75, 16, 17, 20, 21, 22, 23, 24, 25, 26, 75,
+            21, 22, 23, 26, 74, 75, 21, 22, 23, 26, 49, 75, 21, 22, 23, 27, 65, 74, 75, 21, 22, 23,
+            27, 65, 14, 15, 16, 17, 18, 20, 21, 24, 25, 28, 34, 47, 66, 16, 17, 20, 21, 24, 25, 39,
+            14, 15, 16, 17, 18, 20, 21, 24, 25, 28, 34, 46, 52, 66, 77, 16, 17, 20, 24, 25, 28, 32,
+            34, 46, 52, 77, 21, 22, 26, 27, 49, 70, 74, 75, 22, 26, 49, 74, 75, 23, 27, 65, 70, 74,
+            23, 26, 27, 65, 70, 74, 25, 28, 32, 46, 52, 77, 16, 17, 20, 24, 25, 28, 32, 46, 52, 77,
+            82, 13, 29, 30, 56, 63, 13, 29, 30, 31, 56, 58, 63, 29, 30, 31, 58, 63, 29, 30, 31, 58,
+            63, 29, 30, 31, 58, 63, 0, 30, 31, 54, 58, 25, 28, 32, 33, 34, 52, 53, 77, 28, 32, 33,
+            51, 52, 53, 77, 32, 33, 51, 53, 32, 33, 51, 53, 25, 34, 35, 36, 16, 17, 20, 24, 25, 32,
+            34, 35, 36, 46, 34, 35, 36, 34, 35, 36, 34, 35, 36, 37, 40, 34, 35, 36, 37, 40, 36, 37,
+            38, 39, 40, 41, 36, 37, 38, 40, 37, 38, 39, 41, 37, 38, 39, 45, 57, 24, 38, 39, 41, 47,
+            37, 38, 39, 41, 42, 36, 37, 40, 76, 36, 37, 40, 76, 39, 41, 42, 43, 47, 37, 38, 39, 41,
+            42, 47, 39, 41, 42, 43, 44, 45, 47, 48, 57, 41, 42, 43, 44, 45, 50, 68, 81, 42, 43, 44,
+            48, 41, 42, 43, 44, 48, 50, 68, 81, 42, 43, 44, 45, 48, 57, 42, 43, 44, 45, 57, 38, 42,
+            44, 45, 57, 42, 44, 45, 57, 25, 28, 34, 46, 52, 77, 82, 25, 28, 46, 52, 82, 41, 47, 16,
+            17, 20, 24, 39, 41, 42, 47, 43, 48, 42, 43, 44, 48, 22, 26, 49, 75, 26, 49, 75, 6, 9,
+            10, 14, 16, 42, 43, 50, 55, 61, 62, 68, 72, 81, 10, 14, 50, 61, 62, 68, 81, 32, 33, 51,
+            33, 51, 53, 25, 28, 32, 46, 52, 77, 82, 25, 28, 32, 46, 52, 77, 82, 32, 33, 51, 53, 71,
+            32, 33, 53, 71, 0, 1, 3, 31, 54, 58, 67, 0, 1, 2, 3, 54, 64, 67, 73, 2, 55, 64, 73, 79,
+            1, 2, 6, 50, 55, 60, 64, 68, 72, 73, 79, 80, 81, 13, 29, 56, 63, 13, 29, 56, 63, 38,
+            44, 45, 57, 42, 44, 45, 57, 29, 30, 31, 58, 63, 0, 30, 31, 54, 58, 18, 19, 59, 69, 18,
+            19, 59, 66, 69, 5, 6, 9, 10, 55, 60, 61, 62, 72, 80, 5, 6, 7, 9, 60, 61, 72, 80, 5, 6,
+            9, 10, 14, 16, 50, 60, 61, 62, 68, 80, 81, 9, 10, 14, 50, 60, 61, 62, 80, 81, 9, 10,
+            50, 61, 62, 68, 81, 9, 10, 14, 50, 60, 61, 62, 68, 80, 81, 11, 12, 13, 29, 30, 56, 63,
+            29, 30, 31, 56, 58, 63, 1, 2, 54, 55, 64, 73, 79, 0, 1, 2, 3, 55, 64, 67, 73, 79, 23,
+            27, 65, 70, 74, 23, 27, 65, 70, 16, 17, 18, 19, 59, 66, 69, 15, 16, 17, 18, 19, 20, 24,
+            25, 66, 69, 0, 1, 3, 11, 12, 54, 64, 67, 0, 1, 3, 11, 54, 67, 6, 10, 14, 16, 42, 43,
+            50, 55, 62, 68, 72, 81, 10, 14, 50, 61, 62, 68, 81, 16, 17, 18, 19, 59, 66, 69, 16, 18,
+            19, 59, 66, 69, 27, 65, 70, 74, 26, 27, 65, 70, 74, 53, 71, 53, 71, 5, 6, 9, 55, 60,
+            72, 80, 5, 6, 7, 50, 60, 68, 72, 80, 81, 1, 2, 54, 55, 64, 73, 1, 2, 55, 64, 73, 79,
+            26, 27, 70, 74, 75, 22, 23, 26, 27, 65, 70, 74, 75, 21, 22, 26, 49, 74, 75, 21, 22, 23,
+            26, 49, 74, 75, 40, 76, 40, 76, 25, 28, 32, 52, 77, 25, 28, 32, 46, 52, 77, 82, 3, 4,
+            5, 7, 8, 78, 4, 8, 78, 2, 55, 64, 73, 79, 2, 55, 64, 79, 5, 6, 9, 10, 55, 60, 61, 62,
+            72, 80, 5, 6, 7, 9, 60, 61, 72, 80, 6, 9, 10, 42, 43, 50, 55, 61, 62, 68, 72, 81, 10,
+            14, 50, 61, 62, 68, 81, 28, 46, 52, 77, 82, 46, 52, 82,
+        ],
+    };
 const BEACON_POSITIONS: [::kartoffel_gps::GlobalPos; 83] = [
     ::kartoffel_gps::pos::pos_east_south(24, -37),
     ::kartoffel_gps::pos::pos_east_south(25, -26),
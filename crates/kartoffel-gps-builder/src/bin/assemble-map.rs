@@ -0,0 +1,36 @@
+use anyhow::{Context, anyhow};
+use async_kartoffel_generic::Vec2;
+use kartoffel_gps_builder::map_dump::MapDump;
+
+const USAGE: &str =
+    "usage: assemble-map <output> <width> <height> <start east> <start south> <log>...";
+
+/// Reassembles the map chunks sent by a bot over serial into an incomplete map, that can be
+/// combined with others by `combine-map`. The start is the position of the bot at the beginning
+/// of its run, counted from the north-west corner of the map.
+fn main() -> anyhow::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [output, width, height, east, south, logs @ ..] = args.as_slice() else {
+        return Err(anyhow!(USAGE));
+    };
+    if logs.is_empty() {
+        return Err(anyhow!(USAGE));
+    }
+
+    let mut dump = MapDump::new();
+    for path in logs {
+        let log = std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+        let n_invalid = dump.add_log(&log);
+        if n_invalid > 0 {
+            eprintln!("{path}: {n_invalid} invalid frames");
+        }
+    }
+    println!("chunks: {}", dump.len());
+
+    let start = Vec2::new_east_south(east.parse()?, south.parse()?);
+    let map = dump.to_incomplete_map(width.parse()?, height.parse()?, start)?;
+    map.write_file(output)?;
+
+    println!("done");
+    Ok(())
+}
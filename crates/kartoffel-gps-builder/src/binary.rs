@@ -0,0 +1,316 @@
+use std::{env, fs::File, io::Write, path::Path};
+
+use anyhow::{anyhow, ensure};
+use kartoffel_gps::{
+    GlobalPos,
+    binary::{
+        FORMAT_VERSION, HEADER_LEN, MAGIC, SECTION_ENTRY_LEN, TAG_AMBIGUOUS, TAG_BEACON_INFO,
+        TAG_BEACONS, TAG_GRAPH_DATA, TAG_GRAPH_NODES, TAG_MAP, TAG_POSITIONS, TAG_ROTATION_UNIQUE,
+        TAG_TOLERANT, TAG_UNIQUE, Tag, checksum,
+    },
+    gps::{MapSectionTrait, Section, SectionShape, Square},
+};
+
+use crate::{
+    codegen::{Beacons, MapAssets, east_south},
+    const_tolerant_index::TolerantIndexBuilder,
+    map::Map,
+};
+
+impl MapAssets {
+    /// The assets in the binary format of [`kartoffel_gps::binary`], to be parsed with
+    /// [`Assets::parse`](kartoffel_gps::binary::Assets::parse).
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        match self.gps_window {
+            3 => self.to_bytes_with::<Square<3>>(),
+            5 => self.to_bytes_with::<Square<5>>(),
+            7 => self.to_bytes_with::<Square<7>>(),
+            9 => self.to_bytes_with::<Square<9>>(),
+            n => Err(anyhow!("gps_window should be 3, 5, 7 or 9, not {}", n)),
+        }
+    }
+
+    /// Write the binary assets into `$OUT_DIR/<file_name>`, to be embedded with
+    /// [`include_assets!`](kartoffel_gps::include_assets). To be called from a build script.
+    pub fn embed_binary(&self, file_name: &str) -> anyhow::Result<()> {
+        if let Some(path) = &self.path {
+            println!("cargo::rerun-if-changed={}", path.display());
+        }
+        let out_dir = env::var("OUT_DIR").map_err(|_| anyhow!("should run in a build script"))?;
+        File::create(Path::new(&out_dir).join(file_name))?.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    fn to_bytes_with<S: SectionShape>(&self) -> anyhow::Result<Vec<u8>> {
+        ensure!(
+            (1..=255).contains(&self.tolerant_blocks),
+            "the tolerant index needs 1 to 255 blocks"
+        );
+        let map = &self.map;
+        let beacons = Beacons::new(map, self.max_beacon_dist)?;
+        let (ambiguous, positions) = ambiguous_records::<S>(map, self.ambiguous_positions)?;
+        let tolerant = TolerantIndexBuilder::from_map::<Section<S>>(map, self.tolerant_blocks);
+
+        let info = &beacons.info;
+        let beacon_info = [
+            info.max_beacon_dist,
+            info.max_beacons_entry,
+            info.max_beacons_exit,
+            info.max_path_length,
+            info.n_beacons,
+        ];
+        let nodes = beacons.graph.nodes().iter();
+        let sections = [
+            (TAG_MAP, map_bits(map)?),
+            (
+                TAG_BEACON_INFO,
+                beacon_info.into_iter().flat_map(u32::to_le_bytes).collect(),
+            ),
+            (TAG_BEACONS, position_bytes(&beacons.positions)),
+            (
+                TAG_GRAPH_NODES,
+                u16_bytes(nodes.flat_map(|node| [node.start, node.mid, node.end])),
+            ),
+            (
+                TAG_GRAPH_DATA,
+                u16_bytes(beacons.graph.data().iter().copied()),
+            ),
+            (
+                TAG_UNIQUE,
+                records(map.unique_chunks::<Section<S>>().0, |center| {
+                    east_south(center).to_vec()
+                }),
+            ),
+            (TAG_AMBIGUOUS, ambiguous),
+            (TAG_POSITIONS, positions),
+            (
+                TAG_ROTATION_UNIQUE,
+                records(map.rotation_unique_chunks::<Section<S>>().0, |_| Vec::new()),
+            ),
+            (TAG_TOLERANT, u16_bytes(tolerant.sorted().into_iter())),
+        ];
+
+        let mut bytes = vec![0; HEADER_LEN + sections.len() * SECTION_ENTRY_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4..6].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes[6..8].copy_from_slice(&u16::try_from(sections.len())?.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.map_hash().to_le_bytes());
+        bytes[24] = u8::try_from(self.gps_window)?;
+        bytes[25] = u8::try_from(tolerant.n_blocks())?;
+        for (i, (tag, data)) in sections.iter().enumerate() {
+            bytes.resize(bytes.len().next_multiple_of(4), 0);
+            let (offset, entry) = (bytes.len(), HEADER_LEN + i * SECTION_ENTRY_LEN);
+            write_entry(
+                &mut bytes[entry..][..SECTION_ENTRY_LEN],
+                *tag,
+                offset,
+                data.len(),
+            )?;
+            bytes.extend_from_slice(data);
+        }
+        let len = u32::try_from(bytes.len())?;
+        bytes[12..16].copy_from_slice(&len.to_le_bytes());
+        let checksum = checksum(&bytes[12..]);
+        bytes[8..12].copy_from_slice(&checksum.to_le_bytes());
+        Ok(bytes)
+    }
+}
+
+fn write_entry(entry: &mut [u8], tag: Tag, offset: usize, len: usize) -> anyhow::Result<()> {
+    entry[..4].copy_from_slice(&tag);
+    entry[4..8].copy_from_slice(&u32::try_from(offset)?.to_le_bytes());
+    entry[8..12].copy_from_slice(&u32::try_from(len)?.to_le_bytes());
+    Ok(())
+}
+
+/// width, height and the walkable tiles as bits, row by row
+fn map_bits(map: &Map) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&u16::try_from(map.width)?.to_le_bytes());
+    bytes.extend_from_slice(&u16::try_from(map.height)?.to_le_bytes());
+    let mut bits = vec![0u8; map.tiles.len().div_ceil(8)];
+    for (i, &walkable) in map.tiles.iter().enumerate() {
+        bits[i / 8] |= u8::from(walkable) << (i % 8);
+    }
+    bytes.extend(bits);
+    Ok(bytes)
+}
+
+fn u16_bytes(values: impl Iterator<Item = u16>) -> Vec<u8> {
+    values.flat_map(u16::to_le_bytes).collect()
+}
+
+fn position_bytes(positions: &[GlobalPos]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for &pos in positions {
+        let vec = pos - GlobalPos::default();
+        bytes.extend_from_slice(&vec.east().to_le_bytes());
+        bytes.extend_from_slice(&vec.south().to_le_bytes());
+    }
+    bytes
+}
+
+/// compressed chunks followed by `value`, sorted by the chunk
+fn records<S: SectionShape>(
+    chunks: Vec<(Section<S>, GlobalPos)>,
+    value: impl Fn(GlobalPos) -> Vec<u8>,
+) -> Vec<u8> {
+    let mut records: Vec<_> = chunks
+        .into_iter()
+        .map(|(chunk, center)| {
+            let compressed = chunk.compress().expect("center should be walkable");
+            let mut record = compressed.as_ref().to_vec();
+            record.extend(value(center));
+            record
+        })
+        .collect();
+    records.sort();
+    records.concat()
+}
+
+/// records of the ambiguous chunks, sorted by the chunk, and the positions they point to
+fn ambiguous_records<S: SectionShape>(
+    map: &Map,
+    max_positions: usize,
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let mut chunks: Vec<_> = map
+        .ambiguous_chunks::<Section<S>>(max_positions)
+        .into_iter()
+        .map(|(chunk, locations)| {
+            let compressed = chunk.compress().expect("center should be walkable");
+            (compressed.as_ref().to_vec(), locations)
+        })
+        .collect();
+    chunks.sort();
+
+    let (mut records, mut positions) = (Vec::new(), Vec::new());
+    for (chunk, locations) in chunks {
+        let index = u16::try_from(positions.len() / 2)
+            .map_err(|_| anyhow!("too many ambiguous positions"))?;
+        records.extend(chunk);
+        records.extend(index.to_le_bytes());
+        records.push(u8::try_from(locations.len())?);
+        for location in locations {
+            positions.extend(east_south(location));
+        }
+    }
+    Ok((records, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use kartoffel_gps::{
+        assets::GpsTables,
+        binary::{Assets, ParseError},
+        const_graph::Graph,
+        gps::{MapSection, TolerantIndex},
+        map::TrueMap,
+        pos::pos_east_south,
+    };
+
+    use super::*;
+    use crate::codegen::tests::test_map;
+
+    /// copy of the bytes aligned to 4, like `include_assets!`
+    fn leak_aligned(bytes: &[u8]) -> &'static [u8] {
+        let buffer = vec![0u8; bytes.len() + 3].leak();
+        let offset = buffer.as_ptr().align_offset(4);
+        let aligned = &mut buffer[offset..][..bytes.len()];
+        aligned.copy_from_slice(bytes);
+        aligned
+    }
+
+    fn test_assets() -> MapAssets {
+        MapAssets::new(test_map())
+            .gps_window(3)
+            .max_beacon_dist(3)
+            .ambiguous_positions(4)
+    }
+
+    /// compares with the builder; `kartoffel-gps-assets-test` compiles the generated module and
+    /// checks that its lookups agree with the binary assets of the same map
+    #[test]
+    fn round_trip() {
+        let assets = test_assets();
+        let parsed = Assets::parse(leak_aligned(&assets.to_bytes().unwrap())).unwrap();
+        let map = assets.map();
+
+        assert_eq!(parsed.map_hash(), assets.map_hash());
+        assert_eq!(parsed.gps_window(), 3);
+        let true_map = parsed.true_map();
+        assert_eq!(usize::from(true_map.width()), map.width);
+        assert_eq!(usize::from(true_map.height()), map.height);
+        for south in -1..=map.height as i16 {
+            for east in -1..=map.width as i16 {
+                let pos = pos_east_south(east, south);
+                assert_eq!(true_map.get(pos), map.get(pos));
+            }
+        }
+
+        let beacons = Beacons::new(map, 3).unwrap();
+        assert_eq!(parsed.beacons(), beacons.positions);
+        assert_eq!(parsed.beacon_info(), beacons.info);
+        let graph = parsed.beacon_graph();
+        let data = beacons.graph.data();
+        assert_eq!(usize::from(graph.size()), beacons.graph.nodes().len());
+        for (i, node) in beacons.graph.nodes().iter().enumerate() {
+            let i = u16::try_from(i).unwrap();
+            assert_eq!(graph.before(i), &data[node.start.into()..node.mid.into()]);
+            assert_eq!(graph.after(i), &data[node.mid.into()..node.end.into()]);
+        }
+
+        let gps = parsed.gps::<Square<3>>().unwrap();
+        let (unique, _) = map.unique_chunks::<MapSection<3>>();
+        assert_eq!(gps.n_unique(), unique.len());
+        for (chunk, center) in &unique {
+            assert_eq!(gps.get_global_pos(chunk), Some(*center));
+        }
+        let ambiguous = map.ambiguous_chunks::<MapSection<3>>(4);
+        assert!(!ambiguous.is_empty());
+        for (chunk, locations) in ambiguous {
+            assert_eq!(gps.pos_candidates(&chunk).collect::<Vec<_>>(), locations);
+        }
+        let (rotation_unique, _) = map.rotation_unique_chunks::<MapSection<3>>();
+        let n_rotation_unique = unique
+            .iter()
+            .filter(|(chunk, _)| {
+                let compressed = chunk.compress().unwrap();
+                gps.tables.rotation_unique(compressed.as_ref())
+            })
+            .count();
+        assert_eq!(n_rotation_unique, rotation_unique.len());
+
+        let tolerant = TolerantIndexBuilder::from_map::<MapSection<3>>(map, 3);
+        let TolerantIndex { n_blocks, sorted } = gps.tolerant_index;
+        assert_eq!(n_blocks, tolerant.n_blocks());
+        assert_eq!(sorted, tolerant.sorted());
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = test_assets().to_bytes().unwrap();
+        let parsed = Assets::parse(leak_aligned(&bytes)).unwrap();
+        assert_eq!(parsed.gps::<Square<5>>().err(), Some(ParseError::Shape));
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            Assets::parse(leak_aligned(&corrupted)).err(),
+            Some(ParseError::Checksum)
+        );
+        assert_eq!(
+            Assets::parse(leak_aligned(&bytes[..bytes.len() - 1])).err(),
+            Some(ParseError::TooShort)
+        );
+        let mut version = bytes.clone();
+        version[4] = 0xff;
+        assert_eq!(
+            Assets::parse(leak_aligned(&version)).err(),
+            Some(ParseError::Version(0xff))
+        );
+        assert_eq!(
+            Assets::parse(&leak_aligned(&[&[0], bytes.as_slice()].concat())[1..]).err(),
+            Some(ParseError::Misaligned)
+        );
+    }
+}
@@ -0,0 +1,560 @@
+use std::{
+    env,
+    fs::File,
+    io::{BufWriter, Write},
+    ops::Div,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, ensure};
+use kartoffel_gps::{
+    GlobalPos,
+    beacon::BeaconInfo,
+    gps::{MapSection, MapSectionTrait},
+    pos::pos_east_south,
+};
+use phf_codegen::{Map as PhfMap, Set as PhfSet};
+
+use crate::{
+    beacon_nav::{build_trivial_navigation_graph, find_beacons, get_beacon_info},
+    const_global_pos::ArrayBuilder,
+    const_graph::ConstSparseGraphBuilder,
+    const_tolerant_index::TolerantIndexBuilder,
+    map::Map,
+};
+
+/// path of the `phf` re-export, so the bot crate does not need to depend on `phf` itself
+const PHF_PATH: &str = "::kartoffel_gps::phf";
+
+/// Everything a bot needs to know about a map, generated in a build script: the map itself, the
+/// GPS tables and the navigation beacons. Written as a self-contained module with the accessors
+/// `true_map()`, `gps()` and `navigator_resources()`, and the [`AssetMetadata`] `METADATA`.
+///
+/// ```no_run
+/// // in `main` of build.rs
+/// use kartoffel_gps_builder::codegen::MapAssets;
+/// MapAssets::from_path("maps/map-grotta.txt")?.embed("map.rs")?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+/// and in the bot crate:
+/// ```ignore
+/// mod map {
+///     include!(concat!(env!("OUT_DIR"), "/map.rs"));
+/// }
+/// ```
+///
+/// [`AssetMetadata`]: kartoffel_gps::assets::AssetMetadata
+#[derive(Debug, Clone)]
+pub struct MapAssets {
+    pub(crate) map: Map,
+    /// file the map was read from, cargo reruns the build script if it changes
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) gps_window: usize,
+    pub(crate) max_beacon_dist: u32,
+    pub(crate) ambiguous_positions: usize,
+    pub(crate) tolerant_blocks: usize,
+}
+
+impl MapAssets {
+    pub fn new(map: Map) -> Self {
+        Self {
+            map,
+            path: None,
+            gps_window: 7,
+            max_beacon_dist: 5,
+            ambiguous_positions: 16,
+            tolerant_blocks: 3,
+        }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut assets = Self::new(Map::from_path(&path)?);
+        assets.path = Some(path.as_ref().to_owned());
+        Ok(assets)
+    }
+
+    /// side length of the square GPS window: 3, 5, 7 (default) or 9
+    pub fn gps_window(mut self, gps_window: usize) -> Self {
+        self.gps_window = gps_window;
+        self
+    }
+
+    /// maximum distance from any walkable position to the next navigation beacon, default 5
+    pub fn max_beacon_dist(mut self, max_beacon_dist: u32) -> Self {
+        self.max_beacon_dist = max_beacon_dist;
+        self
+    }
+
+    /// chunks occurring at up to this many positions are stored for the `Localizer`, default 16
+    pub fn ambiguous_positions(mut self, ambiguous_positions: usize) -> Self {
+        self.ambiguous_positions = ambiguous_positions;
+        self
+    }
+
+    /// blocks of the tolerant index, scans with fewer wrong tiles are always matched, default 3
+    pub fn tolerant_blocks(mut self, tolerant_blocks: usize) -> Self {
+        self.tolerant_blocks = tolerant_blocks;
+        self
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// FNV-1a hash of the size and the walkable tiles, stored in the metadata
+    pub fn map_hash(&self) -> u64 {
+        let mut hash = 0xcbf29ce484222325u64;
+        let width = self.map.width as u64;
+        let height = self.map.height as u64;
+        let bytes = width.to_le_bytes().into_iter().chain(height.to_le_bytes());
+        for byte in bytes.chain(self.map.tiles.iter().map(|&walkable| walkable as u8)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+
+    /// Write the items into `$OUT_DIR/<file_name>`, to be `include!`d into a module. To be called
+    /// from a build script, cargo is told to rerun it if the map file changes.
+    pub fn embed(&self, file_name: &str) -> anyhow::Result<()> {
+        if let Some(path) = &self.path {
+            println!("cargo::rerun-if-changed={}", path.display());
+        }
+        let out_dir = env::var("OUT_DIR").map_err(|_| anyhow!("should run in a build script"))?;
+        let file = &mut BufWriter::new(File::create(Path::new(&out_dir).join(file_name))?);
+        self.write_items(file)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Write the items wrapped into `pub mod <name>`.
+    pub fn write_module(&self, file: &mut impl Write, name: &str) -> anyhow::Result<()> {
+        writeln!(file, "pub mod {} {{", name)?;
+        self.write_items(file)?;
+        writeln!(file, "}}\n")?;
+        Ok(())
+    }
+
+    /// Write the items of the module. They only refer to `kartoffel_gps`, which has to be a
+    /// dependency of the bot crate.
+    pub fn write_items(&self, file: &mut impl Write) -> anyhow::Result<()> {
+        match self.gps_window {
+            3 => self.write_items_with::<MapSection<3>>(file),
+            5 => self.write_items_with::<MapSection<5>>(file),
+            7 => self.write_items_with::<MapSection<7>>(file),
+            9 => self.write_items_with::<MapSection<9>>(file),
+            n => Err(anyhow!("gps_window should be 3, 5, 7 or 9, not {}", n)),
+        }
+    }
+
+    fn write_items_with<T: MapSectionTrait>(&self, file: &mut impl Write) -> anyhow::Result<()> {
+        ensure!(
+            self.tolerant_blocks > 0,
+            "the tolerant index needs at least one block"
+        );
+        let map = &self.map;
+        let builder = map.builder();
+        let true_map_type = builder.type_string();
+        writeln!(
+            file,
+            "pub const METADATA: ::kartoffel_gps::assets::AssetMetadata = \
+            ::kartoffel_gps::assets::AssetMetadata {{
+    version: {:?},
+    map_hash: {:#x},
+    width: {},
+    height: {},
+    gps_window: {},
+    max_beacon_dist: {},
+}};
+const _: () = assert!(
+    METADATA.is_compatible(),
+    \"map assets were generated by a different version of kartoffel-gps-builder\"
+);
+",
+            env!("CARGO_PKG_VERSION"),
+            self.map_hash(),
+            map.width,
+            map.height,
+            self.gps_window,
+            self.max_beacon_dist,
+        )?;
+        writeln!(
+            file,
+            "pub const CHUNK_SIZE: usize = {0};
+pub type ChunkShape = ::kartoffel_gps::gps::Square<{0}>;
+pub type Chunk = ::kartoffel_gps::gps::Section<ChunkShape>;
+/// radar size matching [`CHUNK_SIZE`]
+pub type ChunkSize = <ChunkShape as ::kartoffel_gps::gps::SectionShape>::Size;
+/// [`Localizer`](::kartoffel_gps::localize::Localizer) that can hold all candidates of an
+/// ambiguous chunk
+pub type MapLocalizer = ::kartoffel_gps::localize::Localizer<{1}>;
+",
+            self.gps_window, self.ambiguous_positions,
+        )?;
+
+        writeln!(file, "const TRUE_MAP: {} = {};\n", true_map_type, builder)?;
+        write_gps::<T>(file, map)?;
+        write_gps_ambiguous::<T>(file, map, self.ambiguous_positions)?;
+        write_gps_rotation_unique::<T>(file, map)?;
+        write_gps_tolerant::<T>(file, map, self.tolerant_blocks)?;
+        write_beacons(file, map, self.max_beacon_dist)?;
+
+        writeln!(
+            file,
+            "pub type MapGps = ::kartoffel_gps::assets::Gps<
+    'static,
+    {0},
+    ::kartoffel_gps::assets::PhfTables<'static, ChunkShape>,
+>;
+
+static GPS: MapGps = ::kartoffel_gps::assets::Gps {{
+    map: &TRUE_MAP,
+    tables: ::kartoffel_gps::assets::PhfTables {{
+        unique_chunks: &UNIQUE_CHUNKS,
+        ambiguous_chunks: &AMBIGUOUS_CHUNKS,
+        ambiguous_positions: &AMBIGUOUS_POSITIONS,
+        rotation_unique_chunks: &ROTATION_UNIQUE_CHUNKS,
+    }},
+    tolerant_index: TOLERANT_INDEX,
+}};
+
+pub fn true_map() -> &'static {0} {{
+    &TRUE_MAP
+}}
+
+pub fn gps() -> &'static MapGps {{
+    &GPS
+}}
+
+pub fn beacons() -> &'static [::kartoffel_gps::GlobalPos] {{
+    &BEACON_POSITIONS
+}}
+
+pub fn beacon_graph() -> &'static impl ::kartoffel_gps::const_graph::Graph {{
+    &BEACON_GRAPH
+}}
+
+pub fn beacons_before(index: u16) -> &'static [u16] {{
+    ::kartoffel_gps::const_graph::Graph::after(&BEACON_GRAPH, index)
+}}
+
+pub fn beacon_info() -> &'static ::kartoffel_gps::beacon::BeaconInfo {{
+    &BEACON_INFO
+}}
+
+pub fn get_navigator_info() -> (usize, usize, usize, usize) {{
+    (
+        NAV_MAX_PATH_LEN,
+        NAV_MAX_ENTRY_EXIT,
+        NAV_TRIV_BUFFER,
+        NAV_NODE_BUFFER,
+    )
+}}
+
+/// Allocates the heap buffers used for the Navigator, and stores references to the map, beacons,
+/// and beacon graph.
+pub fn navigator_resources() -> impl ::kartoffel_gps::beacon::NavigatorResources {{
+    ::kartoffel_gps::beacon::NavigatorResourcesImpl::<
+        NAV_MAX_PATH_LEN,
+        NAV_MAX_ENTRY_EXIT,
+        NAV_TRIV_BUFFER,
+        NAV_NODE_BUFFER,
+        NAV_ACTIVE_BUFFER,
+        _,
+        _,
+    >::new(
+        &TRUE_MAP,
+        &BEACON_GRAPH,
+        &BEACON_POSITIONS,
+        BEACON_INFO.max_beacon_dist as u16,
+    )
+}}",
+            true_map_type
+        )?;
+        Ok(())
+    }
+}
+
+/// Navigation beacons of a map, shared by the source and the binary assets.
+pub(crate) struct Beacons {
+    pub positions: Vec<GlobalPos>,
+    pub graph: ConstSparseGraphBuilder,
+    pub info: BeaconInfo,
+}
+
+impl Beacons {
+    pub fn new(map: &Map, max_beacon_dist: u32) -> anyhow::Result<Self> {
+        let positions = map.walkable_positions();
+        let asymmetric_graph = build_trivial_navigation_graph(map, &positions);
+        let beacon_indices = find_beacons(max_beacon_dist, &asymmetric_graph);
+        let beacon_positions = asymmetric_graph.get_map_start().subset(&beacon_indices);
+        let beacon_graph = asymmetric_graph.sub_graph(&beacon_positions, &beacon_positions);
+
+        let info = get_beacon_info(
+            &beacon_indices,
+            &positions,
+            &asymmetric_graph,
+            &beacon_graph,
+            max_beacon_dist,
+        );
+        ensure!(
+            u16::try_from(info.max_beacon_dist).is_ok(),
+            "max_beacon_dist too large"
+        );
+        Ok(Self {
+            positions: beacon_positions.vec().clone(),
+            graph: ConstSparseGraphBuilder::from_graph(&beacon_graph),
+            info,
+        })
+    }
+}
+
+fn write_beacons(file: &mut impl Write, map: &Map, max_beacon_dist: u32) -> anyhow::Result<()> {
+    let Beacons {
+        positions: beacon_positions,
+        graph: builder_graph,
+        info: beacon_info,
+    } = Beacons::new(map, max_beacon_dist)?;
+    let builder_pos = ArrayBuilder(&beacon_positions);
+    writeln!(
+        file,
+        "const BEACON_GRAPH: {} = {};\n",
+        builder_graph.type_string(),
+        builder_graph
+    )?;
+    writeln!(
+        file,
+        "const BEACON_POSITIONS: {} = {};\n",
+        builder_pos.type_string(),
+        builder_pos
+    )?;
+    writeln!(
+        file,
+        "const BEACON_INFO: ::kartoffel_gps::beacon::BeaconInfo = ::kartoffel_gps::beacon::{:?};\n",
+        beacon_info
+    )?;
+    writeln!(
+        file,
+        "const NAV_MAX_PATH_LEN: usize = {};
+const NAV_MAX_ENTRY_EXIT: usize = {};
+const NAV_TRIV_BUFFER: usize = {};
+const NAV_NODE_BUFFER: usize = {};
+const NAV_ACTIVE_BUFFER: usize = {};
+",
+        (beacon_info.max_path_length * 2).next_power_of_two(), // multiply by two to hedge against (very unlikely) path updates
+        beacon_info
+            .max_beacons_entry
+            .max(beacon_info.max_beacons_exit)
+            .next_power_of_two(),
+        (beacon_info.max_beacon_dist + 2).div(2),
+        beacon_info.n_beacons + 2,
+        (beacon_info.n_beacons + 2).next_power_of_two(),
+    )?;
+
+    let mut map_chars: Vec<Vec<char>> = Vec::new();
+    for row in 0..map.height {
+        let mut row_chars = Vec::new();
+        for col in 0..map.width {
+            let pos = pos_east_south(col as i16, row as i16);
+            row_chars.push(if map.get(pos) { '.' } else { '#' });
+        }
+        map_chars.push(row_chars);
+    }
+    for pos in &beacon_positions {
+        let row = pos.subtract_anchor().south() as usize;
+        let col = pos.subtract_anchor().east() as usize;
+        map_chars[row][col] = '*';
+    }
+    writeln!(file, "// beacons")?;
+    for row in map_chars {
+        writeln!(file, "// {}", row.into_iter().collect::<String>())?;
+    }
+    writeln!(file)?;
+    Ok(())
+}
+
+/// [east, south] of a position, as stored in the GPS tables
+pub(crate) fn east_south(pos: GlobalPos) -> [u8; 2] {
+    let vec = pos - GlobalPos::default();
+    let east = u8::try_from(vec.east()).expect("center should be east (right)");
+    let south = u8::try_from(vec.south()).expect("center should be south (left)");
+    [east, south]
+}
+
+fn write_gps<T: MapSectionTrait>(file: &mut impl Write, map: &Map) -> anyhow::Result<()> {
+    let (unique_chunks, n_total) = map.unique_chunks::<T>();
+    writeln!(
+        file,
+        "// {} of {} chunks are unique",
+        unique_chunks.len(),
+        n_total
+    )?;
+
+    let mut builder = PhfMap::new();
+    builder.phf_path(PHF_PATH);
+    for (chunk, center) in unique_chunks {
+        builder.entry(
+            chunk.compress().expect("center should be walkable"),
+            &format!("{:?}", east_south(center)),
+        );
+    }
+
+    writeln!(
+        file,
+        "static UNIQUE_CHUNKS: {}::Map<{}, [u8; 2]> = {};\n",
+        PHF_PATH,
+        <T>::compressed_type(),
+        builder.build()
+    )?;
+    Ok(())
+}
+
+/// Table of chunks occuring at multiple positions. The phf map points into a flat array of
+/// positions, as (index, length).
+fn write_gps_ambiguous<T: MapSectionTrait>(
+    file: &mut impl Write,
+    map: &Map,
+    max_positions: usize,
+) -> anyhow::Result<()> {
+    let ambiguous_chunks = map.ambiguous_chunks::<T>(max_positions);
+    writeln!(
+        file,
+        "// {} chunks occur at up to {} positions",
+        ambiguous_chunks.len(),
+        max_positions
+    )?;
+
+    let mut builder = PhfMap::new();
+    builder.phf_path(PHF_PATH);
+    let mut positions = Vec::new();
+    for (chunk, locations) in &ambiguous_chunks {
+        let index = u16::try_from(positions.len()).expect("too many ambiguous positions");
+        builder.entry(
+            chunk.compress().expect("center should be walkable"),
+            &format!("({}, {})", index, locations.len()),
+        );
+        for &location in locations {
+            positions.push(format!("{:?}", east_south(location)));
+        }
+    }
+
+    writeln!(
+        file,
+        "static AMBIGUOUS_CHUNKS: {}::Map<{}, (u16, u8)> = {};\n",
+        PHF_PATH,
+        <T>::compressed_type(),
+        builder.build()
+    )?;
+    writeln!(
+        file,
+        "static AMBIGUOUS_POSITIONS: [[u8; 2]; {}] = [{}];\n",
+        positions.len(),
+        positions.join(", ")
+    )?;
+    Ok(())
+}
+
+/// Set of unique chunks that can be matched without knowing the facing, the positions are looked up
+/// in `UNIQUE_CHUNKS`.
+fn write_gps_rotation_unique<T: MapSectionTrait>(
+    file: &mut impl Write,
+    map: &Map,
+) -> anyhow::Result<()> {
+    let (rotation_unique_chunks, n_unique) = map.rotation_unique_chunks::<T>();
+    writeln!(
+        file,
+        "// {} of {} unique chunks remain unique under rotation",
+        rotation_unique_chunks.len(),
+        n_unique
+    )?;
+
+    let mut builder = PhfSet::new();
+    builder.phf_path(PHF_PATH);
+    for (chunk, _) in rotation_unique_chunks {
+        builder.entry(chunk.compress().expect("center should be walkable"));
+    }
+
+    writeln!(
+        file,
+        "static ROTATION_UNIQUE_CHUNKS: {}::Set<{}> = {};\n",
+        PHF_PATH,
+        <T>::compressed_type(),
+        builder.build()
+    )?;
+    Ok(())
+}
+
+/// Index for matching scans with unknown or wrong tiles, finds all matches with less than
+/// `n_blocks` wrong tiles.
+fn write_gps_tolerant<T: MapSectionTrait>(
+    file: &mut impl Write,
+    map: &Map,
+    n_blocks: usize,
+) -> anyhow::Result<()> {
+    let builder = TolerantIndexBuilder::from_map::<T>(map, n_blocks);
+    writeln!(
+        file,
+        "static TOLERANT_INDEX: {} = {};\n",
+        builder.type_string(),
+        builder
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn test_map() -> Map {
+        let lines = [
+            "###########",
+            "#.........#",
+            "#.#.#.#.#.#",
+            "#.#.#...#.#",
+            "#.........#",
+            "###########",
+        ];
+        Map {
+            tiles: lines
+                .iter()
+                .flat_map(|line| line.chars().map(|c| c == '.'))
+                .collect(),
+            width: lines[0].len(),
+            height: lines.len(),
+        }
+    }
+
+    #[test]
+    fn self_contained_module() {
+        let assets = MapAssets::new(test_map()).gps_window(5).max_beacon_dist(3);
+        let mut out = Vec::new();
+        assets.write_module(&mut out, "test_map").unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with("pub mod test_map {"));
+        assert!(out.contains(&format!("map_hash: {:#x},", assets.map_hash())));
+        assert!(out.contains("gps_window: 5,"));
+        assert!(out.contains("Localizer<16>"));
+        for accessor in ["true_map", "gps", "navigator_resources"] {
+            assert!(out.contains(&format!("pub fn {}()", accessor)));
+        }
+        // only the re-exports of kartoffel_gps are used
+        assert!(!out.contains(" ::phf::"));
+        assert!(out.contains("::kartoffel_gps::phf::Map"));
+    }
+
+    #[test]
+    fn map_hash() {
+        let assets = MapAssets::new(test_map());
+        let mut changed = test_map();
+        changed.tiles[12] = false;
+        assert_eq!(assets.map_hash(), MapAssets::new(test_map()).map_hash());
+        assert_ne!(assets.map_hash(), MapAssets::new(changed).map_hash());
+        assert!(
+            MapAssets::new(test_map())
+                .gps_window(4)
+                .write_items(&mut Vec::new())
+                .is_err()
+        );
+    }
+}
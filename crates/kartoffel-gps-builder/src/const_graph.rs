@@ -58,6 +58,15 @@ impl ConstSparseGraphBuilder {
         self.data.extend_from_slice(after);
     }
 
+    pub fn nodes(&self) -> &[ConstSparseGraphNode] {
+        &self.nodes
+    }
+
+    /// `before` and `after` of all nodes, the nodes point into it
+    pub fn data(&self) -> &[u16] {
+        &self.data
+    }
+
     pub fn type_string(&self) -> String {
         assert!(self.is_valid());
         format!(
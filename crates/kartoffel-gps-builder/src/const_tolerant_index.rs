@@ -0,0 +1,153 @@
+use core::fmt::Display;
+
+use async_kartoffel_generic::Vec2;
+use kartoffel_gps::{
+    GlobalPos,
+    gps::{MapSectionTrait, block_value},
+};
+
+use crate::map::Map;
+
+/// Generates a [`kartoffel_gps::gps::TolerantIndex`] with `n_blocks` blocks.
+pub struct TolerantIndexBuilder {
+    blocks: Vec<Vec<u16>>,
+}
+
+impl TolerantIndexBuilder {
+    pub fn from_map<T: MapSectionTrait>(map: &Map, n_blocks: usize) -> Self {
+        assert!(
+            map.width * map.height <= usize::from(u16::MAX) + 1,
+            "map should have at most 2^16 tiles"
+        );
+        let mut chunks = Vec::new();
+        for i_south in 0..map.height {
+            for i_east in 0..map.width {
+                let pos =
+                    GlobalPos::default() + Vec2::new_east_south(i_east as i16, i_south as i16);
+                let chunk = map.get_chunk::<T>(pos);
+                if chunk.at_center() {
+                    chunks.push(((i_east + i_south * map.width) as u16, chunk.bits()));
+                }
+            }
+        }
+        let blocks = (0..n_blocks)
+            .map(|block| {
+                let mut sorted = chunks.clone();
+                sorted
+                    .sort_by_key(|&(index, bits)| (block_value::<T>(bits, block, n_blocks), index));
+                sorted.into_iter().map(|(index, _)| index).collect()
+            })
+            .collect();
+        Self { blocks }
+    }
+
+    pub fn n_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// all blocks concatenated, see [`kartoffel_gps::gps::TolerantIndex::sorted`]
+    pub fn sorted(&self) -> Vec<u16> {
+        self.blocks.concat()
+    }
+
+    pub fn type_string(&self) -> String {
+        "::kartoffel_gps::gps::TolerantIndex<'static>".into()
+    }
+}
+
+impl Display for TolerantIndexBuilder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "::kartoffel_gps::gps::TolerantIndex {{ n_blocks: {}, sorted: &[",
+            self.n_blocks()
+        )?;
+        for block in &self.blocks {
+            write!(f, "    ")?;
+            for index in block {
+                write!(f, "{}, ", index)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "] }}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kartoffel_gps::{
+        gps::{MapSection, MaskedSection, TolerantIndex},
+        pos::pos_east_south,
+    };
+
+    use super::*;
+
+    #[test]
+    fn tolerant_matches() {
+        let map = Map::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../maps/map-grotta.txt"
+        ))
+        .unwrap();
+        let builder = TolerantIndexBuilder::from_map::<MapSection<7>>(&map, 3);
+        let sorted = builder.sorted();
+        let index = TolerantIndex {
+            n_blocks: builder.n_blocks(),
+            sorted: &sorted,
+        };
+
+        let positions = map.walkable_positions();
+        for &pos in positions.vec().iter().step_by(97) {
+            let mut query = MaskedSection {
+                section: map.get_chunk::<MapSection<7>>(pos),
+                known: MapSection::from_function(|_| true),
+            };
+            // one wrong and two unknown tiles
+            let flip = Vec2::new_east_south(2, -1);
+            query.section.set(flip, !map.get(pos + flip)).unwrap();
+            query.known.set(Vec2::new_east_south(-3, 3), false).unwrap();
+            query.known.set(Vec2::new_east_south(0, 2), false).unwrap();
+
+            let matches = index.matches::<_, 2048>(&query, 2, &map);
+            assert!(
+                matches.contains(&(pos, 1)),
+                "{} not found: {:?}",
+                pos,
+                matches
+            );
+            assert!(matches.windows(2).all(|w| w[0].1 <= w[1].1));
+        }
+
+        // exact matches are found as well
+        let pos = pos_east_south(14, 36);
+        let query = MaskedSection {
+            section: map.get_chunk::<MapSection<7>>(pos),
+            known: MapSection::from_function(|_| true),
+        };
+        assert_eq!(index.matches::<_, 4>(&query, 0, &map)[0], (pos, 0));
+    }
+
+    #[test]
+    fn more_blocks_than_bits() {
+        let map = Map::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../maps/map-grotta.txt"
+        ))
+        .unwrap();
+        // 8 bits in 10 blocks, some of them are empty
+        let builder = TolerantIndexBuilder::from_map::<MapSection<3>>(&map, 10);
+        let sorted = builder.sorted();
+        let index = TolerantIndex {
+            n_blocks: builder.n_blocks(),
+            sorted: &sorted,
+        };
+        let pos = pos_east_south(14, 36);
+        let query = MaskedSection {
+            section: map.get_chunk::<MapSection<3>>(pos),
+            known: MapSection::from_function(|_| true),
+        };
+        let matches = index.matches::<_, 2048>(&query, 0, &map);
+        assert!(matches.contains(&(pos, 0)));
+    }
+}
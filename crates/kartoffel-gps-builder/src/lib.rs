@@ -1,5 +1,9 @@
 pub mod beacon_nav;
+pub mod binary;
+pub mod codegen;
 pub mod const_global_pos;
 pub mod const_graph;
+pub mod const_tolerant_index;
 pub mod graph;
 pub mod map;
+pub mod map_dump;
@@ -1,7 +1,7 @@
 use anyhow::anyhow;
-use async_kartoffel_generic::{Tile, Vec2};
+use async_kartoffel_generic::{Global, Rotation, Tile, Vec2};
 use core::{default::Default, fmt::Display};
-use kartoffel_gps::{GlobalPos, gps::MapSectionTrait};
+use kartoffel_gps::{GlobalPos, gps::MapSectionTrait, map::TrueMap};
 use std::{
     collections::HashMap,
     fs::File,
@@ -16,7 +16,7 @@ pub struct IncompleteMap {
     pub height: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Map {
     pub tiles: Vec<bool>,
     pub width: usize,
@@ -149,6 +149,45 @@ impl Map {
         (unique_chunks, n_total)
     }
 
+    /// Unique chunks that stay unique if the facing is unknown: no rotation of them occurs anywhere
+    /// else, and they are not symmetric under rotation. Also returns the number of unique chunks.
+    pub fn rotation_unique_chunks<T: MapSectionTrait>(&self) -> (Vec<(T, GlobalPos)>, usize) {
+        let chunks = self.get_chunks::<T>();
+        let mut n_unique = 0;
+
+        let mut rotation_unique_chunks = Vec::new();
+        for (chunk, locations) in &chunks {
+            if locations.len() != 1 {
+                continue;
+            }
+            n_unique += 1;
+            if [Rotation::Left, Rotation::Right, Rotation::Inverse]
+                .into_iter()
+                .map(|rotation| chunk.rotate(rotation))
+                .all(|rotated| rotated != *chunk && !chunks.contains_key(&rotated))
+            {
+                rotation_unique_chunks.push((chunk.clone(), locations[0]));
+            }
+        }
+        (rotation_unique_chunks, n_unique)
+    }
+
+    /// Chunks that occur at 2 to `max_positions` positions, with their positions. Those can be used
+    /// to narrow down the position with `kartoffel_gps::localize::Localizer`.
+    pub fn ambiguous_chunks<T: MapSectionTrait>(
+        &self,
+        max_positions: usize,
+    ) -> Vec<(T, Vec<GlobalPos>)> {
+        let mut ambiguous_chunks = self
+            .get_chunks::<T>()
+            .into_iter()
+            .filter(|(_, locations)| (2..=max_positions).contains(&locations.len()))
+            .collect::<Vec<_>>();
+        // deterministic output
+        ambiguous_chunks.sort();
+        ambiguous_chunks
+    }
+
     pub fn get_chunk<T: MapSectionTrait>(&self, center: GlobalPos) -> T {
         T::from_function(|vec| self.get(center + vec))
     }
@@ -343,7 +382,7 @@ impl IncompleteMap {
     fn process_line(vec: &mut Vec<Option<bool>>, line: &str) -> anyhow::Result<()> {
         for char in line.chars() {
             let walkable = match char {
-                '↓' | '↑' | '→' | '←' => None,
+                '↓' | '↑' | '→' | '←' | '?' => None,
                 c => Some(
                     Tile::from_char(c)
                         .unwrap_or_else(|| panic!("encountered unknown char {}", c))
@@ -354,6 +393,51 @@ impl IncompleteMap {
         }
         Ok(())
     }
+
+    /// Like [`Map::write_file`], unknown tiles are written as `?`.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let file = File::create(path)?;
+        let mut file = LineWriter::new(file);
+
+        let mut first = true;
+        for line in self.tiles.chunks(self.width) {
+            if first {
+                first = false;
+            } else {
+                writeln!(file)?;
+            }
+            for &walkable in line {
+                let c = match walkable {
+                    Some(true) => ".",
+                    Some(false) => "#",
+                    None => "?",
+                };
+                write!(file, "{}", c)?;
+            }
+        }
+
+        file.flush()?;
+
+        Ok(())
+    }
+}
+
+impl TrueMap for Map {
+    fn get(&self, pos: GlobalPos) -> bool {
+        Map::get(self, pos)
+    }
+    fn vec_east(&self) -> Vec2<Global> {
+        Vec2::new_east(self.width as i16)
+    }
+    fn vec_south(&self) -> Vec2<Global> {
+        Vec2::new_south(self.height as i16)
+    }
+    fn width(&self) -> u16 {
+        self.width.try_into().unwrap()
+    }
+    fn height(&self) -> u16 {
+        self.height.try_into().unwrap()
+    }
 }
 
 pub struct TrueMapBuilder {
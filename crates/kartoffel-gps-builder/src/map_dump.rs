@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use async_algorithm::{ChunkLocation, ChunkTerrain, IterInChunk};
+use async_kartoffel_generic::{Global, Vec2};
+use kartoffel_telemetry::{Frames, Record};
+
+use crate::map::IncompleteMap;
+
+/// The map discovered by a bot, reassembled from the [`Record::Chunk`] telemetry in its serial
+/// logs (see `runner-slam`). Positions are relative to the start of the bot, so all logs have to
+/// come from the same run.
+#[derive(Debug, Default)]
+pub struct MapDump {
+    chunks: HashMap<ChunkLocation, ChunkTerrain>,
+}
+
+impl MapDump {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Collect the chunks of a serial log, a chunk sent later replaces the earlier one. Returns the
+    /// number of invalid frames.
+    pub fn add_log(&mut self, log: &str) -> usize {
+        let mut n_invalid = 0;
+        for frame in Frames::new(log) {
+            match frame {
+                Ok(event) => {
+                    if let Record::Chunk { location, terrain } = event.record {
+                        self.chunks.insert(location, terrain);
+                    }
+                }
+                Err(_) => n_invalid += 1,
+            }
+        }
+        n_invalid
+    }
+
+    /// number of collected chunks
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Place the known tiles on a map of the given size, where the bot started at `start` from the
+    /// north-west corner. Fails if a walkable tile lies outside of the map, then `start` is wrong.
+    pub fn to_incomplete_map(
+        &self,
+        width: usize,
+        height: usize,
+        start: Vec2<Global>,
+    ) -> anyhow::Result<IncompleteMap> {
+        let mut tiles = vec![None; width * height];
+        for (&location, terrain) in &self.chunks {
+            for index in IterInChunk::new() {
+                let Some(walkable) = terrain.get(index).is_walkable() else {
+                    continue;
+                };
+                let vec = start + (index + location).subtract_anchor();
+                match (usize::try_from(vec.east()), usize::try_from(vec.south())) {
+                    (Ok(east), Ok(south)) if east < width && south < height => {
+                        tiles[south * width + east] = Some(walkable);
+                    }
+                    // the radar sees a bit beyond the border of the map
+                    _ if !walkable => {}
+                    _ => {
+                        return Err(anyhow!(
+                            "walkable tile outside of the map at east {} south {}",
+                            vec.east(),
+                            vec.south()
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(IncompleteMap {
+            tiles,
+            width,
+            height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_algorithm::{ChunkMapHash, Map, Terrain, telemetry::write};
+    use async_kartoffel_generic::Position;
+    use kartoffel_telemetry::Event;
+
+    use super::*;
+
+    type BotMap = ChunkMapHash<4, Terrain, ChunkTerrain>;
+
+    fn pos(east: i16, south: i16) -> Position {
+        Position::add_to_anchor(Vec2::new_east_south(east, south))
+    }
+
+    /// serial log with the chunks containing `positions`
+    fn log(map: &BotMap, positions: &[Position]) -> String {
+        let mut log = String::from("async_kartoffel\n");
+        for &pos in positions {
+            let location = ChunkLocation::containing(pos);
+            let terrain = *map.chunk(location).unwrap();
+            let record = Record::Chunk { location, terrain };
+            write(&mut log, &Event { ticks: 0, record }).unwrap();
+            log.push('\n');
+        }
+        log
+    }
+
+    #[test]
+    fn reassemble() {
+        let mut map = BotMap::new();
+        // a corridor crossing a chunk border, with a wall to the north
+        for east in -2..=1 {
+            map.set(pos(east, 0), Terrain::Walkable).unwrap();
+            map.set(pos(east, -1), Terrain::Blocked).unwrap();
+        }
+        let first = log(&map, &[pos(-1, -1), pos(0, -1), pos(-1, 0), pos(0, 0)]);
+        map.set(pos(1, 0), Terrain::Blocked).unwrap();
+        let second = log(&map, &[pos(0, 0)]);
+
+        let mut dump = MapDump::new();
+        assert_eq!(dump.add_log(&format!("{first}<AAAA>{second}")), 1);
+        assert_eq!(dump.len(), 4);
+
+        let incomplete = dump
+            .to_incomplete_map(5, 2, Vec2::new_east_south(2, 1))
+            .unwrap();
+        let (walkable, blocked) = (Some(true), Some(false));
+        assert_eq!(
+            incomplete.tiles,
+            [
+                [blocked, blocked, blocked, blocked, None],
+                [walkable, walkable, walkable, blocked, None],
+            ]
+            .concat()
+        );
+        // the west end of the corridor would be outside
+        assert!(
+            dump.to_incomplete_map(5, 2, Vec2::new_east_south(1, 1))
+                .is_err()
+        );
+    }
+}
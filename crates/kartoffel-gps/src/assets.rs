@@ -0,0 +1,201 @@
+use async_kartoffel_generic::{Direction, Local, RadarScanTrait, Tile, Vec2};
+use heapless::Vec;
+use phf_shared::PhfBorrow;
+
+use crate::{
+    GlobalPos,
+    gps::{MapSectionTrait, MaskedSection, Section, SectionShape, TolerantIndex},
+    map::TrueMap,
+    pos::pos_east_south,
+};
+
+/// Describes the generated map assets, written by `kartoffel_gps_builder::codegen::MapAssets`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct AssetMetadata {
+    /// version of `kartoffel-gps-builder` that generated the assets
+    pub version: &'static str,
+    /// FNV-1a hash of the size and walkable tiles of the map
+    pub map_hash: u64,
+    pub width: u16,
+    pub height: u16,
+    pub gps_window: usize,
+    pub max_beacon_dist: u32,
+}
+
+impl AssetMetadata {
+    /// Whether the assets were generated by the builder of the same version as this crate, so the
+    /// generated code matches the types used here.
+    pub const fn is_compatible(&self) -> bool {
+        let (a, b) = (self.version.as_bytes(), VERSION.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// version of this crate, the builder writes the same into [`AssetMetadata::version`]
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Lookup tables for the GPS, generated by `kartoffel_gps_builder`. Positions are stored as
+/// `[east, south]`.
+pub trait GpsTables {
+    type Shape: SectionShape;
+    /// position of a chunk that occurs only once
+    fn unique(&self, chunk: &[u8]) -> Option<&[u8; 2]>;
+    /// positions of a chunk that occurs at a few positions, empty if it is unique or too common
+    fn ambiguous(&self, chunk: &[u8]) -> &[[u8; 2]];
+    /// whether a unique chunk can be matched without knowing the facing
+    fn rotation_unique(&self, chunk: &[u8]) -> bool;
+    fn n_unique(&self) -> usize;
+}
+
+/// [`GpsTables`] generated as Rust source, see `kartoffel_gps_builder::codegen::MapAssets`.
+#[derive(Debug)]
+pub struct PhfTables<'a, S: SectionShape> {
+    pub unique_chunks: &'a phf::Map<S::Compressed, [u8; 2]>,
+    /// chunks that occur at a few positions, as (index, length) into `ambiguous_positions`
+    pub ambiguous_chunks: &'a phf::Map<S::Compressed, (u16, u8)>,
+    pub ambiguous_positions: &'a [[u8; 2]],
+    pub rotation_unique_chunks: &'a phf::Set<S::Compressed>,
+}
+
+impl<S: SectionShape> GpsTables for PhfTables<'_, S>
+where
+    S::Compressed: PhfBorrow<[u8]>,
+{
+    type Shape = S;
+
+    fn unique(&self, chunk: &[u8]) -> Option<&[u8; 2]> {
+        self.unique_chunks.get(chunk)
+    }
+
+    fn ambiguous(&self, chunk: &[u8]) -> &[[u8; 2]] {
+        match self.ambiguous_chunks.get(chunk) {
+            Some(&(index, len)) => {
+                &self.ambiguous_positions[usize::from(index)..][..usize::from(len)]
+            }
+            None => &[],
+        }
+    }
+
+    fn rotation_unique(&self, chunk: &[u8]) -> bool {
+        self.rotation_unique_chunks.contains(chunk)
+    }
+
+    fn n_unique(&self) -> usize {
+        self.unique_chunks.len()
+    }
+}
+
+fn position([east, south]: [u8; 2]) -> GlobalPos {
+    pos_east_south(east.into(), south.into())
+}
+
+/// The GPS of a map: finds positions with the [`GpsTables`] (unique chunks, ambiguous chunks for
+/// the [`Localizer`](crate::localize::Localizer), chunks that are unique under rotation) and the
+/// [`TolerantIndex`].
+#[derive(Debug)]
+pub struct Gps<'a, M: TrueMap, T: GpsTables> {
+    pub map: &'a M,
+    pub tables: T,
+    pub tolerant_index: TolerantIndex<'a>,
+}
+
+impl<M: TrueMap, T: GpsTables> Gps<'_, M, T> {
+    pub fn get_global_pos(&self, chunk: &Section<T::Shape>) -> Option<GlobalPos> {
+        Some(position(*self.tables.unique(chunk.compress()?.as_ref())?))
+    }
+
+    /// Position and facing from a single scan, without knowing the facing. Only succeeds if the
+    /// chunk is unique under rotation.
+    pub fn get_global_pose(
+        &self,
+        scan: &impl RadarScanTrait<<T::Shape as SectionShape>::Size>,
+    ) -> Option<(GlobalPos, Direction)> {
+        Section::<T::Shape>::from_scan_any_facing(scan)
+            .into_iter()
+            .find_map(|(chunk, facing)| {
+                let compressed = chunk.compress()?;
+                if self.tables.rotation_unique(compressed.as_ref()) {
+                    Some((self.get_global_pos(&chunk)?, facing))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// All positions where the terrain looks like `chunk`, empty if there are too many (more
+    /// than the maximum of the ambiguous chunk table). Use with
+    /// [`Localizer`](crate::localize::Localizer).
+    pub fn pos_candidates(
+        &self,
+        chunk: &Section<T::Shape>,
+    ) -> impl Iterator<Item = GlobalPos> + use<'_, M, T> {
+        let positions = match chunk.compress() {
+            Some(compressed) => match self.tables.unique(compressed.as_ref()) {
+                Some(pos) => core::slice::from_ref(pos),
+                None => self.tables.ambiguous(compressed.as_ref()),
+            },
+            None => &[],
+        };
+        positions.iter().map(|&pos| position(pos))
+    }
+
+    /// All positions and facings that fit a scan, see [`Gps::pos_candidates`].
+    pub fn pose_candidates<R: RadarScanTrait<<T::Shape as SectionShape>::Size>>(
+        &self,
+        scan: &R,
+    ) -> impl Iterator<Item = (GlobalPos, Direction)> + use<'_, M, T, R> {
+        Section::<T::Shape>::from_scan_any_facing(scan)
+            .into_iter()
+            .flat_map(|(chunk, facing)| self.pos_candidates(&chunk).map(move |pos| (pos, facing)))
+    }
+
+    /// Positions whose terrain differs from the scan in at most `max_distance` tiles, sorted by
+    /// the distance. Void tiles are unknown. Finds all matches for `max_distance` below the
+    /// number of blocks of the tolerant index.
+    pub fn pos_tolerant<const N: usize>(
+        &self,
+        scan: &impl RadarScanTrait<<T::Shape as SectionShape>::Size>,
+        facing: Direction,
+        max_distance: u32,
+    ) -> Vec<(GlobalPos, u32), N> {
+        let query =
+            MaskedSection::<Section<T::Shape>>::from_scan(scan, facing, |tile| tile != Tile::Void);
+        self.tolerant_index.matches(&query, max_distance, self.map)
+    }
+
+    /// Whether the terrain around the bot occurs anywhere on this map, for any facing. `tile_at`
+    /// returns the scanned tile, None if the scan does not cover the GPS window. Void tiles are
+    /// outside of the arena, so they are known to be not walkable.
+    pub fn fits(&self, tile_at: impl Fn(Vec2<Local>) -> Option<Tile>) -> Option<bool> {
+        let mut any_fits = false;
+        for facing in Direction::all() {
+            let mut query = MaskedSection::<Section<T::Shape>>::default();
+            for vec in Section::<T::Shape>::tiles() {
+                let walkable = tile_at(vec.local(facing))?.is_walkable_terrain();
+                // unwrap: we know vec is in bounds
+                query.section.set(vec, walkable).unwrap();
+                query.known.set(vec, true).unwrap();
+            }
+            any_fits |= !self
+                .tolerant_index
+                .matches::<_, 1>(&query, 0, self.map)
+                .is_empty();
+        }
+        Some(any_fits)
+    }
+
+    /// number of positions that can be found with a single scan
+    pub fn n_unique(&self) -> usize {
+        self.tables.n_unique()
+    }
+}
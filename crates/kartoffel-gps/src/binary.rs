@@ -0,0 +1,430 @@
+//! Binary map assets, an alternative to the generated Rust source of
+//! `kartoffel_gps_builder::codegen::MapAssets` for large maps. The data is written by
+//! `MapAssets::to_bytes`, embedded with [`include_assets!`](crate::include_assets) and viewed in
+//! place, nothing is copied.
+//!
+//! All numbers are little endian. The file starts with a header:
+//!
+//! | offset | size | content                                              |
+//! |--------|------|------------------------------------------------------|
+//! | 0      | 4    | [`MAGIC`]                                            |
+//! | 4      | 2    | [`FORMAT_VERSION`]                                   |
+//! | 6      | 2    | number of sections                                   |
+//! | 8      | 4    | [`checksum`] of everything after this field          |
+//! | 12     | 4    | total length                                         |
+//! | 16     | 8    | hash of the map, see `MapAssets::map_hash`           |
+//! | 24     | 1    | GPS window                                           |
+//! | 25     | 1    | blocks of the tolerant index                         |
+//! | 26     | 6    | reserved                                             |
+//!
+//! followed by the section table, one entry of 12 bytes per section: a [`Tag`], the offset from
+//! the start of the file and the length. Sections start at multiples of 4.
+
+use core::marker::PhantomData;
+
+use async_kartoffel_generic::{Global, Vec2};
+
+use crate::{
+    GlobalPos,
+    assets::{Gps, GpsTables},
+    beacon::BeaconInfo,
+    const_graph::Graph,
+    gps::{SectionShape, TolerantIndex},
+    map::TrueMap,
+};
+
+pub const MAGIC: [u8; 4] = *b"KGPS";
+pub const FORMAT_VERSION: u16 = 1;
+pub const HEADER_LEN: usize = 32;
+pub const SECTION_ENTRY_LEN: usize = 12;
+
+pub type Tag = [u8; 4];
+
+/// `width: u16, height: u16`, then the walkable tiles as bits, row by row
+pub const TAG_MAP: Tag = *b"MAP_";
+/// [`BeaconInfo`] as five `u32`
+pub const TAG_BEACON_INFO: Tag = *b"BINF";
+/// beacon positions as `east: i16, south: i16`
+pub const TAG_BEACONS: Tag = *b"BPOS";
+/// beacon graph nodes as `start: u16, mid: u16, end: u16`, see [`ConstSparseGraphNode`]
+///
+/// [`ConstSparseGraphNode`]: crate::const_graph::ConstSparseGraphNode
+pub const TAG_GRAPH_NODES: Tag = *b"GNOD";
+/// beacon graph edges as `u16`
+pub const TAG_GRAPH_DATA: Tag = *b"GDAT";
+/// unique chunks as `chunk, east: u8, south: u8`, sorted by chunk
+pub const TAG_UNIQUE: Tag = *b"GUNI";
+/// ambiguous chunks as `chunk, index: u16, len: u8` into [`TAG_POSITIONS`], sorted by chunk
+pub const TAG_AMBIGUOUS: Tag = *b"GAMB";
+/// positions of ambiguous chunks as `east: u8, south: u8`
+pub const TAG_POSITIONS: Tag = *b"GPOS";
+/// chunks that are unique under rotation, sorted
+pub const TAG_ROTATION_UNIQUE: Tag = *b"GROT";
+/// [`TolerantIndex::sorted`] as `u16`
+pub const TAG_TOLERANT: Tag = *b"TIDX";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ParseError {
+    TooShort,
+    Magic,
+    Version(u16),
+    Checksum,
+    /// a section is missing or has an invalid length
+    Section(Tag),
+    /// the data does not start at a multiple of 4, use [`include_assets!`](crate::include_assets)
+    Misaligned,
+    /// the GPS was generated for a different window or section shape
+    Shape,
+}
+
+/// FNV-1a (32 bit) of `bytes`
+pub const fn checksum(bytes: &[u8]) -> u32 {
+    let mut hash = 0x811c9dc5u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(0x01000193);
+        i += 1;
+    }
+    hash
+}
+
+/// Includes a file as bytes aligned to 4, as required by [`Assets::parse`].
+///
+/// ```ignore
+/// static ASSETS: &[u8] = kartoffel_gps::include_assets!(concat!(env!("OUT_DIR"), "/map.bin"));
+/// ```
+#[macro_export]
+macro_rules! include_assets {
+    ($path:expr) => {{
+        #[repr(C, align(4))]
+        struct Aligned<B: ?Sized>(B);
+        static ALIGNED: &Aligned<[u8]> = &Aligned(*include_bytes!($path));
+        &ALIGNED.0
+    }};
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..][..4].try_into().unwrap())
+}
+
+/// view little endian `u16`s in place
+fn u16_slice(bytes: &[u8]) -> Option<&[u16]> {
+    if cfg!(target_endian = "big") {
+        return None;
+    }
+    // SAFETY: every bit pattern is a valid u16
+    let (prefix, values, suffix) = unsafe { bytes.align_to::<u16>() };
+    (prefix.is_empty() && suffix.is_empty()).then_some(values)
+}
+
+/// view positions stored as little endian `east: i16, south: i16` in place
+fn position_slice(bytes: &[u8]) -> Option<&[GlobalPos]> {
+    if cfg!(target_endian = "big") {
+        return None;
+    }
+    // SAFETY: `Position` is `repr(C)` with two i16 and a zero sized marker, every bit pattern is
+    // valid
+    let (prefix, values, suffix) = unsafe { bytes.align_to::<GlobalPos>() };
+    (prefix.is_empty() && suffix.is_empty()).then_some(values)
+}
+
+/// Parsed binary assets, all parts refer to the original bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct Assets<'a> {
+    map_hash: u64,
+    gps_window: u8,
+    map: MapRef<'a>,
+    beacon_info: &'a [u8],
+    beacons: &'a [GlobalPos],
+    graph: GraphRef<'a>,
+    unique: &'a [u8],
+    ambiguous: &'a [u8],
+    positions: &'a [[u8; 2]],
+    rotation_unique: &'a [u8],
+    tolerant_index: TolerantIndex<'a>,
+}
+
+impl<'a> Assets<'a> {
+    /// Check the header and checksum and find all sections. The bytes have to be aligned to 4.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::TooShort);
+        }
+        if bytes[..4] != MAGIC {
+            return Err(ParseError::Magic);
+        }
+        let version = u16_at(bytes, 4);
+        if version != FORMAT_VERSION {
+            return Err(ParseError::Version(version));
+        }
+        if !(bytes.as_ptr() as usize).is_multiple_of(4) {
+            return Err(ParseError::Misaligned);
+        }
+        let n_sections = usize::from(u16_at(bytes, 6));
+        let len = u32_at(bytes, 12) as usize;
+        if bytes.len() < len || len < HEADER_LEN + n_sections * SECTION_ENTRY_LEN {
+            return Err(ParseError::TooShort);
+        }
+        let bytes = &bytes[..len];
+        if checksum(&bytes[12..]) != u32_at(bytes, 8) {
+            return Err(ParseError::Checksum);
+        }
+
+        let section = |tag: Tag| -> Result<&'a [u8], ParseError> {
+            (0..n_sections)
+                .map(|i| &bytes[HEADER_LEN + i * SECTION_ENTRY_LEN..][..SECTION_ENTRY_LEN])
+                .find(|entry| entry[..4] == tag)
+                .and_then(|entry| {
+                    let offset = u32_at(entry, 4) as usize;
+                    bytes.get(offset..offset.checked_add(u32_at(entry, 8) as usize)?)
+                })
+                .ok_or(ParseError::Section(tag))
+        };
+        let u16s = |tag: Tag| u16_slice(section(tag)?).ok_or(ParseError::Section(tag));
+
+        let map = section(TAG_MAP)?;
+        if map.len() < 4 {
+            return Err(ParseError::Section(TAG_MAP));
+        }
+        let (width, height) = (u16_at(map, 0), u16_at(map, 2));
+        let map = MapRef {
+            width,
+            height,
+            bits: &map[4..],
+        };
+        if map.bits.len() < (usize::from(width) * usize::from(height)).div_ceil(8) {
+            return Err(ParseError::Section(TAG_MAP));
+        }
+
+        let beacon_info = section(TAG_BEACON_INFO)?;
+        if beacon_info.len() != 20 {
+            return Err(ParseError::Section(TAG_BEACON_INFO));
+        }
+        let beacons =
+            position_slice(section(TAG_BEACONS)?).ok_or(ParseError::Section(TAG_BEACONS))?;
+        let nodes = u16s(TAG_GRAPH_NODES)?;
+        let data = u16s(TAG_GRAPH_DATA)?;
+        // node indices are u16
+        let size =
+            u16::try_from(nodes.len() / 3).map_err(|_| ParseError::Section(TAG_GRAPH_NODES))?;
+        if !nodes.len().is_multiple_of(3)
+            || nodes.len() / 3 != beacons.len()
+            || nodes.chunks(3).any(|node| {
+                node[0] > node[1] || node[1] > node[2] || usize::from(node[2]) > data.len()
+            })
+        {
+            return Err(ParseError::Section(TAG_GRAPH_NODES));
+        }
+
+        let (positions, rest) = section(TAG_POSITIONS)?.as_chunks::<2>();
+        if !rest.is_empty() {
+            return Err(ParseError::Section(TAG_POSITIONS));
+        }
+        let n_blocks = usize::from(bytes[25]);
+        let sorted = u16s(TAG_TOLERANT)?;
+        if n_blocks == 0 || !sorted.len().is_multiple_of(n_blocks) {
+            return Err(ParseError::Section(TAG_TOLERANT));
+        }
+
+        Ok(Self {
+            map_hash: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            gps_window: bytes[24],
+            map,
+            beacon_info,
+            beacons,
+            graph: GraphRef { nodes, data, size },
+            unique: section(TAG_UNIQUE)?,
+            ambiguous: section(TAG_AMBIGUOUS)?,
+            positions,
+            rotation_unique: section(TAG_ROTATION_UNIQUE)?,
+            tolerant_index: TolerantIndex { n_blocks, sorted },
+        })
+    }
+
+    pub fn map_hash(&self) -> u64 {
+        self.map_hash
+    }
+
+    /// side length of the square GPS window
+    pub fn gps_window(&self) -> usize {
+        self.gps_window.into()
+    }
+
+    pub fn true_map(&self) -> &MapRef<'a> {
+        &self.map
+    }
+
+    pub fn beacons(&self) -> &'a [GlobalPos] {
+        self.beacons
+    }
+
+    pub fn beacon_graph(&self) -> &GraphRef<'a> {
+        &self.graph
+    }
+
+    pub fn beacon_info(&self) -> BeaconInfo {
+        let value = |i: usize| u32_at(self.beacon_info, 4 * i);
+        BeaconInfo {
+            max_beacon_dist: value(0),
+            max_beacons_entry: value(1),
+            max_beacons_exit: value(2),
+            max_path_length: value(3),
+            n_beacons: value(4),
+        }
+    }
+
+    /// The GPS tables, the section shape `S` has to match the one used by the builder (a square of
+    /// the size of [`Assets::gps_window`]).
+    pub fn tables<S: SectionShape>(&self) -> Result<BinaryTables<'a, S>, ParseError> {
+        if S::width() != self.gps_window() || S::height() != self.gps_window() {
+            return Err(ParseError::Shape);
+        }
+        let chunk_len = size_of::<S::Compressed>();
+        let valid = |records: &[u8], extra| records.len().is_multiple_of(chunk_len + extra);
+        if !valid(self.unique, 2) || !valid(self.ambiguous, 3) || !valid(self.rotation_unique, 0) {
+            return Err(ParseError::Shape);
+        }
+        Ok(BinaryTables {
+            unique: self.unique,
+            ambiguous: self.ambiguous,
+            positions: self.positions,
+            rotation_unique: self.rotation_unique,
+            _shape: PhantomData,
+        })
+    }
+}
+
+impl Assets<'static> {
+    pub fn gps<S: SectionShape>(
+        &self,
+    ) -> Result<Gps<'_, MapRef<'static>, BinaryTables<'static, S>>, ParseError> {
+        Ok(Gps {
+            map: &self.map,
+            tables: self.tables()?,
+            tolerant_index: self.tolerant_index,
+        })
+    }
+}
+
+/// [`TrueMap`] viewing the bits of the binary assets.
+#[derive(Debug, Clone, Copy)]
+pub struct MapRef<'a> {
+    width: u16,
+    height: u16,
+    bits: &'a [u8],
+}
+
+impl TrueMap for MapRef<'static> {
+    fn get(&self, pos: GlobalPos) -> bool {
+        let vec = pos.subtract_anchor();
+        match (u16::try_from(vec.east()), u16::try_from(vec.south())) {
+            (Ok(east), Ok(south)) if east < self.width && south < self.height => {
+                let index = usize::from(east) + usize::from(south) * usize::from(self.width);
+                self.bits[index / 8] & (1 << (index % 8)) != 0
+            }
+            _ => false,
+        }
+    }
+    fn vec_east(&self) -> Vec2<Global> {
+        Vec2::new_east(self.width as i16)
+    }
+    fn vec_south(&self) -> Vec2<Global> {
+        Vec2::new_south(self.height as i16)
+    }
+    fn width(&self) -> u16 {
+        self.width
+    }
+    fn height(&self) -> u16 {
+        self.height
+    }
+}
+
+/// [`Graph`] viewing the beacon graph of the binary assets, like
+/// [`ConstSparseGraph`](crate::const_graph::ConstSparseGraph).
+#[derive(Debug, Clone, Copy)]
+pub struct GraphRef<'a> {
+    /// `start, mid, end` of every node
+    nodes: &'a [u16],
+    data: &'a [u16],
+    /// number of nodes, checked to fit when parsing
+    size: u16,
+}
+
+impl GraphRef<'_> {
+    fn node(&self, index: u16) -> &[u16] {
+        &self.nodes[3 * usize::from(index)..][..3]
+    }
+}
+
+impl Graph for GraphRef<'static> {
+    fn after(&self, index: u16) -> &[u16] {
+        let node = self.node(index);
+        &self.data[usize::from(node[1])..usize::from(node[2])]
+    }
+    fn before(&self, index: u16) -> &[u16] {
+        let node = self.node(index);
+        &self.data[usize::from(node[0])..usize::from(node[1])]
+    }
+    fn size(&self) -> u16 {
+        self.size
+    }
+}
+
+/// [`GpsTables`] viewing sorted records of the binary assets, looked up by binary search.
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryTables<'a, S: SectionShape> {
+    unique: &'a [u8],
+    ambiguous: &'a [u8],
+    positions: &'a [[u8; 2]],
+    rotation_unique: &'a [u8],
+    _shape: PhantomData<S>,
+}
+
+/// the record starting with `key`, in records of `key.len() + extra` bytes sorted by key
+fn find<'r>(records: &'r [u8], key: &[u8], extra: usize) -> Option<&'r [u8]> {
+    let record_len = key.len() + extra;
+    let record = |i: usize| &records[i * record_len..][..record_len];
+    let (mut low, mut high) = (0, records.len() / record_len);
+    while low < high {
+        let mid = (low + high) / 2;
+        match record(mid)[..key.len()].cmp(key) {
+            core::cmp::Ordering::Less => low = mid + 1,
+            core::cmp::Ordering::Greater => high = mid,
+            core::cmp::Ordering::Equal => return Some(&record(mid)[key.len()..]),
+        }
+    }
+    None
+}
+
+impl<S: SectionShape> GpsTables for BinaryTables<'_, S> {
+    type Shape = S;
+
+    fn unique(&self, chunk: &[u8]) -> Option<&[u8; 2]> {
+        find(self.unique, chunk, 2).map(|pos| pos.try_into().unwrap())
+    }
+
+    fn ambiguous(&self, chunk: &[u8]) -> &[[u8; 2]] {
+        match find(self.ambiguous, chunk, 3) {
+            Some(&[index_low, index_high, len]) => {
+                let index = usize::from(u16::from_le_bytes([index_low, index_high]));
+                self.positions
+                    .get(index..index + usize::from(len))
+                    .unwrap_or(&[])
+            }
+            _ => &[],
+        }
+    }
+
+    fn rotation_unique(&self, chunk: &[u8]) -> bool {
+        find(self.rotation_unique, chunk, 0).is_some()
+    }
+
+    fn n_unique(&self) -> usize {
+        self.unique.len() / (size_of::<S::Compressed>() + 2)
+    }
+}
@@ -0,0 +1,142 @@
+use async_kartoffel_generic::{Local, RadarScanTrait, RadarSize, Tile, Vec2};
+
+/// tile of a radar scan, None if it is out of range
+pub type TileAt<'a> = dyn Fn(Vec2<Local>) -> Option<Tile> + 'a;
+
+/// A map embedded by a bot crate, e.g. by the build script of `cross`.
+pub struct EmbeddedMap {
+    pub name: &'static str,
+    /// whether the scanned terrain occurs on the map, e.g. [`crate::assets::Gps::fits`] of the map
+    /// module
+    pub fits: fn(&TileAt<'_>) -> Option<bool>,
+}
+
+/// Finds out which of the embedded maps the bot is on, by excluding the maps on which the scanned
+/// terrain does not occur.
+#[derive(Clone, Copy)]
+pub struct MapDetector {
+    maps: &'static [EmbeddedMap],
+    /// bit `i` is set if `maps[i]` is still possible
+    possible: u32,
+}
+
+impl MapDetector {
+    pub const fn new(maps: &'static [EmbeddedMap]) -> Self {
+        assert!(maps.len() <= 32, "too many embedded maps");
+        Self {
+            maps,
+            possible: match u32::MAX.checked_shr(32 - maps.len() as u32) {
+                Some(possible) => possible,
+                None => 0,
+            },
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.maps);
+    }
+
+    /// indices into the maps of the ones that fit all scans so far
+    pub fn possible(&self) -> impl Iterator<Item = usize> + use<> {
+        let possible = self.possible;
+        (0..self.maps.len()).filter(move |i| possible & (1 << i) != 0)
+    }
+
+    /// the map, once all others are excluded
+    pub fn map(&self) -> Option<&'static EmbeddedMap> {
+        let mut possible = self.possible();
+        match (possible.next(), possible.next()) {
+            (Some(i), None) => Some(&self.maps[i]),
+            _ => None,
+        }
+    }
+
+    /// Exclude the maps that don't fit `scan`. A scan that fits none of the maps is ignored, e.g.
+    /// it was taken while the bot was moved. Scans that are too small for the GPS window of a map
+    /// don't exclude it.
+    pub fn update<S: RadarSize>(
+        &mut self,
+        scan: &impl RadarScanTrait<S>,
+    ) -> Option<&'static EmbeddedMap> {
+        self.update_tiles(&|vec| scan.at(vec))
+    }
+
+    /// [`Self::update`] with the tiles of a scan
+    pub fn update_tiles(&mut self, tile_at: &TileAt<'_>) -> Option<&'static EmbeddedMap> {
+        let fitting = self
+            .possible()
+            .filter(|&i| (self.maps[i].fits)(tile_at) != Some(false))
+            .fold(0, |fitting, i| fitting | 1 << i);
+        if fitting != 0 {
+            self.possible = fitting;
+        }
+        self.map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the terrain in front of the bot, None if it wasn't scanned
+    fn front(tile_at: &TileAt<'_>) -> Option<bool> {
+        tile_at(Vec2::new_front(1)).map(|tile| tile.is_walkable_terrain())
+    }
+
+    /// a map without walls, one with walls only, and one with both
+    static MAPS: [EmbeddedMap; 3] = [
+        EmbeddedMap {
+            name: "open",
+            fits: |tile_at| front(tile_at),
+        },
+        EmbeddedMap {
+            name: "closed",
+            fits: |tile_at| front(tile_at).map(|walkable| !walkable),
+        },
+        EmbeddedMap {
+            name: "mixed",
+            fits: |tile_at| {
+                let left = tile_at(Vec2::new_left(1))?.is_walkable_terrain();
+                Some(front(tile_at)? || left)
+            },
+        },
+    ];
+
+    fn scan(front: Tile, left: Tile) -> impl Fn(Vec2<Local>) -> Option<Tile> {
+        move |vec| match vec {
+            _ if vec == Vec2::new_front(1) => Some(front),
+            _ if vec == Vec2::new_left(1) => Some(left),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn narrow_down() {
+        let mut detector = MapDetector::new(&MAPS);
+        assert_eq!(detector.possible().count(), 3);
+
+        // a scan without the front tile excludes nothing
+        assert!(detector.update_tiles(&|_| None).is_none());
+        assert_eq!(detector.possible().count(), 3);
+
+        // a wall in front, with free space to the left
+        assert!(
+            detector
+                .update_tiles(&scan(Tile::WallCave, Tile::Empty))
+                .is_none()
+        );
+        assert!(detector.possible().eq([1, 2]));
+
+        // free in front, only the closed map is excluded, the open one already was
+        let map = detector.update_tiles(&scan(Tile::Empty, Tile::Empty));
+        assert_eq!(map.map(|map| map.name), Some("mixed"));
+
+        // a scan that fits none of the remaining maps is ignored
+        let map = detector.update_tiles(&scan(Tile::WallCave, Tile::WallCave));
+        assert_eq!(map.map(|map| map.name), Some("mixed"));
+
+        detector.reset();
+        assert_eq!(detector.possible().count(), 3);
+        assert!(MapDetector::new(&[]).possible().eq([]));
+    }
+}
@@ -1,6 +1,12 @@
-use core::{fmt::Display, hash::Hash};
+use core::{fmt::Display, hash::Hash, marker::PhantomData, mem::size_of};
 
-use async_kartoffel_generic::{D3, D5, D7, D9, Direction, Global, RadarScanTrait, RadarSize, Vec2};
+use alloc::{format, string::String};
+use async_kartoffel_generic::{
+    D3, D5, D7, D9, Direction, Global, RadarScanTrait, RadarSize, Rotation, Tile, Vec2,
+};
+use heapless::Vec;
+
+use crate::{GlobalPos, map::TrueMap};
 
 #[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct OutOfBounds;
@@ -8,28 +14,33 @@ pub struct OutOfBounds;
 pub trait MapSectionTrait: Default + Hash + PartialEq + Eq + PartialOrd + Ord + Clone {
     type Size: RadarSize;
     type Compressed: phf_shared::FmtConst + phf_shared::PhfHash + Eq + Hash;
-    fn compressed_type() -> &'static str;
+    fn compressed_type() -> String;
+    /// All tiles of the section, relative to the center. The default is the full radar scan.
+    fn tiles() -> impl Iterator<Item = Vec2<Global>> {
+        Self::Size::range().flat_map(|i_east| {
+            Self::Size::range()
+                .map(move |i_south| Vec2::new_east_south(i_east.into(), i_south.into()))
+        })
+    }
     fn from_scan(scan: &impl RadarScanTrait<Self::Size>, facing: Direction) -> Self {
         let mut ret = Self::default();
-        for i_east in Self::Size::range() {
-            for i_south in Self::Size::range() {
-                let vec = Vec2::new_east_south(i_east.into(), i_south.into());
-                // unwrap: we know vec is in bounds
-                let walkable = scan.at(vec.local(facing)).unwrap().is_walkable_terrain();
-                // unwrap: we know vec is in bounds
-                ret.set(vec, walkable).unwrap();
-            }
+        for vec in Self::tiles() {
+            // unwrap: we know vec is in bounds
+            let walkable = scan.at(vec.local(facing)).unwrap().is_walkable_terrain();
+            // unwrap: we know vec is in bounds
+            ret.set(vec, walkable).unwrap();
         }
         ret
     }
+    /// One chunk for every possible facing, for matching a scan while the facing is unknown.
+    fn from_scan_any_facing(scan: &impl RadarScanTrait<Self::Size>) -> [(Self, Direction); 4] {
+        Direction::all().map(|facing| (Self::from_scan(scan, facing), facing))
+    }
     fn from_function(f: impl Fn(Vec2<Global>) -> bool) -> Self {
         let mut ret = Self::default();
-        for i_east in Self::Size::range() {
-            for i_south in Self::Size::range() {
-                let vec = Vec2::new_east(i_east.into()) + Vec2::new_south(i_south.into());
-                // unwrap: we know vec is in bounds
-                ret.set(vec, f(vec)).unwrap();
-            }
+        for vec in Self::tiles() {
+            // unwrap: we know vec is in bounds
+            ret.set(vec, f(vec)).unwrap();
         }
         ret
     }
@@ -40,152 +51,206 @@ pub trait MapSectionTrait: Default + Hash + PartialEq + Eq + PartialOrd + Ord +
     fn from_compressed(compressed: Self::Compressed) -> Self;
     fn get(&self, vec: Vec2<Global>) -> Result<bool, OutOfBounds>;
     fn set(&mut self, vec: Vec2<Global>, val: bool) -> Result<(), OutOfBounds>;
-    fn at_center(&self) -> bool {
-        self.get(Vec2::default()).unwrap()
+    /// The same terrain, rotated around the center. For sections that are not square and centered,
+    /// tiles rotated out of the section are lost and tiles rotated into it are not walkable.
+    fn rotate(&self, rotation: Rotation) -> Self {
+        Self::from_function(|vec| self.get(vec.rotate(-rotation)).unwrap_or(false))
     }
-}
-
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone)]
-pub struct MapSection<const N: usize>([[bool; N]; N]);
-
-impl<const N: usize> Display for MapSection<N> {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        for line in self.0 {
-            for val in line {
-                write!(f, "{}", if val { "." } else { "#" })?;
+    /// number of tiles except the center, see [`MapSectionTrait::bits`]
+    fn n_bits() -> u32 {
+        Self::tiles().count() as u32 - 1
+    }
+    /// Tiles except the center as bits, in the same order as [`MapSectionTrait::compress`]. Can be
+    /// used for sections with up to 129 tiles.
+    fn bits(&self) -> u128 {
+        let mut ret = 0u128;
+        for (index, vec) in Self::tiles().filter(|&vec| vec != Vec2::zero()).enumerate() {
+            // unwrap: we know vec is in bounds
+            if self.get(vec).unwrap() {
+                ret |= 1 << index;
             }
-            writeln!(f)?;
         }
-        Ok(())
+        ret
     }
-}
-
-impl<const N: usize> Default for MapSection<N> {
-    fn default() -> Self {
-        MapSection([[false; N]; N])
+    fn at_center(&self) -> bool {
+        self.get(Vec2::default()).unwrap()
     }
 }
 
-impl MapSectionTrait for MapSection<3> {
-    type Size = D3;
-    type Compressed = [u8; 1];
+/// Number of bytes needed to store a compressed section with `width * height` tiles. The center is
+/// always walkable and not stored.
+pub const fn compressed_len(width: usize, height: usize) -> usize {
+    (width * height - 1).div_ceil(8)
+}
 
-    fn compressed_type() -> &'static str {
-        "[u8; 1]"
+/// Shape of a [`Section`]: the tiles `WEST` to `EAST` and `NORTH` to `SOUTH` of the center (so
+/// `WEST` and `NORTH` are usually negative), cut out of a radar scan of size `Size`. Implement it
+/// with [`section_shape!`](crate::section_shape), which also computes `Compressed`.
+pub trait SectionShape:
+    Clone + Copy + Default + core::fmt::Debug + PartialEq + Eq + PartialOrd + Ord + Hash + 'static
+{
+    type Size: RadarSize;
+    const WEST: i8;
+    const EAST: i8;
+    const NORTH: i8;
+    const SOUTH: i8;
+    /// `[u8; N]` with `N` from [`compressed_len`]
+    type Compressed: phf_shared::FmtConst
+        + phf_shared::PhfHash
+        + Eq
+        + Hash
+        + Default
+        + AsRef<[u8]>
+        + AsMut<[u8]>;
+
+    /// evaluated for every used shape, fails to compile if the shape is invalid
+    #[doc(hidden)]
+    const CHECK: () = {
+        let r = Self::Size::R as i8;
+        assert!(-r <= Self::WEST && Self::WEST <= 0 && 0 <= Self::EAST && Self::EAST <= r);
+        assert!(-r <= Self::NORTH && Self::NORTH <= 0 && 0 <= Self::SOUTH && Self::SOUTH <= r);
+        assert!(
+            (Self::EAST - Self::WEST + 1) as usize * (Self::SOUTH - Self::NORTH + 1) as usize
+                <= 129,
+            "sections should have at most 129 tiles"
+        );
+    };
+
+    fn width() -> usize {
+        (Self::EAST - Self::WEST + 1) as usize
     }
-
-    fn compress(&self) -> Option<Self::Compressed> {
-        // unwrap: we known center is in range
-        if self.get(Vec2::default()).unwrap() {
-            let mut ret = Self::Compressed::default();
-            let mut index = 0usize;
-            for i_east in Self::Size::range() {
-                for i_south in Self::Size::range() {
-                    if i_east != 0 || i_south != 0 {
-                        if self
-                            .get(Vec2::new_east_south(i_east.into(), i_south.into()))
-                            .unwrap()
-                        {
-                            ret[index.div_euclid(8)] ^= 1u8 << index.rem_euclid(8);
-                        }
-                        index += 1;
-                    }
-                }
-            }
-            Some(ret)
-        } else {
-            None
-        }
+    fn height() -> usize {
+        (Self::SOUTH - Self::NORTH + 1) as usize
     }
+}
 
-    fn from_compressed(compressed: Self::Compressed) -> Self {
-        let mut ret = Self::default();
-        let mut index: usize = 0;
-        for i_east in Self::Size::range() {
-            for i_south in Self::Size::range() {
-                if i_east != 0 || i_south != 0 {
-                    let val = compressed[index.div_euclid(8)] & (1u8 << index.rem_euclid(8)) > 0u8;
-                    // unwrap: always in bounds
-                    ret.set(Vec2::new_east_south(i_east.into(), i_south.into()), val)
-                        .unwrap();
-
-                    index += 1;
-                }
-            }
+/// Defines a [`SectionShape`] with a computed compressed size. Using a shape that does not fit
+/// into the radar scan or does not contain the center fails to compile.
+///
+/// ```
+/// use async_kartoffel_generic::D9;
+/// use kartoffel_gps::gps::{MapSectionTrait, Section};
+///
+/// // 5x5 window out of a D9 scan
+/// kartoffel_gps::section_shape!(pub Window5: D9, west -2, east 2, north -2, south 2);
+/// // more tiles to the north
+/// kartoffel_gps::section_shape!(pub North7x5: D9, west -3, east 3, north -4, south 0);
+///
+/// assert_eq!(Section::<Window5>::n_bits(), 24);
+/// assert_eq!(Section::<North7x5>::compressed_type(), "[u8; 5]");
+/// ```
+#[macro_export]
+macro_rules! section_shape {
+    ($(#[$meta:meta])* $vis:vis $name:ident: $size:ty, west $west:literal, east $east:literal, north $north:literal, south $south:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis struct $name;
+        impl $crate::gps::SectionShape for $name {
+            type Size = $size;
+            const WEST: i8 = $west;
+            const EAST: i8 = $east;
+            const NORTH: i8 = $north;
+            const SOUTH: i8 = $south;
+            type Compressed = [u8; $crate::gps::compressed_len(
+                ($east - $west + 1) as usize,
+                ($south - $north + 1) as usize,
+            )];
         }
-        // unwrap: center is always in bounds
-        ret.set(Vec2::default(), true).unwrap();
-        ret
-    }
+    };
+}
 
-    fn get(&self, vec: Vec2<Global>) -> Result<bool, OutOfBounds> {
-        if Self::Size::contains(vec) {
-            Ok(
-                self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                    [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()],
-            )
-        } else {
-            Err(OutOfBounds)
+/// Square section of size `N`, centered on the bot. This is the full radar scan.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square<const N: usize>;
+
+macro_rules! impl_square {
+    ($n:literal, $size:ty) => {
+        impl SectionShape for Square<$n> {
+            type Size = $size;
+            const WEST: i8 = -($n / 2);
+            const EAST: i8 = $n / 2;
+            const NORTH: i8 = -($n / 2);
+            const SOUTH: i8 = $n / 2;
+            type Compressed = [u8; compressed_len($n, $n)];
         }
+    };
+}
+impl_square!(3, D3);
+impl_square!(5, D5);
+impl_square!(7, D7);
+impl_square!(9, D9);
+
+/// Walkable tiles of a section of the map, in the shape `S`, stored as bits.
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+pub struct Section<S: SectionShape> {
+    bits: u128,
+    _phantom: PhantomData<S>,
+}
+
+/// square section of size `N`, centered on the bot
+pub type MapSection<const N: usize> = Section<Square<N>>;
+
+impl<S: SectionShape> Section<S> {
+    fn index(vec: Vec2<Global>) -> Option<usize> {
+        #[allow(clippy::let_unit_value)]
+        let () = S::CHECK;
+        let east = vec.east().checked_sub(S::WEST.into())?;
+        let south = vec.south().checked_sub(S::NORTH.into())?;
+        let (east, south) = (usize::try_from(east).ok()?, usize::try_from(south).ok()?);
+        (east < S::width() && south < S::height()).then_some(east * S::height() + south)
     }
+}
 
-    fn set(&mut self, vec: Vec2<Global>, val: bool) -> Result<(), OutOfBounds> {
-        if Self::Size::contains(vec) {
-            self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()] = val;
-            Ok(())
-        } else {
-            Err(OutOfBounds)
+impl<S: SectionShape> Display for Section<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for i_south in S::NORTH..=S::SOUTH {
+            for i_east in S::WEST..=S::EAST {
+                // unwrap: we know vec is in bounds
+                let val = self
+                    .get(Vec2::new_east_south(i_east.into(), i_south.into()))
+                    .unwrap();
+                write!(f, "{}", if val { "." } else { "#" })?;
+            }
+            writeln!(f)?;
         }
+        Ok(())
     }
 }
 
-impl MapSectionTrait for MapSection<5> {
-    type Size = D5;
-    type Compressed = [u8; 3];
+impl<S: SectionShape> MapSectionTrait for Section<S> {
+    type Size = S::Size;
+    type Compressed = S::Compressed;
+
+    fn compressed_type() -> String {
+        format!("[u8; {}]", size_of::<S::Compressed>())
+    }
 
-    fn compressed_type() -> &'static str {
-        "[u8; 3]"
+    fn tiles() -> impl Iterator<Item = Vec2<Global>> {
+        (S::WEST..=S::EAST).flat_map(|i_east| {
+            (S::NORTH..=S::SOUTH)
+                .map(move |i_south| Vec2::new_east_south(i_east.into(), i_south.into()))
+        })
     }
 
     fn compress(&self) -> Option<Self::Compressed> {
-        // unwrap: we known center is in range
-        if self.get(Vec2::default()).unwrap() {
-            let mut ret = Self::Compressed::default();
-            let mut index = 0usize;
-            for i_east in Self::Size::range() {
-                for i_south in Self::Size::range() {
-                    if i_east != 0 || i_south != 0 {
-                        if self
-                            .get(Vec2::new_east_south(i_east.into(), i_south.into()))
-                            .unwrap()
-                        {
-                            ret[index.div_euclid(8)] ^= 1u8 << index.rem_euclid(8);
-                        }
-                        index += 1;
-                    }
-                }
-            }
-            Some(ret)
-        } else {
-            None
+        if !self.at_center() {
+            return None;
+        }
+        let mut ret = Self::Compressed::default();
+        let bits = self.bits();
+        for (index, byte) in ret.as_mut().iter_mut().enumerate() {
+            *byte = (bits >> (index * 8)) as u8;
         }
+        Some(ret)
     }
 
     fn from_compressed(compressed: Self::Compressed) -> Self {
         let mut ret = Self::default();
-        let mut index: usize = 0;
-        for i_east in Self::Size::range() {
-            for i_south in Self::Size::range() {
-                if i_east != 0 || i_south != 0 {
-                    let val = compressed[index.div_euclid(8)] & (1u8 << index.rem_euclid(8)) > 0u8;
-                    // unwrap: always in bounds
-                    ret.set(Vec2::new_east_south(i_east.into(), i_south.into()), val)
-                        .unwrap();
-
-                    index += 1;
-                }
-            }
+        let bytes = compressed.as_ref();
+        for (index, vec) in Self::tiles().filter(|&vec| vec != Vec2::zero()).enumerate() {
+            let val = bytes[index.div_euclid(8)] & (1u8 << index.rem_euclid(8)) > 0u8;
+            // unwrap: always in bounds
+            ret.set(vec, val).unwrap();
         }
         // unwrap: center is always in bounds
         ret.set(Vec2::default(), true).unwrap();
@@ -193,171 +258,247 @@ impl MapSectionTrait for MapSection<5> {
     }
 
     fn get(&self, vec: Vec2<Global>) -> Result<bool, OutOfBounds> {
-        if Self::Size::contains(vec) {
-            Ok(
-                self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                    [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()],
-            )
-        } else {
-            Err(OutOfBounds)
-        }
+        let index = Self::index(vec).ok_or(OutOfBounds)?;
+        Ok(self.bits >> index & 1 == 1)
     }
 
     fn set(&mut self, vec: Vec2<Global>, val: bool) -> Result<(), OutOfBounds> {
-        if Self::Size::contains(vec) {
-            self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()] = val;
-            Ok(())
+        let index = Self::index(vec).ok_or(OutOfBounds)?;
+        if val {
+            self.bits |= 1 << index;
         } else {
-            Err(OutOfBounds)
+            self.bits &= !(1 << index);
         }
+        Ok(())
     }
 }
 
-impl MapSectionTrait for MapSection<7> {
-    type Size = D7;
-    type Compressed = [u8; 6];
-
-    fn compressed_type() -> &'static str {
-        "[u8; 6]"
-    }
-
-    fn compress(&self) -> Option<Self::Compressed> {
-        // unwrap: we known center is in range
-        if self.get(Vec2::default()).unwrap() {
-            let mut ret = Self::Compressed::default();
-            let mut index = 0usize;
-            for i_east in Self::Size::range() {
-                for i_south in Self::Size::range() {
-                    if i_east != 0 || i_south != 0 {
-                        if self
-                            .get(Vec2::new_east_south(i_east.into(), i_south.into()))
-                            .unwrap()
-                        {
-                            ret[index.div_euclid(8)] ^= 1u8 << index.rem_euclid(8);
-                        }
-                        index += 1;
-                    }
-                }
-            }
-            Some(ret)
-        } else {
-            None
-        }
-    }
+/// A map section where some tiles are unknown, e.g. hidden by bots or outside of the arena.
+#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone, Default)]
+pub struct MaskedSection<T: MapSectionTrait> {
+    pub section: T,
+    /// true if the tile in `section` is known
+    pub known: T,
+}
 
-    fn from_compressed(compressed: Self::Compressed) -> Self {
+impl<T: MapSectionTrait> MaskedSection<T> {
+    /// Like [`MapSectionTrait::from_scan`], tiles for which `is_known` returns false are unknown.
+    pub fn from_scan(
+        scan: &impl RadarScanTrait<T::Size>,
+        facing: Direction,
+        is_known: impl Fn(Tile) -> bool,
+    ) -> Self {
         let mut ret = Self::default();
-        let mut index: usize = 0;
-        for i_east in Self::Size::range() {
-            for i_south in Self::Size::range() {
-                if i_east != 0 || i_south != 0 {
-                    let val = compressed[index.div_euclid(8)] & (1u8 << index.rem_euclid(8)) > 0u8;
-                    // unwrap: always in bounds
-                    ret.set(Vec2::new_east_south(i_east.into(), i_south.into()), val)
-                        .unwrap();
-
-                    index += 1;
-                }
-            }
+        for vec in T::tiles() {
+            // unwrap: we know vec is in bounds
+            let tile = scan.at(vec.local(facing)).unwrap();
+            // unwrap: we know vec is in bounds
+            ret.section.set(vec, tile.is_walkable_terrain()).unwrap();
+            ret.known.set(vec, is_known(tile)).unwrap();
         }
-        // unwrap: center is always in bounds
-        ret.set(Vec2::default(), true).unwrap();
         ret
     }
 
-    fn get(&self, vec: Vec2<Global>) -> Result<bool, OutOfBounds> {
-        if Self::Size::contains(vec) {
-            Ok(
-                self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                    [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()],
-            )
-        } else {
-            Err(OutOfBounds)
-        }
+    /// number of known tiles that differ from `other`
+    pub fn distance(&self, other: &T) -> u32 {
+        ((self.section.bits() ^ other.bits()) & self.known.bits()).count_ones()
     }
+}
 
-    fn set(&mut self, vec: Vec2<Global>, val: bool) -> Result<(), OutOfBounds> {
-        if Self::Size::contains(vec) {
-            self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()] = val;
-            Ok(())
-        } else {
-            Err(OutOfBounds)
-        }
-    }
+/// Maximum number of unknown tiles in a block of the [`TolerantIndex`], blocks with more unknown
+/// tiles are skipped because every combination has to be looked up.
+pub const MAX_UNKNOWN_PER_BLOCK: u32 = 4;
+
+/// Index for finding positions whose chunk differs from a scan in at most `k` known tiles, built
+/// with `kartoffel_gps_builder::const_tolerant_index::TolerantIndexBuilder`.
+///
+/// Uses multi-index hashing: the bits of a chunk (see [`MapSectionTrait::bits`]) are split into
+/// blocks, and for every block the walkable positions are sorted by the value of that block. If
+/// there are more blocks than `k`, one of the blocks of a match is equal to the scan (pigeonhole
+/// principle), so it is found by a binary search in that block. Only the positions are stored, the
+/// chunks are taken from the [`TrueMap`].
+#[derive(Debug, Clone, Copy)]
+pub struct TolerantIndex<'a> {
+    pub n_blocks: usize,
+    /// walkable positions as `east + south * width`, once for every block sorted by its value,
+    /// concatenated
+    pub sorted: &'a [u16],
 }
 
-impl MapSectionTrait for MapSection<9> {
-    type Size = D9;
-    type Compressed = [u8; 10];
+/// value of the `block`-th of `n_blocks` blocks of `bits`
+pub fn block_value<T: MapSectionTrait>(bits: u128, block: usize, n_blocks: usize) -> u32 {
+    let (start, end) = block_range::<T>(block, n_blocks);
+    ((bits >> start) & ((1 << (end - start)) - 1)) as u32
+}
+
+/// [`block_value`] of the chunk of `map` centered at `center`, only the tiles of the block are
+/// looked up
+fn block_value_at<T: MapSectionTrait>(
+    map: &impl TrueMap,
+    center: GlobalPos,
+    block: usize,
+    n_blocks: usize,
+) -> u32 {
+    let (start, end) = block_range::<T>(block, n_blocks);
+    T::tiles()
+        .filter(|&vec| vec != Vec2::zero())
+        .skip(start as usize)
+        .take((end - start) as usize)
+        .enumerate()
+        .filter(|&(_bit, vec)| map.get(center + vec))
+        .fold(0, |value, (bit, _vec)| value | 1 << bit)
+}
+
+fn block_range<T: MapSectionTrait>(block: usize, n_blocks: usize) -> (u32, u32) {
+    let n_bits = T::n_bits() as usize;
+    let range = (
+        (n_bits * block / n_blocks) as u32,
+        (n_bits * (block + 1) / n_blocks) as u32,
+    );
+    assert!(
+        range.1 - range.0 <= 32,
+        "blocks should have at most 32 bits"
+    );
+    range
+}
 
-    fn compressed_type() -> &'static str {
-        "[u8; 10]"
+impl<'a> TolerantIndex<'a> {
+    pub fn n_blocks(&self) -> usize {
+        self.n_blocks
     }
 
-    fn compress(&self) -> Option<Self::Compressed> {
-        // unwrap: we known center is in range
-        if self.get(Vec2::default()).unwrap() {
-            let mut ret = Self::Compressed::default();
-            let mut index = 0usize;
-            for i_east in Self::Size::range() {
-                for i_south in Self::Size::range() {
-                    if i_east != 0 || i_south != 0 {
-                        if self
-                            .get(Vec2::new_east_south(i_east.into(), i_south.into()))
-                            .unwrap()
-                        {
-                            ret[index.div_euclid(8)] ^= 1u8 << index.rem_euclid(8);
-                        }
-                        index += 1;
+    /// the walkable positions, sorted by the value of `block`
+    pub fn block(&self, block: usize) -> &'a [u16] {
+        let len = self.sorted.len() / self.n_blocks;
+        &self.sorted[block * len..][..len]
+    }
+
+    /// Positions whose chunk differs from `query` in at most `max_distance` known tiles, sorted by
+    /// distance. All of them are found if `max_distance` is less than the number of blocks and no
+    /// block has more than [`MAX_UNKNOWN_PER_BLOCK`] unknown tiles. Only the `N` best are returned.
+    pub fn matches<T: MapSectionTrait, const N: usize>(
+        &self,
+        query: &MaskedSection<T>,
+        max_distance: u32,
+        map: &impl TrueMap,
+    ) -> Vec<(GlobalPos, u32), N> {
+        let mut matches = Vec::new();
+        let bits = query.section.bits();
+        let known = query.known.bits();
+        let width = map.width();
+        let pos =
+            |index: u16| crate::pos::pos_east_south((index % width) as i16, (index / width) as i16);
+
+        for i_block in 0..self.n_blocks() {
+            let sorted = self.block(i_block);
+            let known_block = block_value::<T>(known, i_block, self.n_blocks());
+            let (start, end) = block_range::<T>(i_block, self.n_blocks());
+            // blocks are empty if there are more blocks than bits
+            let unknown_block =
+                !known_block & u32::MAX.checked_shr(32 - (end - start)).unwrap_or(0);
+            if unknown_block.count_ones() > MAX_UNKNOWN_PER_BLOCK {
+                continue;
+            }
+            let value = block_value::<T>(bits, i_block, self.n_blocks()) & known_block;
+
+            // every combination of the unknown bits
+            let mut fill = unknown_block;
+            loop {
+                let key = value | fill;
+                // the comparisons of the search only need the tiles of the block
+                let first = sorted.partition_point(|&index| {
+                    block_value_at::<T>(map, pos(index), i_block, self.n_blocks()) < key
+                });
+                for &index in &sorted[first..] {
+                    // the whole chunk is needed for the distance, build it only once
+                    let chunk = T::from_function(|vec| map.get(pos(index) + vec));
+                    if block_value::<T>(chunk.bits(), i_block, self.n_blocks()) != key {
+                        break;
+                    }
+                    let distance = query.distance(&chunk);
+                    if distance <= max_distance {
+                        insert_match(&mut matches, pos(index), distance);
                     }
                 }
+                if fill == 0 {
+                    break;
+                }
+                fill = (fill - 1) & unknown_block;
             }
-            Some(ret)
-        } else {
-            None
         }
+        matches
     }
+}
 
-    fn from_compressed(compressed: Self::Compressed) -> Self {
-        let mut ret = Self::default();
-        let mut index: usize = 0;
-        for i_east in Self::Size::range() {
-            for i_south in Self::Size::range() {
-                if i_east != 0 || i_south != 0 {
-                    let val = compressed[index.div_euclid(8)] & (1u8 << index.rem_euclid(8)) > 0u8;
-                    // unwrap: always in bounds
-                    ret.set(Vec2::new_east_south(i_east.into(), i_south.into()), val)
-                        .unwrap();
-
-                    index += 1;
-                }
-            }
-        }
-        // unwrap: center is always in bounds
-        ret.set(Vec2::default(), true).unwrap();
-        ret
+/// insert sorted by distance, without duplicates, dropping the worst match if full
+fn insert_match<const N: usize>(
+    matches: &mut Vec<(GlobalPos, u32), N>,
+    pos: GlobalPos,
+    distance: u32,
+) {
+    if matches.iter().any(|&(other, _)| other == pos) {
+        return;
+    }
+    let index = matches
+        .iter()
+        .position(|&(_, other)| other > distance)
+        .unwrap_or(matches.len());
+    if index == N {
+        return;
+    }
+    if matches.is_full() {
+        matches.pop();
     }
+    // unwrap: there is space and index is in range
+    matches.insert(index, (pos, distance)).unwrap();
+}
 
-    fn get(&self, vec: Vec2<Global>) -> Result<bool, OutOfBounds> {
-        if Self::Size::contains(vec) {
-            Ok(
-                self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                    [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()],
-            )
-        } else {
-            Err(OutOfBounds)
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::section_shape!(Front: D9, west -4, east 4, north -4, south 1);
+
+    #[test]
+    fn window() {
+        assert_eq!(Section::<Front>::n_bits(), 53);
+        assert_eq!(
+            size_of::<<Section<Front> as MapSectionTrait>::Compressed>(),
+            7
+        );
+        assert_eq!(MapSection::<9>::compressed_type(), "[u8; 10]");
+
+        let f = |vec: Vec2<Global>| (vec.east() * 3 + vec.south()).rem_euclid(4) != 1;
+        let section = Section::<Front>::from_function(f);
+        assert_eq!(section.get(Vec2::new_south(2)), Err(OutOfBounds));
+        assert_eq!(section.get(Vec2::new_north(4)), Ok(f(Vec2::new_north(4))));
+        let compressed = section.compress().unwrap();
+        assert_eq!(Section::<Front>::from_compressed(compressed), section);
+
+        // same encoding as for the full scan, the center is skipped
+        let full = MapSection::<3>::from_function(|vec| vec.east() >= 0);
+        assert_eq!(full.compress(), Some([0b1111_1000]));
     }
 
-    fn set(&mut self, vec: Vec2<Global>, val: bool) -> Result<(), OutOfBounds> {
-        if Self::Size::contains(vec) {
-            self.0[usize::try_from(vec.east() + i16::from(Self::Size::R)).unwrap()]
-                [usize::try_from(vec.south() + i16::from(Self::Size::R)).unwrap()] = val;
-            Ok(())
-        } else {
-            Err(OutOfBounds)
-        }
+    #[test]
+    fn rotate() {
+        let mut chunk = MapSection::<5>::default();
+        chunk.set(Vec2::default(), true).unwrap();
+        chunk.set(Vec2::new_east(2), true).unwrap();
+        chunk.set(Vec2::new_east_south(1, -1), true).unwrap();
+
+        let rotated = chunk.rotate(Rotation::Right);
+        assert!(rotated.get(Vec2::new_south(2)).unwrap());
+        assert!(rotated.get(Vec2::new_east_south(1, 1)).unwrap());
+        assert!(!rotated.get(Vec2::new_east(2)).unwrap());
+        assert_eq!(rotated.rotate(Rotation::Left), chunk);
+        assert_eq!(
+            chunk.rotate(Rotation::Right).rotate(Rotation::Right),
+            chunk.rotate(Rotation::Inverse)
+        );
+        assert_eq!(
+            MapSection::<5>::from_compressed(chunk.rotate(Rotation::Left).compress().unwrap()),
+            rotated.rotate(Rotation::Inverse)
+        );
     }
 }
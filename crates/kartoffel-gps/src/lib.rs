@@ -5,10 +5,18 @@ extern crate alloc;
 use async_kartoffel_generic::Position;
 use pos::GpsAnchor;
 
+pub mod assets;
 pub mod beacon;
+pub mod binary;
 pub mod const_graph;
+pub mod detect;
 pub mod gps;
+pub mod localize;
 pub mod map;
 pub mod pos;
 
 pub type GlobalPos = Position<GpsAnchor>;
+
+/// used by the generated map assets
+#[doc(hidden)]
+pub use phf;
@@ -0,0 +1,198 @@
+use async_kartoffel_generic::{Direction, Transform};
+use heapless::Vec;
+
+use crate::{GlobalPos, gps::MapSectionTrait, map::TrueMap};
+
+/// Global localisation from scans that are not unique on their own.
+///
+/// A non-unique chunk still narrows the position down to a few candidates (provided by the
+/// ambiguous chunk table of the builder). Each candidate is moved along with the bot and checked
+/// against every following scan, until only one remains. At most `N` candidates are tracked,
+/// chunks that occur more often are ignored until a more distinctive scan comes along.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Localizer<const N: usize> {
+    candidates: Vec<GlobalPos, N>,
+}
+
+impl<const N: usize> Default for Localizer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Localizer<N> {
+    pub const fn new() -> Self {
+        Self {
+            candidates: Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.candidates.clear();
+    }
+
+    /// positions that fit all scans and movements since the last reset
+    pub fn candidates(&self) -> &[GlobalPos] {
+        &self.candidates
+    }
+
+    /// the position, if only one candidate is left
+    pub fn position(&self) -> Option<GlobalPos> {
+        match self.candidates.as_slice() {
+            [pos] => Some(*pos),
+            _ => None,
+        }
+    }
+
+    /// The bot moved by `transform`, while it was facing `facing`. Candidates that would end up on
+    /// non-walkable terrain are discarded.
+    pub fn moved(&mut self, transform: Transform, facing: Direction, map: &impl TrueMap) {
+        for pos in self.candidates.iter_mut() {
+            *pos = transform.apply(*pos, facing).0;
+        }
+        self.candidates.retain(|&pos| map.get(pos));
+    }
+
+    /// Update the candidates with a scan, converted to `chunk` in global orientation. `lookup`
+    /// returns all positions with this chunk, it is only called if there are no candidates yet or
+    /// none of them fits the scan.
+    pub fn scan<T: MapSectionTrait, I: IntoIterator<Item = GlobalPos>>(
+        &mut self,
+        chunk: &T,
+        map: &impl TrueMap,
+        lookup: impl FnOnce(&T) -> I,
+    ) -> Option<GlobalPos> {
+        self.candidates
+            .retain(|&pos| T::from_function(|vec| map.get(pos + vec)) == *chunk);
+        if self.candidates.is_empty() {
+            for pos in lookup(chunk) {
+                if self.candidates.push(pos).is_err() {
+                    // too ambiguous
+                    self.candidates.clear();
+                    break;
+                }
+            }
+        }
+        self.position()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::{Global, Rotation, Vec2};
+
+    use super::*;
+    use crate::{gps::MapSection, pos::pos_east_south};
+
+    /// corridors with the same entry, but different lengths
+    const MAP: [&str; 7] = [
+        "###########",
+        "#.........#",
+        "#.#.#.#.#.#",
+        "#.#.#.#.#.#",
+        "#.#.#.###.#",
+        "#.#.###.#.#",
+        "###########",
+    ];
+
+    struct TestMap;
+
+    impl TrueMap for TestMap {
+        fn get(&self, pos: GlobalPos) -> bool {
+            let vec = pos.subtract_anchor();
+            usize::try_from(vec.south())
+                .ok()
+                .and_then(|south| MAP.get(south))
+                .zip(usize::try_from(vec.east()).ok())
+                .and_then(|(line, east)| line.as_bytes().get(east))
+                .is_some_and(|&c| c == b'.')
+        }
+        fn vec_east(&self) -> Vec2<Global> {
+            Vec2::new_east(MAP[0].len() as i16)
+        }
+        fn vec_south(&self) -> Vec2<Global> {
+            Vec2::new_south(MAP.len() as i16)
+        }
+        fn width(&self) -> u16 {
+            MAP[0].len() as u16
+        }
+        fn height(&self) -> u16 {
+            MAP.len() as u16
+        }
+    }
+
+    fn lookup(chunk: &MapSection<3>) -> impl Iterator<Item = GlobalPos> + use<> {
+        let chunk = *chunk;
+        (0..MAP.len() as i16)
+            .flat_map(|south| (0..MAP[0].len() as i16).map(move |east| pos_east_south(east, south)))
+            .filter(|&pos| TestMap.get(pos))
+            .filter(move |&pos| {
+                MapSection::<3>::from_function(|vec| TestMap.get(pos + vec)) == chunk
+            })
+    }
+
+    #[test]
+    fn prune_candidates() {
+        let map = &TestMap;
+        let chunk_at = |pos: GlobalPos| MapSection::<3>::from_function(|vec| map.get(pos + vec));
+        let mut localizer = Localizer::<8>::new();
+        // the bot enters the corridor at (3, 2), facing south
+        let mut pos = pos_east_south(3, 2);
+        let mut facing = Direction::South;
+
+        assert_eq!(localizer.scan(&chunk_at(pos), map, lookup), None);
+        assert_eq!(
+            localizer.candidates(),
+            &[
+                pos_east_south(3, 2),
+                pos_east_south(5, 2),
+                pos_east_south(7, 2)
+            ]
+        );
+
+        let step = Transform::from(Vec2::new_front(1));
+        localizer.moved(step, facing, map);
+        pos = step.apply(pos, facing).0;
+        assert_eq!(localizer.scan(&chunk_at(pos), map, lookup), None);
+        // the corridor at (7, _) is too short
+        assert_eq!(
+            localizer.candidates(),
+            &[pos_east_south(3, 3), pos_east_south(5, 3)]
+        );
+
+        // turning does not change the candidates
+        for rotation in [Rotation::Left, Rotation::Right] {
+            let turn = Transform::from(rotation);
+            localizer.moved(turn, facing, map);
+            (pos, facing) = turn.apply(pos, facing);
+            assert_eq!(localizer.candidates().len(), 2);
+        }
+        assert_eq!(facing, Direction::South);
+
+        localizer.moved(step, facing, map);
+        pos = step.apply(pos, facing).0;
+        assert_eq!(localizer.scan(&chunk_at(pos), map, lookup), Some(pos));
+
+        // too ambiguous
+        let mut localizer = Localizer::<2>::new();
+        assert_eq!(
+            localizer.scan(&chunk_at(pos_east_south(3, 2)), map, lookup),
+            None
+        );
+        assert!(localizer.candidates().is_empty());
+    }
+
+    #[test]
+    fn reseed_after_contradiction() {
+        let map = &TestMap;
+        let mut localizer = Localizer::<8>::new();
+        let corner = pos_east_south(1, 1);
+        let chunk = MapSection::<3>::from_function(|vec| map.get(corner + vec));
+        assert_eq!(localizer.scan(&chunk, map, lookup), Some(corner));
+
+        // the bot did not actually move, the next scan does not fit
+        localizer.moved(Vec2::new_front(1).into(), Direction::East, map);
+        assert_eq!(localizer.candidates(), &[pos_east_south(2, 1)]);
+        assert_eq!(localizer.scan(&chunk, map, lookup), Some(corner));
+    }
+}
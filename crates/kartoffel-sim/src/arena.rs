@@ -0,0 +1,90 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use anyhow::anyhow;
+use async_kartoffel_generic::{Tile, Vec2};
+use kartoffel_gps::GlobalPos;
+use kartoffel_gps_builder::map::Map;
+
+/// Terrain and initial content of an arena, in the format of `maps/map-grotta.txt`.
+///
+/// Besides terrain, the file may contain items (`*`, `=`), which are placed in the world, and bots
+/// (`@`), which are used as spawn points. Both are on walkable terrain.
+#[derive(Clone, Debug)]
+pub struct Arena {
+    pub map: Map,
+    pub items: Vec<(GlobalPos, Tile)>,
+    pub spawns: Vec<GlobalPos>,
+}
+
+impl Arena {
+    pub fn from_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let file =
+            File::open(&path).map_err(|err| anyhow!("{}: {}", path.as_ref().display(), err))?;
+        Self::from_reader(file)
+    }
+
+    pub fn from_reader(reader: impl Read) -> anyhow::Result<Self> {
+        let mut tiles = Vec::new();
+        let mut items = Vec::new();
+        let mut spawns = Vec::new();
+        let mut width = None;
+        let mut height = 0usize;
+        for (i_south, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line_width = line.chars().count();
+            match width {
+                Some(width) if width != line_width => {
+                    return Err(anyhow!(
+                        "line {}: length should be {} but was {}",
+                        i_south,
+                        width,
+                        line_width
+                    ));
+                }
+                Some(_) => {}
+                None => width = Some(line_width),
+            }
+            for (i_east, char) in line.chars().enumerate() {
+                let tile = Tile::from_char(char)
+                    .ok_or_else(|| anyhow!("line {}: unknown char {:?}", i_south, char))?;
+                let pos =
+                    GlobalPos::default() + Vec2::new_east_south(i_east as i16, i_south as i16);
+                if tile.is_item() {
+                    items.push((pos, tile));
+                } else if tile.is_bot() {
+                    spawns.push(pos);
+                }
+                tiles.push(tile.is_walkable_terrain());
+            }
+            height += 1;
+        }
+        let width = width.ok_or(anyhow!("unknown line length -> zero lines?"))?;
+        Ok(Self {
+            map: Map {
+                tiles,
+                width,
+                height,
+            },
+            items,
+            spawns,
+        })
+    }
+
+    pub fn from_map(map: Map) -> Self {
+        Self {
+            map,
+            items: Vec::new(),
+            spawns: Vec::new(),
+        }
+    }
+}
+
+impl From<Map> for Arena {
+    fn from(map: Map) -> Self {
+        Self::from_map(map)
+    }
+}
@@ -0,0 +1,125 @@
+use async_kartoffel_generic::{Direction, Rotation, Vec2};
+use host_kartoffel::{World, WorldBot};
+use kartoffel_gps::GlobalPos;
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+/// What a scripted bot does next. Every action except [`Action::Wait`] has the cooldown of the
+/// corresponding peripheral of our own bot.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Action {
+    /// do nothing for the given number of ticks
+    Wait(u32),
+    StepFw,
+    StepBw,
+    TurnLeft,
+    TurnRight,
+    Stab,
+}
+
+/// Behaviour of a scripted bot. It is asked for its next action whenever its previous action has
+/// cooled down.
+pub trait Enemy {
+    /// `bot` is the scripted bot itself, [`World::me`] is the bot under test.
+    fn act(&mut self, bot: &WorldBot, world: &World) -> Action;
+}
+
+impl<F: FnMut(&WorldBot, &World) -> Action> Enemy for F {
+    fn act(&mut self, bot: &WorldBot, world: &World) -> Action {
+        self(bot, world)
+    }
+}
+
+fn front(bot: &WorldBot) -> GlobalPos {
+    bot.pos + Vec2::new_in_direction(bot.facing, 1)
+}
+
+fn is_free(world: &World, pos: GlobalPos) -> bool {
+    world.map().get(pos) && world.bot_at(pos).is_none()
+}
+
+/// Never moves, an easy target.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Idle;
+
+impl Enemy for Idle {
+    fn act(&mut self, _bot: &WorldBot, _world: &World) -> Action {
+        Action::Wait(u32::MAX)
+    }
+}
+
+/// Walks straight until blocked, then turns right. Stabs whatever is in front.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Roomba;
+
+impl Enemy for Roomba {
+    fn act(&mut self, bot: &WorldBot, world: &World) -> Action {
+        let front = front(bot);
+        if world.tile_at(front).is_bot() {
+            Action::Stab
+        } else if is_free(world, front) {
+            Action::StepFw
+        } else {
+            Action::TurnRight
+        }
+    }
+}
+
+/// Random walk, deterministic given the seed. Stabs whatever is in front.
+#[derive(Clone, Debug)]
+pub struct Wanderer {
+    rng: SmallRng,
+}
+
+impl Wanderer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Enemy for Wanderer {
+    fn act(&mut self, bot: &WorldBot, world: &World) -> Action {
+        let front = front(bot);
+        if world.tile_at(front).is_bot() {
+            Action::Stab
+        } else if is_free(world, front) && self.rng.random_ratio(3, 4) {
+            Action::StepFw
+        } else if self.rng.random() {
+            Action::TurnLeft
+        } else {
+            Action::TurnRight
+        }
+    }
+}
+
+/// Moves towards our bot once it is within `radius` (manhattan distance, ignoring walls), otherwise
+/// behaves like a [`Roomba`].
+#[derive(Clone, Copy, Debug)]
+pub struct Hunter {
+    pub radius: u16,
+}
+
+impl Enemy for Hunter {
+    fn act(&mut self, bot: &WorldBot, world: &World) -> Action {
+        let target = world.me().pos - bot.pos;
+        let distance = target.east().unsigned_abs() + target.south().unsigned_abs();
+        if front(bot) == world.me().pos {
+            return Action::Stab;
+        }
+        if distance > self.radius {
+            return Roomba.act(bot, world);
+        }
+        let closer = |direction: Direction| target.in_direction(direction) > 0;
+        if closer(bot.facing) && is_free(world, front(bot)) {
+            Action::StepFw
+        } else if closer(bot.facing + Rotation::Left) {
+            Action::TurnLeft
+        } else if closer(bot.facing + Rotation::Right) || closer(bot.facing + Rotation::Inverse) {
+            Action::TurnRight
+        } else {
+            // blocked in the only useful direction
+            Roomba.act(bot, world)
+        }
+    }
+}
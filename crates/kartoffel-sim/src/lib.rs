@@ -0,0 +1,28 @@
+//! Deterministic simulation of a whole arena on the host, to evaluate bots without uploading them.
+//!
+//! An [`Arena`] is loaded from a map file like `maps/map-grotta.txt`. A [`Simulation`] places our
+//! bot and any number of scripted [`Enemy`] bots in it, runs the bot code on the
+//! [`HostBackend`](host_kartoffel::HostBackend) with the real peripheral cooldowns and returns a
+//! [`Report`] of what happened: outcome, survival time and [`WorldStats`](host_kartoffel::WorldStats)
+//! (steps, kills, diamonds picked, ...).
+//!
+//! [`evaluate`] runs a bot over several seeds and summarizes survival time and goal completion,
+//! assertions on the [`Summary`] make regression tests of bot strategies.
+//!
+//! The bot code has to be written against the generic peripherals (e.g. [`host_kartoffel::Bot`]).
+//! `challenge-roomba` is a thin driver around
+//! [`CoverageBot`](async_algorithm::CoverageBot), which is regression-tested here. `runner-gps`
+//! and `runner-slam` are not covered: their strategies are spread over embassy tasks in the riscv
+//! binaries (and `runner-gps` needs the GPS tables of the embedded maps). Moving them into a
+//! generic entry point like `CoverageBot` and adding seeded scenarios for them is a separate
+//! follow-up, listed under "Work in progress" in the README.
+
+mod arena;
+mod enemy;
+mod regression;
+mod simulation;
+
+pub use arena::Arena;
+pub use enemy::{Action, Enemy, Hunter, Idle, Roomba, Wanderer};
+pub use regression::{Summary, evaluate};
+pub use simulation::{Outcome, Report, Simulation};
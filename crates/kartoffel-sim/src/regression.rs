@@ -0,0 +1,177 @@
+use core::future::Future;
+
+use host_kartoffel::Bot;
+
+use crate::{Outcome, Report, Simulation};
+
+/// Survival and goal completion of a bot over several seeded runs, see [`evaluate`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+    pub runs: usize,
+    /// runs in which our bot was not stabbed
+    pub survived: usize,
+    /// runs in which the goal was reached
+    pub completed: usize,
+    /// the shortest time our bot was alive, over all runs. A run that didn't end with its death
+    /// counts with its full length.
+    pub min_survival_ticks: u32,
+    /// the longest time needed to reach the goal, over the runs that reached it
+    pub max_completion_ticks: Option<u32>,
+}
+
+impl Summary {
+    pub fn all_survived(&self) -> bool {
+        self.survived == self.runs
+    }
+
+    pub fn all_completed(&self) -> bool {
+        self.completed == self.runs
+    }
+
+    fn add<T>(&mut self, report: &Report<T>, completed: bool) {
+        self.min_survival_ticks = match self.runs {
+            0 => report.ticks,
+            _ => self.min_survival_ticks.min(report.ticks),
+        };
+        self.runs += 1;
+        self.survived += usize::from(report.survived());
+        if completed {
+            self.completed += 1;
+            self.max_completion_ticks = self.max_completion_ticks.max(Some(report.ticks));
+        }
+    }
+}
+
+/// Run `bot` once per seed, in the simulation that `setup` creates for the seed, for at most
+/// `max_ticks`. A run reached the goal if the bot future returned and `goal` accepts the report,
+/// e.g. because every flag was picked.
+///
+/// Assertions on the [`Summary`] turn this into a regression test of a strategy: e.g. that every
+/// run completes within a time limit, or that the bot survives a number of enemies for some time.
+pub fn evaluate<T, F: Future<Output = T>>(
+    seeds: impl IntoIterator<Item = u64>,
+    mut setup: impl FnMut(u64) -> Simulation,
+    mut bot: impl FnMut(Bot) -> F,
+    max_ticks: u32,
+    mut goal: impl FnMut(&Report<T>) -> bool,
+) -> Summary {
+    let mut summary = Summary::default();
+    for seed in seeds {
+        let report = setup(seed).run(&mut bot, max_ticks);
+        let completed = matches!(report.outcome, Outcome::Finished(_)) && goal(&report);
+        summary.add(&report, completed);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use async_algorithm::{CoverageBot, Map, Terrain};
+    use async_kartoffel_generic::{Direction, Tile};
+    use kartoffel_gps::pos::pos_east_south;
+
+    use super::*;
+    use crate::{Arena, Wanderer};
+
+    /// the roomba challenge: rooms with pillars and flags, no enemies
+    const ROOMS: &str = "\
+##################
+#...#......#.....#
+#.=.#..##..#..=..#
+#...........#....#
+#...#..##........#
+#####.....####.###
+#.......=.#......#
+#..##.....#..=...#
+#.........#......#
+##################";
+
+    fn rooms() -> Arena {
+        Arena::from_reader(ROOMS.as_bytes()).unwrap()
+    }
+
+    async fn roomba(mut bot: Bot) -> Box<CoverageBot> {
+        let facing = bot.compass.direction().await;
+        let mut coverage = Box::new(CoverageBot::new(facing));
+        coverage.run(&mut bot, String::new()).await;
+        coverage
+    }
+
+    /// all flags picked and every walkable tile seen
+    fn roomba_done(arena: &Arena, report: &Report<Box<CoverageBot>>) -> bool {
+        let Outcome::Finished(coverage) = &report.outcome else {
+            return false;
+        };
+        let now = report.world.me().pos;
+        let flags = arena.items.iter().filter(|(_, item)| *item == Tile::Flag);
+        report.stats.flags_picked == flags.count() as u32
+            && arena.map.walkable_positions().vec().iter().all(|&tile| {
+                // positions of the coverage bot are relative to its start
+                let pos = coverage.pose().0 + (tile - now);
+                coverage.coverage().is_covered(pos)
+                    && coverage.map().get(pos) == Some(Terrain::Walkable)
+            })
+    }
+
+    #[test]
+    fn roomba_completes() {
+        let arena = rooms();
+        let summary = evaluate(
+            0..4,
+            |seed| Simulation::new_random(&arena, seed),
+            roomba,
+            20_000_000,
+            |report| roomba_done(&arena, report),
+        );
+        assert!(
+            summary.all_survived() && summary.all_completed(),
+            "{summary:?}"
+        );
+        // about a minute, currently the slowest seed takes 3.2M ticks
+        assert!(
+            summary.max_completion_ticks.unwrap() < 4_000_000,
+            "{summary:?}"
+        );
+    }
+
+    #[test]
+    fn roomba_survival() {
+        let arena = rooms();
+        let summary = evaluate(
+            0..4,
+            |seed| {
+                let mut sim = Simulation::new_random(&arena, seed);
+                sim.spawn_enemies(2, 10, |rng| Wanderer::new(rand::Rng::random(rng)));
+                sim
+            },
+            roomba,
+            40_000_000,
+            |report| roomba_done(&arena, report),
+        );
+        assert_eq!(summary.runs, 4);
+        // the roomba doesn't fight back, currently it survives at least 835k ticks (13 seconds)
+        assert!(summary.min_survival_ticks > 640_000, "{summary:?}");
+    }
+
+    #[test]
+    fn summary() {
+        let arena = Arena::from_reader("#####\n#...#\n#####".as_bytes()).unwrap();
+        let summary = evaluate(
+            [1, 2],
+            |_| Simulation::new(&arena, pos_east_south(1, 1), Direction::East, 0),
+            |mut bot| async move { bot.motor.step_fw().await },
+            1_000_000,
+            |report| report.stats.steps == 1,
+        );
+        assert_eq!(
+            summary,
+            Summary {
+                runs: 2,
+                survived: 2,
+                completed: 2,
+                min_survival_ticks: summary.max_completion_ticks.unwrap(),
+                max_completion_ticks: summary.max_completion_ticks,
+            }
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use core::{
+    future::Future,
+    num::NonZeroU64,
+    task::{Context, Poll, Waker},
+};
+
+use async_kartoffel_generic::{ClockBackend, Direction, Rotation, Vec2, cooldown};
+use host_kartoffel::{Bot, HostClock, POLL_COST, World, WorldStats};
+use kartoffel_gps::GlobalPos;
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+use crate::{Action, Arena, Enemy};
+
+struct EnemySlot {
+    id: NonZeroU64,
+    ready_at: u32,
+    script: Box<dyn Enemy>,
+}
+
+/// How a [`Simulation::run`] ended.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Outcome<T> {
+    /// the bot future returned
+    Finished(T),
+    /// stabbed by the scripted bot with this id
+    Died(NonZeroU64),
+    /// the tick limit was reached
+    Timeout,
+}
+
+#[derive(Debug)]
+pub struct Report<T> {
+    pub outcome: Outcome<T>,
+    /// ticks until the outcome was decided
+    pub ticks: u32,
+    pub stats: WorldStats,
+    pub enemies_left: usize,
+    /// final state of the arena
+    pub world: World,
+}
+
+impl<T> Report<T> {
+    pub fn survived(&self) -> bool {
+        !matches!(self.outcome, Outcome::Died(_))
+    }
+}
+
+/// Deterministic arena simulation: our bot (driven by async bot code on the
+/// [`HostBackend`](host_kartoffel::HostBackend)) and scripted enemies.
+///
+/// Time only advances while the bot future is pending, by [`POLL_COST`] per poll. Enemies act in
+/// the order they were added whenever their previous action has cooled down. Given the same seed,
+/// arena and bot code, every run is identical.
+pub struct Simulation {
+    world: World,
+    rng: SmallRng,
+    enemies: Vec<EnemySlot>,
+}
+
+impl Simulation {
+    pub fn new(arena: &Arena, pos: GlobalPos, facing: Direction, seed: u64) -> Self {
+        let mut world = World::new(arena.map.clone(), pos, facing);
+        for &(pos, item) in &arena.items {
+            world.set_item(pos, Some(item));
+        }
+        Self {
+            world,
+            rng: SmallRng::seed_from_u64(seed),
+            enemies: Vec::new(),
+        }
+    }
+
+    /// Place our bot on a random spawn point of the arena, or on a random free tile if there are
+    /// none.
+    pub fn new_random(arena: &Arena, seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let pos = if arena.spawns.is_empty() {
+            let candidates = arena.map.walkable_positions();
+            let candidates: Vec<_> = candidates
+                .vec()
+                .iter()
+                .copied()
+                .filter(|pos| arena.items.iter().all(|(item, _)| item != pos))
+                .collect();
+            assert!(!candidates.is_empty(), "arena has no free tile");
+            candidates[rng.random_range(0..candidates.len())]
+        } else {
+            arena.spawns[rng.random_range(0..arena.spawns.len())]
+        };
+        let facing = Direction::all()[rng.random_range(0..4)];
+        let mut sim = Self::new(arena, pos, facing, seed);
+        sim.rng = rng;
+        sim
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn add_enemy(
+        &mut self,
+        pos: GlobalPos,
+        facing: Direction,
+        enemy: impl Enemy + 'static,
+    ) -> NonZeroU64 {
+        let id = self.world.add_bot(pos, facing);
+        self.enemies.push(EnemySlot {
+            id,
+            ready_at: 0,
+            script: Box::new(enemy),
+        });
+        id
+    }
+
+    /// Place `n` enemies on random free tiles at least `min_distance` (manhattan) away from our
+    /// bot. `enemy` receives the simulation rng, e.g. to seed a [`Wanderer`](crate::Wanderer).
+    pub fn spawn_enemies<E: Enemy + 'static>(
+        &mut self,
+        n: usize,
+        min_distance: u16,
+        mut enemy: impl FnMut(&mut SmallRng) -> E,
+    ) -> Vec<NonZeroU64> {
+        let me = self.world.me().pos;
+        let mut candidates: Vec<_> = self
+            .world
+            .map()
+            .walkable_positions()
+            .vec()
+            .iter()
+            .copied()
+            .filter(|&pos| {
+                let vec = pos - me;
+                vec.east().unsigned_abs() + vec.south().unsigned_abs() >= min_distance
+            })
+            .filter(|&pos| self.world.is_free(pos))
+            .collect();
+        assert!(candidates.len() >= n, "not enough free tiles for enemies");
+        (0..n)
+            .map(|_| {
+                let pos = candidates.swap_remove(self.rng.random_range(0..candidates.len()));
+                let facing = Direction::all()[self.rng.random_range(0..4)];
+                let enemy = enemy(&mut self.rng);
+                self.add_enemy(pos, facing, enemy)
+            })
+            .collect()
+    }
+
+    /// Run the bot code until it returns, our bot is stabbed or `max_ticks` passed.
+    pub fn run<F: Future>(self, bot: impl FnOnce(Bot) -> F, max_ticks: u32) -> Report<F::Output> {
+        let Self {
+            world, mut enemies, ..
+        } = self;
+        world.install();
+
+        let mut future = Box::pin(bot(Bot::take()));
+        let mut cx = Context::from_waker(Waker::noop());
+        let outcome = loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                break Outcome::Finished(output);
+            }
+            if HostClock::now() >= max_ticks {
+                break Outcome::Timeout;
+            }
+            HostClock::advance(POLL_COST);
+            if let Some(id) = World::with(|world| Self::step_enemies(world, &mut enemies)) {
+                break Outcome::Died(id);
+            }
+        };
+        let ticks = HostClock::now();
+        drop(future);
+
+        // unwrap: installed above
+        let world = World::uninstall().unwrap();
+        Report {
+            outcome,
+            ticks,
+            stats: *world.stats(),
+            enemies_left: world.bots().len(),
+            world,
+        }
+    }
+
+    /// returns the id of the enemy that stabbed our bot
+    fn step_enemies(world: &mut World, enemies: &mut Vec<EnemySlot>) -> Option<NonZeroU64> {
+        let now = HostClock::now();
+        // enemies may have been stabbed since
+        enemies.retain(|slot| world.bots().iter().any(|bot| bot.id == slot.id));
+
+        let mut killed = Vec::new();
+        for slot in enemies.iter_mut() {
+            if slot.ready_at > now || killed.contains(&slot.id) {
+                continue;
+            }
+            // unwrap: retained above and not killed in this round
+            let index = world
+                .bots()
+                .iter()
+                .position(|bot| bot.id == slot.id)
+                .unwrap();
+            let bot = world.bots()[index];
+            let action = slot.script.act(&bot, world);
+            let cooldown = match action {
+                Action::Wait(ticks) => ticks,
+                Action::StepFw | Action::StepBw => {
+                    let (vec, cooldown) = if action == Action::StepFw {
+                        (Vec2::new_front(1), cooldown::MOTOR_STEP_FW)
+                    } else {
+                        (Vec2::new_back(1), cooldown::MOTOR_STEP_BW)
+                    };
+                    let target = bot.pos + vec.global(bot.facing);
+                    if world.map().get(target) && world.bot_at(target).is_none() {
+                        world.bots_mut()[index].pos = target;
+                    }
+                    cooldown
+                }
+                Action::TurnLeft => {
+                    world.bots_mut()[index].facing += Rotation::Left;
+                    cooldown::MOTOR_TURN
+                }
+                Action::TurnRight => {
+                    world.bots_mut()[index].facing += Rotation::Right;
+                    cooldown::MOTOR_TURN
+                }
+                Action::Stab => {
+                    let front = bot.pos + Vec2::new_in_direction(bot.facing, 1);
+                    if world.me().pos == front {
+                        return Some(bot.id);
+                    }
+                    if let Some(victim) = world.bot_at(front).map(|victim| victim.id) {
+                        world.remove_bot(victim);
+                        killed.push(victim);
+                    }
+                    cooldown::ARM_STAB
+                }
+            };
+            slot.ready_at = now.saturating_add(cooldown);
+        }
+        enemies.retain(|slot| !killed.contains(&slot.id));
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_kartoffel_generic::{D3, Direction, RadarScanTrait, Tile, Vec2};
+    use host_kartoffel::Bot;
+    use kartoffel_gps::pos::pos_east_south;
+
+    use crate::{Arena, Hunter, Idle, Outcome, Roomba, Simulation, Wanderer};
+
+    fn grotta() -> Arena {
+        Arena::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../maps/map-grotta.txt"
+        ))
+        .unwrap()
+    }
+
+    fn arena(s: &str) -> Arena {
+        Arena::from_reader(s.as_bytes()).unwrap()
+    }
+
+    /// stab bots in front, walk straight and turn right when blocked
+    async fn stabber(mut bot: Bot) {
+        loop {
+            let scan = bot.radar.scan::<D3>().await;
+            let front = Vec2::new_front(1);
+            let tile = scan.at(front).unwrap();
+            drop(scan);
+            if tile.is_bot() {
+                bot.arm.stab().await;
+            } else if tile.is_empty() {
+                bot.motor.step_fw().await;
+            } else {
+                bot.motor.turn_right().await;
+            }
+        }
+    }
+
+    #[test]
+    fn load_arena() {
+        let arena = arena("#####\n#.*@#\n#####");
+        assert_eq!((arena.map.width, arena.map.height), (5, 3));
+        assert_eq!(arena.items, vec![(pos_east_south(2, 1), Tile::Diamond)]);
+        assert_eq!(arena.spawns, vec![pos_east_south(3, 1)]);
+        assert!(arena.map.get(pos_east_south(2, 1)));
+        assert!(!arena.map.get(pos_east_south(0, 1)));
+
+        assert!(Arena::from_reader("##\n#".as_bytes()).is_err());
+        assert!(Arena::from_reader("#?".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn kill_idle() {
+        let mut sim = Simulation::new(
+            &arena("#######\n#.....#\n#######"),
+            pos_east_south(1, 1),
+            Direction::East,
+            0,
+        );
+        sim.add_enemy(pos_east_south(5, 1), Direction::West, Idle);
+        let report = sim.run(stabber, 1_000_000);
+        assert_eq!(report.outcome, Outcome::Timeout);
+        assert_eq!(report.stats.kills, 1);
+        assert_eq!(report.enemies_left, 0);
+        assert!(report.stats.steps >= 3);
+    }
+
+    #[test]
+    fn killed_by_hunter() {
+        let mut sim = Simulation::new(
+            &arena("#######\n#.....#\n#######"),
+            pos_east_south(1, 1),
+            Direction::West,
+            0,
+        );
+        sim.add_enemy(pos_east_south(5, 1), Direction::West, Hunter { radius: 10 });
+        let report = sim.run(
+            |mut bot| async move {
+                loop {
+                    bot.radar.scan::<D3>().await;
+                }
+            },
+            1_000_000,
+        );
+        assert!(!report.survived());
+        assert!(matches!(report.outcome, Outcome::Died(_)));
+        assert_eq!(report.world.bots()[0].pos, pos_east_south(2, 1));
+    }
+
+    #[test]
+    fn grotta_deterministic() {
+        let run = |seed| {
+            let arena = grotta();
+            let mut sim = Simulation::new_random(&arena, seed);
+            sim.spawn_enemies(10, 8, |rng| Wanderer::new(rand::Rng::random(rng)));
+            sim.spawn_enemies(10, 8, |_| Roomba);
+            sim.run(stabber, 20_000_000)
+        };
+        let (a, b) = (run(7), run(7));
+        assert_eq!(a.outcome, b.outcome);
+        assert_eq!(a.ticks, b.ticks);
+        assert_eq!(a.stats, b.stats);
+        assert_eq!(a.world.me(), b.world.me());
+        assert_eq!(a.world.bots(), b.world.bots());
+        assert!(a.stats.steps > 0);
+    }
+}
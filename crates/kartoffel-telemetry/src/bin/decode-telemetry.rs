@@ -0,0 +1,30 @@
+use std::io::Read;
+
+use anyhow::Context;
+use kartoffel_telemetry::Frames;
+
+/// Prints the telemetry events of a serial log, read from the file given as argument or stdin.
+fn main() -> anyhow::Result<()> {
+    let mut log = String::new();
+    match std::env::args().nth(1) {
+        Some(path) => {
+            log = std::fs::read_to_string(&path).with_context(|| format!("reading {path}"))?
+        }
+        None => _ = std::io::stdin().read_to_string(&mut log)?,
+    }
+
+    let mut n_errors = 0;
+    for frame in Frames::new(&log) {
+        match frame {
+            Ok(event) => println!("{}\t{:?}", event.ticks, event.record),
+            Err(err) => {
+                eprintln!("invalid frame: {err:?}");
+                n_errors += 1;
+            }
+        }
+    }
+    if n_errors > 0 {
+        eprintln!("{n_errors} invalid frames");
+    }
+    Ok(())
+}
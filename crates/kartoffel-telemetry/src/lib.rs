@@ -0,0 +1,84 @@
+//! Decodes the telemetry frames of [`async_algorithm::telemetry`] from a captured serial log back
+//! into [`Event`]s. Text printed in between frames (e.g. with `println!`) is skipped, and frames
+//! wrapped over several lines by the serial display are joined.
+
+pub use async_algorithm::telemetry::{DecodeError, Event, NavigationStatus, Record};
+
+use async_algorithm::telemetry::{FRAME_END, FRAME_START, decode};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameError {
+    /// a new frame started before the previous one ended, or the log ended
+    Truncated,
+    Decode(DecodeError),
+}
+
+/// Iterator over the frames of a serial log, see [`Frames::new`].
+pub struct Frames<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Frames<'a> {
+    pub fn new(log: &'a str) -> Self {
+        Self { rest: log }
+    }
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<Event, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.rest.find(FRAME_START)?;
+        let content = &self.rest[start + FRAME_START.len_utf8()..];
+        match content.find([FRAME_START, FRAME_END]) {
+            Some(end) if content[end..].starts_with(FRAME_END) => {
+                self.rest = &content[end + FRAME_END.len_utf8()..];
+                Some(decode(&content[..end]).map_err(FrameError::Decode))
+            }
+            Some(end) => {
+                // continue with the next frame
+                self.rest = &content[end..];
+                Some(Err(FrameError::Truncated))
+            }
+            None => {
+                self.rest = "";
+                Some(Err(FrameError::Truncated))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_algorithm::telemetry::write;
+    use async_kartoffel_generic::{Direction, Position, Vec2};
+
+    use super::*;
+
+    #[test]
+    fn frames_in_log() {
+        let event = Event {
+            ticks: 640_000,
+            record: Record::Pose {
+                pos: Position::add_to_anchor(Vec2::new_east_south(4, -2)),
+                facing: Direction::South,
+            },
+        };
+        let mut frame = String::new();
+        write(&mut frame, &event).unwrap();
+        let (head, tail) = frame.split_at(10);
+
+        let log = format!("async_kartoffel\n{frame}pos update\n<AAAA{head}\n{tail}\n{frame}<");
+        let frames: Vec<_> = Frames::new(&log).collect();
+        assert_eq!(
+            frames,
+            [
+                Ok(event),
+                Err(FrameError::Truncated),
+                Ok(event),
+                Ok(event),
+                Err(FrameError::Truncated),
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use core::cell::RefCell;
+
+use async_kartoffel_generic::{BackendState, BotBackend};
+use critical_section::Mutex;
+use kartoffel::{
+    arm_drop, arm_pick, arm_stab, compass_dir, is_arm_ready, is_motor_ready, is_radar_ready,
+    motor_step_bw, motor_step_fw, motor_turn_left, motor_turn_right, radar_read, radar_scan,
+};
+
+use crate::KartoffelClock;
+
+/// The [`BotBackend`] of the actual bot, calling into the kartoffel firmware.
+pub enum KartoffelBackend {}
+
+static STATE: Mutex<RefCell<BackendState>> = Mutex::new(RefCell::new(BackendState::new()));
+
+impl BotBackend for KartoffelBackend {
+    type Clock = KartoffelClock;
+
+    #[inline(always)]
+    fn is_motor_ready() -> bool {
+        is_motor_ready()
+    }
+    #[inline(always)]
+    fn motor_step_fw() {
+        motor_step_fw()
+    }
+    #[inline(always)]
+    fn motor_step_bw() {
+        motor_step_bw()
+    }
+    #[inline(always)]
+    fn motor_turn_left() {
+        motor_turn_left()
+    }
+    #[inline(always)]
+    fn motor_turn_right() {
+        motor_turn_right()
+    }
+
+    #[inline(always)]
+    fn is_arm_ready() -> bool {
+        is_arm_ready()
+    }
+    #[inline(always)]
+    fn arm_stab() {
+        arm_stab()
+    }
+    #[inline(always)]
+    fn arm_pick() {
+        arm_pick()
+    }
+    #[inline(always)]
+    fn arm_drop(idx: u8) {
+        arm_drop(idx)
+    }
+
+    #[inline(always)]
+    fn is_radar_ready() -> bool {
+        is_radar_ready()
+    }
+    #[inline(always)]
+    fn radar_scan(d: u8) {
+        radar_scan(d.into())
+    }
+    #[inline(always)]
+    fn radar_read(d: u8, dx: i8, dy: i8, z: u8) -> u32 {
+        radar_read(d.into(), dx, dy, z.into())
+    }
+
+    #[inline(always)]
+    fn compass_dir() -> u32 {
+        compass_dir()
+    }
+
+    fn with_state<T>(f: impl FnOnce(&mut BackendState) -> T) -> T {
+        critical_section::with(|cs| f(&mut STATE.borrow_ref_mut(cs)))
+    }
+}
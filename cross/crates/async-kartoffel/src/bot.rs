@@ -0,0 +1,11 @@
+use crate::KartoffelBackend;
+
+pub type Bot = async_kartoffel_generic::Bot<KartoffelBackend>;
+pub type Motor = async_kartoffel_generic::Motor<KartoffelBackend>;
+pub type Arm = async_kartoffel_generic::Arm<KartoffelBackend>;
+pub type Compass = async_kartoffel_generic::Compass<KartoffelBackend>;
+pub type Radar = async_kartoffel_generic::Radar<KartoffelBackend>;
+pub type RadarScan<Size> = async_kartoffel_generic::RadarScan<KartoffelBackend, Size>;
+pub type RadarScanWeak<Size> = async_kartoffel_generic::RadarScanWeak<KartoffelBackend, Size>;
+pub type CheckedStep<Size> = async_kartoffel_generic::CheckedStep<KartoffelBackend, Size>;
+pub type Reactor = async_kartoffel_generic::Reactor<KartoffelBackend>;
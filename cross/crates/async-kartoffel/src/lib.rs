@@ -6,15 +6,24 @@
 
 extern crate alloc;
 
+mod backend;
 mod bot;
 mod clock;
 #[cfg(feature = "critical-section-impl")]
 mod critical_section_impl;
+mod serial;
+#[cfg(feature = "embassy-time")]
+pub mod time_driver;
 
+#[cfg(feature = "embassy-time")]
+use async_kartoffel_generic::ClockBackend;
 use kartoffel::timer_seed;
 
-pub use bot::{Arm, Bot, Compass, Motor, Radar, RadarScan, RadarScanWeak};
+pub use async_kartoffel_generic::{Inventory, InventoryError, ItemKind, StepOutcome};
+pub use backend::KartoffelBackend;
+pub use bot::{Arm, Bot, CheckedStep, Compass, Motor, Radar, RadarScan, RadarScanWeak, Reactor};
 pub use clock::{Duration, Instant, KartoffelClock, Timer};
+pub use serial::{Serial, send_telemetry};
 
 #[cfg(target_arch = "riscv32")]
 pub use kartoffel::{print, println};
@@ -26,6 +35,24 @@ pub fn random_seed() -> u32 {
     timer_seed()
 }
 
+/// Wakes futures waiting for the peripherals (see [`Reactor`]) and, with the `embassy-time`
+/// feature, expired embassy timers. Has to be spawned as a task, in between it sleeps until the
+/// earliest deadline (see [`Reactor::sleep_until`]).
+pub async fn drive() -> ! {
+    loop {
+        let next = Reactor::wake_expired();
+        #[cfg(feature = "embassy-time")]
+        let next = {
+            let now = KartoffelClock::now();
+            [next, time_driver::wake_expired().map(time_driver::deadline)]
+                .into_iter()
+                .flatten()
+                .min_by_key(|deadline| deadline.wrapping_sub(now) as i32)
+        };
+        Reactor::sleep_until(next).await;
+    }
+}
+
 #[cfg(all(test, feature = "test-kartoffel"))]
 #[unsafe(no_mangle)]
 fn main() {
@@ -0,0 +1,36 @@
+use core::fmt::Write;
+
+use async_algorithm::telemetry::{self, Event, MAX_FRAME, Record};
+use heapless::String;
+use kartoffel::serial_write;
+
+use crate::Instant;
+
+/// The serial port as [`core::fmt::Write`], e.g. to write telemetry frames with
+/// [`send_telemetry`]. Blocks until everything is written.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Serial;
+
+impl Write for Serial {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            serial_write(c);
+        }
+        Ok(())
+    }
+}
+
+/// Write a telemetry frame of `record` at the current time to `log`, e.g. [`Serial`] or an
+/// `async_algorithm::SerialWriter`. It can be decoded from the serial log with `decode-telemetry`.
+///
+/// The frame is written with a single `write_str`, so a `SerialWriter` drops it only as a whole.
+pub fn send_telemetry(mut log: impl Write, record: Record) {
+    let event = Event {
+        ticks: Instant::now().as_ticks(),
+        record,
+    };
+    let mut frame = String::<MAX_FRAME>::new();
+    // can't fail, every frame fits
+    _ = telemetry::write(&mut frame, &event);
+    _ = log.write_str(&frame);
+}
@@ -0,0 +1,200 @@
+//! [`embassy_time_driver::Driver`] on top of [`KartoffelClock`], so `embassy-time` (and everything
+//! built on it, like `embassy_time::with_timeout`) can be used on the bot.
+//!
+//! The kartoffel timer has 32 bits and wraps after about 18 hours, the driver extends it to the 64
+//! bits embassy expects. There is no timer interrupt, so expired alarms are woken by
+//! [`drive`](crate::drive), which has to be spawned as a task. Scheduling an alarm ends its sleep
+//! if the alarm is earlier than the deadline it sleeps until.
+
+use core::{cell::RefCell, task::Waker};
+
+use async_kartoffel_generic::ClockBackend;
+use critical_section::Mutex;
+use embassy_time_driver::{Driver, time_driver_impl};
+use heapless::Vec;
+
+use crate::{Duration, Instant, KartoffelClock, Reactor};
+
+/// Maximum number of wakers waiting for an alarm at the same time. If the queue is full, further
+/// wakers are woken immediately, which is correct but causes busy polling.
+pub const ALARM_QUEUE_LEN: usize = 16;
+
+/// 32 bit kartoffel clock extended to 64 bit, works as long as it is read at least once per
+/// wrap-around of the 32 bit clock
+struct ExtendedClock {
+    last: u32,
+    high: u32,
+}
+
+impl ExtendedClock {
+    fn now(&mut self) -> u64 {
+        let ticks = KartoffelClock::now();
+        if ticks < self.last {
+            self.high += 1;
+        }
+        self.last = ticks;
+        (u64::from(self.high) << 32) | u64::from(ticks)
+    }
+}
+
+struct State {
+    clock: ExtendedClock,
+    alarms: Vec<(u64, Waker), ALARM_QUEUE_LEN>,
+}
+
+pub struct KartoffelTimeDriver {
+    state: Mutex<RefCell<State>>,
+}
+
+time_driver_impl!(static DRIVER: KartoffelTimeDriver = KartoffelTimeDriver {
+    state: Mutex::new(RefCell::new(State {
+        clock: ExtendedClock { last: 0, high: 0 },
+        alarms: Vec::new(),
+    })),
+});
+
+impl Driver for KartoffelTimeDriver {
+    fn now(&self) -> u64 {
+        critical_section::with(|cs| self.state.borrow_ref_mut(cs).clock.now())
+    }
+
+    fn schedule_wake(&self, at: u64, waker: &Waker) {
+        let wake_now = critical_section::with(|cs| {
+            let mut state = self.state.borrow_ref_mut(cs);
+            if at <= state.clock.now() {
+                return true;
+            }
+            if let Some((alarm, _)) = state.alarms.iter_mut().find(|(_, w)| w.will_wake(waker)) {
+                *alarm = (*alarm).min(at);
+                false
+            } else {
+                state.alarms.push((at, waker.clone())).is_err()
+            }
+        });
+        if wake_now {
+            waker.wake_by_ref();
+        } else {
+            // outside of the critical section, this might wake drive
+            Reactor::interrupt_sleep(deadline(at));
+        }
+    }
+}
+
+impl KartoffelTimeDriver {
+    /// Wake all alarms that are due. Returns the time of the next alarm.
+    pub fn wake_expired(&self) -> Option<u64> {
+        let mut expired = Vec::<Waker, ALARM_QUEUE_LEN>::new();
+        let next = critical_section::with(|cs| {
+            let mut state = self.state.borrow_ref_mut(cs);
+            let now = state.clock.now();
+            let mut i = 0;
+            while i < state.alarms.len() {
+                if state.alarms[i].0 <= now {
+                    let (_, waker) = state.alarms.swap_remove(i);
+                    // unwrap: same capacity as alarms
+                    expired.push(waker).unwrap();
+                } else {
+                    i += 1;
+                }
+            }
+            state.alarms.iter().map(|(at, _)| *at).min()
+        });
+        // wake outside of the critical section
+        for waker in expired {
+            waker.wake();
+        }
+        next
+    }
+}
+
+/// Wake all embassy timers that are due, see [`KartoffelTimeDriver::wake_expired`].
+pub fn wake_expired() -> Option<u64> {
+    DRIVER.wake_expired()
+}
+
+/// The 32 bit tick of the 64 bit `at`, at most [`Instant::MAX_SPAN`] ticks from now, so that it
+/// can be compared wrap-aware. Alarms further away are checked again early.
+pub(crate) fn deadline(at: u64) -> u32 {
+    let now = DRIVER.now();
+    now.saturating_add(at.saturating_sub(now).min(Instant::MAX_SPAN.into())) as u32
+}
+
+/// Lossless conversion to `embassy-time` types and back.
+///
+/// [`Instant`] only has 32 bits, it is extended using the current time. This is exact as long as
+/// the instant is at most [`Instant::MAX_SPAN`] ticks (about 9 hours) away from now, instants
+/// further away are out of range.
+pub trait EmbassyTime: Sized {
+    type Embassy;
+    fn to_embassy(self) -> Self::Embassy;
+    /// `None` if out of range
+    fn from_embassy(value: Self::Embassy) -> Option<Self>;
+}
+
+impl EmbassyTime for Instant {
+    type Embassy = embassy_time::Instant;
+
+    fn to_embassy(self) -> Self::Embassy {
+        let now = DRIVER.now();
+        let behind = (now as u32).wrapping_sub(self.as_ticks()) as i32;
+        embassy_time::Instant::from_ticks(now.wrapping_sub(behind as i64 as u64))
+    }
+
+    fn from_embassy(value: Self::Embassy) -> Option<Self> {
+        let ticks = value.as_ticks();
+        if ticks.abs_diff(DRIVER.now()) > Instant::MAX_SPAN.into() {
+            return None;
+        }
+        Some(Instant::from_ticks(ticks as u32))
+    }
+}
+
+impl EmbassyTime for Duration {
+    type Embassy = embassy_time::Duration;
+
+    fn to_embassy(self) -> Self::Embassy {
+        embassy_time::Duration::from_ticks(self.as_ticks().into())
+    }
+
+    fn from_embassy(value: Self::Embassy) -> Option<Self> {
+        Some(Duration::from_ticks(value.as_ticks().try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_kartoffel::{TestError, assert_eq, option_unwrap};
+
+    #[test_case]
+    fn round_trip() -> Result<(), TestError> {
+        assert_eq!(embassy_time::TICK_HZ, 64_000);
+
+        let now = Instant::now();
+        let embassy = now.to_embassy();
+        assert_eq!(embassy.as_ticks() as u32, now.as_ticks());
+        assert_eq!(option_unwrap!(Instant::from_embassy(embassy)), now);
+        let span = embassy_time::Duration::from_ticks(Instant::MAX_SPAN.into());
+        assert_eq!(
+            option_unwrap!(Instant::from_embassy(embassy + span)).as_ticks(),
+            now.as_ticks().wrapping_add(Instant::MAX_SPAN)
+        );
+        let too_far = span + embassy_time::Duration::from_secs(1);
+        assert_eq!(Instant::from_embassy(embassy + too_far), None);
+
+        let duration = Duration::from_millis(1234);
+        assert_eq!(
+            duration.to_embassy(),
+            embassy_time::Duration::from_millis(1234)
+        );
+        assert_eq!(
+            option_unwrap!(Duration::from_embassy(duration.to_embassy())),
+            duration
+        );
+        assert_eq!(
+            Duration::from_embassy(embassy_time::Duration::from_ticks(1 << 32)),
+            None
+        );
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ fn main() {
     let executor = EXECUTOR.init(Executor::new());
 
     executor.run(|spawner| {
+        spawner.spawn(example_kartoffels::reactor()).unwrap();
         spawner.spawn(main_task(Bot::take())).unwrap();
     })
 }
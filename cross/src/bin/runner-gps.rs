@@ -4,23 +4,29 @@
 #![test_runner(test_kartoffel::runner)]
 #![feature(iter_next_chunk)]
 
-use async_algorithm::{Breakpoint, DistanceManhattan, DistanceMeasure, StatsDog};
+use alloc::boxed::Box;
+use async_algorithm::{
+    Breakpoint, ChunkMapHash, Confidence, DangerMap, DistanceBotWalk, Move, NavigationState,
+    Overflow, Pose, PoseDistances, PoseTracker, SerialWriter, StatsDog, WeightedNavigation,
+    telemetry::{NavigationStatus, Record},
+};
 use async_kartoffel::{
-    Arm, Bot, Instant, KartoffelClock, Motor, Radar, RadarScan, Timer, exit, println,
+    Arm, Bot, Instant, KartoffelClock, Motor, Radar, RadarScan, Serial, Timer, exit, println,
+    send_telemetry,
 };
 use async_kartoffel_generic::{
-    D7, Direction, Duration, Local, RadarScanTrait, RadarSize, Rotation, Tile, Transform, Vec2,
+    D7, Direction, Duration, Position, RadarScanTrait, RadarSize, Rotation, Transform, Vec2,
 };
+use core::fmt::Write;
 use embassy_executor::{Executor, task};
 use embassy_futures::select::{Either, Either3, select, select3};
 use embassy_sync::{blocking_mutex::raw::NoopRawMutex, channel::Channel, signal::Signal};
-use example_kartoffels::{get_global_pos, navigator_resources};
-use heapless::Vec;
+use example_kartoffels::{MapLocalizer, gps, navigator_resources, true_map};
 use kartoffel_gps::{
     GlobalPos,
     beacon::{Navigator, NavigatorEnum, NavigatorError},
     gps::{MapSection, MapSectionTrait},
-    pos::pos_east_south,
+    pos::{GpsAnchor, pos_east_south},
 };
 use static_cell::StaticCell;
 
@@ -36,12 +42,14 @@ fn main() {
     static SIGNAL_DESTINATION: StaticCell<Signal<NoopRawMutex, GlobalPos>> = StaticCell::new();
     static SIGNAL_RESET: StaticCell<Signal<NoopRawMutex, ()>> = StaticCell::new();
     static SIGNAL_COMPLETE: StaticCell<Signal<NoopRawMutex, ()>> = StaticCell::new();
+    static LOG: StaticCell<Log> = StaticCell::new();
 
     let channel_position = CHANNEL_POSITION.init(Channel::new());
     let signal_navigation = SIGNAL_NAVIGATION.init(Signal::new());
     let signal_destination = SIGNAL_DESTINATION.init(Signal::new());
     let signal_reset = SIGNAL_RESET.init(Signal::new());
     let signal_complete = SIGNAL_COMPLETE.init(Signal::new());
+    let log = LOG.init(SerialWriter::new(Overflow::DropOldest));
 
     let executor = EXECUTOR.init(Executor::new());
 
@@ -49,6 +57,8 @@ fn main() {
     println!("gps navigation test");
 
     executor.run(|spawner| {
+        spawner.spawn(example_kartoffels::reactor()).unwrap();
+        spawner.spawn(serial(log)).unwrap();
         spawner
             .spawn(foreground(
                 Bot::take(),
@@ -59,6 +69,7 @@ fn main() {
                     signal_reset,
                 },
                 signal_complete,
+                log,
             ))
             .unwrap();
         spawner
@@ -69,7 +80,7 @@ fn main() {
                 signal_reset,
             }))
             .unwrap();
-        spawner.spawn(watchdog(signal_complete)).unwrap();
+        spawner.spawn(watchdog(signal_complete, log)).unwrap();
     })
 }
 
@@ -79,497 +90,55 @@ struct NavigationSection {
     start: GlobalPos,
 }
 
-impl NavigationSection {
-    /// update if new position is available, only handles trivial navigation (= right direction single step)
-    /// updates
-    fn update(self_: &mut Option<Self>, pos: GlobalPos) {
-        if let Some(section) = self_ {
-            let movement = pos - section.start;
-            let dist = DistanceManhattan::measure(movement);
-            if dist == 1
-                && (section.trivial_dest - section.start)
-                    .directions()
-                    .contains(movement.directions().first().unwrap())
-            {
-                // single step in the right direction to keep trivial navigation invariant
-                section.start = pos;
-            }
-        }
-    }
+/// navigation from the bot to the next target of the beacon navigation, in the local frame
+type LocalNav = WeightedNavigation<
+    ChunkMapHash<16, PoseDistances, [[PoseDistances; 8]; 8]>,
+    DistanceBotWalk,
+    64,
+>;
 
-    fn directions(&self) -> &'static [Direction] {
-        (self.trivial_dest - self.start).directions()
-    }
-}
-
-#[derive(Clone, Copy, Debug)]
-enum ArmAction {
-    Stab,
-}
-#[derive(Clone, Copy, PartialEq, Debug)]
-enum MotorAction {
-    Step,
-    StepBack,
-    TurnRight,
-    TurnLeft,
-}
-impl MotorAction {
-    const ALL_AND_NOTHING: [Option<MotorAction>; 5] = [
-        Some(Self::Step),
-        Some(Self::StepBack),
-        Some(Self::TurnRight),
-        Some(Self::TurnLeft),
-        None,
-    ];
-    async fn execute(&self, motor: &mut Motor) {
-        match self {
-            MotorAction::Step => motor.step_fw().await,
-            MotorAction::StepBack => motor.step_bw().await,
-            MotorAction::TurnRight => motor.turn_right().await,
-            MotorAction::TurnLeft => motor.turn_left().await,
-        }
-    }
-    fn to_transform(motor: Option<MotorAction>) -> Transform {
-        match motor {
-            Some(MotorAction::Step) => Transform::from(Vec2::new_front(1)),
-            Some(MotorAction::TurnLeft) => Transform::from(Rotation::Left),
-            Some(MotorAction::TurnRight) => Transform::from(Rotation::Right),
-            Some(MotorAction::StepBack) => Transform::from(Vec2::new_back(1)),
-            None => Transform::identity(),
-        }
-    }
-}
-
-fn instincts<D: RadarSize>(
-    arm: &Arm,
-    motor: &Motor,
-    radar_scan: &RadarScan<D>,
-    time_stamp: Instant,
-) -> MotorArmAction {
-    let max_stab_wait = Duration::from_ticks(10_000);
-    if arm.is_ready() {
-        // overwrite next movement with urgent one
-        if radar_scan.at(Vec2::new_front(1)).unwrap().is_bot() {
-            MotorArmAction {
-                motor: None,
-                arm: Some(ArmAction::Stab),
-                arm_timeout: time_stamp + max_stab_wait,
-            }
-        } else if radar_scan.at(Vec2::new_left(1)).unwrap().is_bot() {
-            MotorArmAction {
-                motor: Some(MotorAction::TurnLeft),
-                arm: Some(ArmAction::Stab),
-                arm_timeout: time_stamp + max_stab_wait,
-            }
-        } else if radar_scan.at(Vec2::new_right(1)).unwrap().is_bot() {
-            MotorArmAction {
-                motor: Some(MotorAction::TurnRight),
-                arm: Some(ArmAction::Stab),
-                arm_timeout: time_stamp + max_stab_wait,
-            }
-        } else if radar_scan
-            .at(Vec2::new_front(2))
-            .is_some_and(|t| t.is_bot())
-            && radar_scan
-                .at(Vec2::new_front(1))
-                .is_some_and(|t| t.is_empty())
-            && motor.is_ready()
-        {
-            MotorArmAction {
-                motor: Some(MotorAction::Step),
-                arm: Some(ArmAction::Stab),
-                arm_timeout: time_stamp + max_stab_wait,
-            }
-        } else {
-            MotorArmAction {
-                motor: None,
-                arm: None,
-                arm_timeout: time_stamp + max_stab_wait,
-            }
-        }
-    } else {
-        MotorArmAction {
-            motor: None,
-            arm: None,
-            arm_timeout: time_stamp + max_stab_wait,
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct MotorArmAction {
-    motor: Option<MotorAction>,
-    arm: Option<ArmAction>,
-    arm_timeout: Instant,
-}
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-enum ExecutionResult {
-    Success,
-    ArmTimeout,
-    RadarReady,
-}
-
-/// First execute motor action to move to a desired location, then execute arm action to stab.
-/// If a new radar scan is ready, or at a certain timeout instant, it is canceled.
-async fn execute_with_arm_timeout(
-    radar: &mut Radar,
-    motor: &mut Motor,
-    arm: &mut Arm,
-    action: &MotorArmAction,
-) -> (Transform, ExecutionResult) {
-    if let Some(motor_action) = action.motor
-        && matches!(
-            select(radar.wait(), motor_action.execute(motor)).await,
-            Either::First(_)
-        )
-    {
-        return (Transform::identity(), ExecutionResult::RadarReady);
-    }
-    (
-        MotorAction::to_transform(action.motor),
-        if action.arm.is_some() {
-            match select3(radar.wait(), arm.stab(), Timer::at(action.arm_timeout)).await {
-                Either3::First(()) => ExecutionResult::RadarReady,
-                Either3::Second(()) => ExecutionResult::Success,
-                Either3::Third(()) => ExecutionResult::ArmTimeout,
-            }
-        } else {
-            ExecutionResult::Success
-        },
-    )
-}
-
-/// terrain evaluation, higher is better
-fn terrain_eval_func(get_terrain: impl Fn(Vec2<Local>) -> Option<bool>) -> i8 {
-    let mut eval = 0; // large is good
-
-    // back against the wall, very important!
-    if get_terrain(Vec2::new_back(1)).is_some_and(|b| !b) {
-        eval += 8;
-    } else if get_terrain(Vec2::new_back(2)).is_some_and(|b| !b) {
-        eval += 4;
-    }
-
-    // left
-    if get_terrain(Vec2::new_left(1)).is_some_and(|b| !b) {
-        eval += 2;
-    } else if get_terrain(Vec2::new_left(2)).is_some_and(|b| !b) {
-        // an empty space between us and the wall is better, because it
-        // is a very save space for us to stab stupid bots
-        eval += 3;
-    }
-
-    // right
-    if get_terrain(Vec2::new_right(1)).is_some_and(|b| !b) {
-        eval += 2;
-    } else if get_terrain(Vec2::new_right(2)).is_some_and(|b| !b) {
-        // an empty space between us and the wall is better, because it
-        // is a very save space for us to stab stupid bots
-        eval += 3;
-    }
-
-    // front
-    if get_terrain(Vec2::new_front(1)).is_some_and(|b| !b) {
-        // even more important than back against the wall, we want to keep able to move instantly
-        eval -= 10;
-    } else if get_terrain(Vec2::new_front(2)).is_some_and(|b| !b) {
-        // one free space in front is optimal, because then we don't have to move to stab
-        // but at the end of the day it is not that important
-        eval += 1;
-    }
-
-    // Some evaluations:
-    // corridor blocked:         -2
-    // completely open:           0
-    // corridor movable:          4
-    // next to wall back:         8
-    // three-wide corridor:       8
-    // two-wide corridor:         9
-    // corner:                   10
-    // next to corner:           11
-    // dead end:                 12
-    // optimum:                  15
-    // ←↑↓→
-    eval
-}
-
-fn bot_eval_func(dir: Vec2<Local>, stab: bool, back_against_wall: bool) -> (u8, bool) {
-    const VALUES: [[u8; 7]; 7] = [
-        [0, 0, 0, 1, 0, 0, 0],
-        [0, 0, 0, 5, 0, 0, 0],
-        [1, 2, 3, 13, 3, 2, 1],
-        [2, 7, 17, 255, 17, 7, 2],
-        [1, 2, 5, 26, 5, 2, 1],
-        [0, 1, 2, 13, 2, 1, 0],
-        [0, 0, 1, 4, 1, 0, 0],
-    ];
-
-    if stab && dir == Vec2::new_front(1) {
-        // this bot will no longer exist
-        (0, true)
-    } else if dir.front().unsigned_abs() > 3 || dir.right().unsigned_abs() > 3 {
-        // this bot is far away
-        (0, false)
-    } else if back_against_wall && dir.back() > 0 {
-        (0, false)
-    } else {
-        // unconventional indexing (back, right) instead of (right, front) to understand VALUES
-        // intuitively
-        (
-            VALUES[usize::try_from(dir.back() + 3).unwrap()]
-                [usize::try_from(dir.right() + 3).unwrap()],
-            false,
-        )
-    }
-}
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-enum NavigationEvaluation {
-    StepGood,
-    StepBad,
-    NoStep(Rotation), // no movement, rotation to good direction is described by rotation
-    NoInformation,
-}
-
-fn min_rotation(from: Direction, to: &[Direction]) -> Option<Rotation> {
-    to.iter().map(|to| *to - from).min_by_key(|rot| match rot {
-        Rotation::Id => 0,
-        Rotation::Left => 1,
-        Rotation::Right => 1,
-        Rotation::Inverse => 2,
-    })
-}
-
-fn movement(
-    nav_state: &BotNavState,
-    transform_from_scan: Transform,
-    radar_scan: &RadarScan<impl RadarSize>,
-    bots: &[Vec2<Local>],
-    can_stab: bool,
-    time_stamp: Instant,
-) -> MotorArmAction {
-    let max_stab_wait = Duration::from_ticks(10_000);
-    let nav_dirs = nav_state.preferred_directions();
-
-    let (motor, stab) = MotorAction::ALL_AND_NOTHING
-        .into_iter()
-        .filter_map(|movement| {
-            let transform_movement = MotorAction::to_transform(movement);
-            let transform_next_from_scan = transform_from_scan.chain(transform_movement);
-            let next_location = transform_next_from_scan.translation();
-
-            let possible = radar_scan
-                .at(next_location)
-                .is_some_and(|tile| tile.is_walkable_terrain());
-            let back_against_wall = radar_scan
-                .at(transform_next_from_scan
-                    .chain(Vec2::new_back(1).into())
-                    .translation())
-                .is_some_and(|tile| !tile.is_walkable_terrain());
-            if possible {
-                // add evaluation for all bots
-                let (bot_eval, stab) = bots
-                    .iter()
-                    .map(|&bot| {
-                        bot_eval_func(
-                            transform_next_from_scan
-                                .inverse()
-                                .chain(bot.into())
-                                .translation(),
-                            can_stab,
-                            back_against_wall,
-                        )
-                    })
-                    .fold(
-                        (0, false),
-                        |(value_acc, stab_acc): (u8, _), (value, stab)| {
-                            (value_acc.saturating_add(value), stab_acc || stab)
-                        },
-                    );
-
-                // evaluation for being able to walk forward
-                // let wall_eval = wall_eval_func(radar_scan, &t);
-
-                let wall_eval = -terrain_eval_func(|vec| {
-                    radar_scan
-                        .at(transform_next_from_scan.chain(vec.into()).translation())
-                        .map(|tile| tile.is_walkable_terrain())
-                });
-
-                let nav_eval = if nav_dirs.is_empty() {
-                    NavigationEvaluation::NoInformation
-                } else {
-                    let movement_dirs = transform_movement
-                        .translation()
-                        .global(nav_state.facing())
-                        .directions();
-                    if let &[movement_dir] = movement_dirs {
-                        let good = nav_dirs.contains(&movement_dir);
-                        // println!(
-                        //     "nav {:?} {}",
-                        //     movement_dir,
-                        //     if good { "good" } else { "bad" }
-                        // );
-                        if good {
-                            NavigationEvaluation::StepGood
-                        } else {
-                            NavigationEvaluation::StepBad
-                        }
-                    } else {
-                        // unwrap: we checked not empty
-                        let remaining_rotation = min_rotation(
-                            transform_movement.apply_dir(nav_state.facing()),
-                            nav_dirs,
-                        )
-                        .unwrap();
-                        // println!(
-                        //     "nav {:?} {:?}",
-                        //     transform_movement.apply_dir(nav_state.facing()),
-                        //     remaining_rotation,
-                        // );
-                        NavigationEvaluation::NoStep(remaining_rotation)
-                    }
-                };
-
-                // long-term evaluation
-                Some((movement, stab, bot_eval, wall_eval, nav_eval))
-            } else {
-                None
-            }
-        })
-        .min_by_key(|&(movement, stab, bot_eval, wall_eval, nav_eval)| {
-            value_function(
-                movement,
-                stab,
-                bot_eval,
-                wall_eval,
-                bots.is_empty(),
-                nav_eval,
-                nav_state.position_known(),
-            )
-        })
-        .map(|(movement, stab, _, _, _)| (movement, stab))
-        .unwrap_or((None, false));
-    MotorArmAction {
-        motor,
-        arm: if stab { Some(ArmAction::Stab) } else { None },
-        arm_timeout: time_stamp + max_stab_wait,
-    }
-}
-
-/// the action that minimized these values is selected
-fn value_function(
-    movement: Option<MotorAction>,
-    _stab: bool,
-    bot_eval: u8,
-    wall_eval: i8,
-    _bots_empty: bool,
-    nav_eval: NavigationEvaluation,
-    pos_known: bool,
-) -> (u8, i8, i8, bool, bool) {
-    if pos_known {
-        (
-            // disincentivise backward with bot score due to cooldown cost
-            bot_eval
-                + if movement == Some(MotorAction::StepBack) {
-                    3
-                } else {
-                    0
-                },
-            match nav_eval {
-                NavigationEvaluation::StepGood => -4,
-                NavigationEvaluation::StepBad => 1,
-                NavigationEvaluation::NoStep(Rotation::Id) => -3,
-                NavigationEvaluation::NoStep(Rotation::Left) => -2,
-                NavigationEvaluation::NoStep(Rotation::Right) => -2,
-                NavigationEvaluation::NoStep(Rotation::Inverse) => -1,
-                NavigationEvaluation::NoInformation => 0,
-            },
-            wall_eval,
-            // prefer forward
-            movement != Some(MotorAction::Step),
-            // prefer no movement if there are bots, prefer movement if there are no bots
-            // movement.is_some() != (bots.len() == 0),
-            movement.is_some(),
-        )
-    } else {
-        (
-            // disincentivise backward with bot score due to cooldown cost
-            bot_eval
-                + if movement == Some(MotorAction::StepBack) {
-                    3
-                } else {
-                    0
-                },
-            0, // nav info is not available
-            0, // don't hide in corners
-            // prefer forward
-            movement != Some(MotorAction::Step),
-            // prefer no movement if there are bots, prefer movement if there are no bots
-            // movement.is_some() != (bots.len() == 0),
-            movement.is_none(),
-        )
-    }
-}
-
-/// get a list of all bots (that have not yet been stabbed) in the scan area
-fn get_bot_list<const MAX_N_BOTS: usize, D: RadarSize>(
-    transform: Transform,
-    has_stabbed: bool,
-    radar_scan: &RadarScan<D>,
-) -> Vec<Vec2<Local>, MAX_N_BOTS> {
-    let stabbed_location =
-        has_stabbed.then_some(transform.chain(Vec2::new_front(1).into()).translation());
-    if let Some(stabbed_location) = stabbed_location {
-        radar_scan
-            .iter_tile(Tile::Bot)
-            .filter(|&v| v != stabbed_location)
-            .collect()
-    } else {
-        radar_scan.iter_tile(Tile::Bot).collect()
-    }
-}
+/// recently seen bots, in the local frame
+type Danger = DangerMap<KartoffelClock, 8>;
 
 /// Navigation, Position, and Orientation
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct BotNavState {
-    facing: Direction,
-    global_pos: Option<GlobalPos>,
+    pose: PoseTracker<GpsAnchor>,
+    localizer: MapLocalizer,
     last_pos_synced: Option<GlobalPos>,
     navigation_section: Option<NavigationSection>,
+    log: &'static Log,
 }
 
 impl BotNavState {
-    fn new(facing: Direction) -> Self {
+    fn new(facing: Direction, log: &'static Log) -> Self {
+        let mut pose = PoseTracker::new();
+        // unwrap: the first reading can't contradict anything
+        pose.compass(facing).unwrap();
         Self {
-            facing,
-            global_pos: None,
+            pose,
+            localizer: MapLocalizer::new(),
             last_pos_synced: None,
             navigation_section: None,
+            log,
         }
     }
 
-    fn position_known(&self) -> bool {
-        self.global_pos.is_some()
+    fn facing(&self) -> Direction {
+        // unwrap: facing is known since creation
+        self.pose.facing().unwrap()
     }
 
-    fn facing(&self) -> Direction {
-        self.facing
+    fn local_pose(&self) -> Pose {
+        // unwrap: the local pose is known since creation
+        self.pose.local_pose().unwrap()
     }
 
     /// update position and facing after the bot moved, and sync with navigation task
     async fn update_and_sync(&mut self, transform: Transform, sync: &DataSync) {
-        // apply transform to modify pos and facing
-        if let Some(old_pos) = self.global_pos {
-            let new_pos;
-            (new_pos, self.facing) = transform.apply(old_pos, self.facing);
-            self.global_pos = Some(new_pos);
-        } else {
-            self.facing = transform.apply_dir(self.facing);
-        }
+        self.localizer.moved(transform, self.facing(), true_map());
+        self.pose.moved(transform);
 
-        if let Some(global_pos) = self.global_pos {
+        if let Some(global_pos) = self.pose.global_position() {
             // send new position to navigator
             if self.last_pos_synced.is_none_or(|last| last != global_pos) {
                 if sync.channel_position.is_full() {
@@ -585,20 +154,29 @@ impl BotNavState {
         }
 
         self.sync_navigation(sync);
-
-        if let Some(global_pos) = self.global_pos {
-            // update navigation section
-            NavigationSection::update(&mut self.navigation_section, global_pos);
-        }
     }
 
     /// update global position if new scan is available to analyse
     fn analyse_scan(&mut self, radar_scan: &RadarScan<D7>) {
-        if let Some(pos) = get_global_pos(&MapSection::from_scan(radar_scan, self.facing))
-            && self.global_pos.is_none_or(|old_pos| old_pos != pos)
+        let facing = self.facing();
+        let chunk = MapSection::from_scan(radar_scan, facing);
+        if let Some(pos) = self
+            .localizer
+            .scan(&chunk, true_map(), |chunk| gps().pos_candidates(chunk))
         {
-            self.global_pos = Some(pos);
-            println!("pos update {}", pos);
+            let known = self.pose.confidence() == Confidence::Global;
+            if let Err(contradiction) = self.pose.fix(pos, facing) {
+                _ = writeln!(self.log, "pos update {:?}", contradiction);
+            } else if !known {
+                _ = writeln!(self.log, "pos update {}", pos);
+            }
+            send_telemetry(
+                self.log,
+                Record::Pose {
+                    pos: to_position(pos),
+                    facing,
+                },
+            );
         }
     }
 
@@ -608,19 +186,167 @@ impl BotNavState {
             match update {
                 Ok(section) => {
                     self.navigation_section = Some(section);
-                    // println!("<<n {} {}", section.start, section.trivial_dest);
+                    send_telemetry(
+                        self.log,
+                        Record::Navigation {
+                            status: NavigationStatus::Success,
+                            from: to_position(section.start),
+                            to: to_position(section.trivial_dest),
+                        },
+                    );
                 }
-                Err(err) => println!("nav err: {:?}", err),
+                Err(err) => {
+                    _ = writeln!(self.log, "nav err: {:?}", err);
+                    send_telemetry(
+                        self.log,
+                        Record::Navigation {
+                            status: NavigationStatus::Error,
+                            from: Default::default(),
+                            to: Default::default(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// The first move of the cheapest path to the next target of the beacon navigation, stepping
+    /// onto a tile additionally costs `tile_cost`. None if the position or the target is unknown,
+    /// the target is reached, or it can't be reached.
+    async fn next_move(
+        &self,
+        nav: &mut LocalNav,
+        tile_cost: impl Fn(Position) -> u16,
+    ) -> Option<Move> {
+        let target = self.pose.to_local(self.navigation_section?.trivial_dest)?;
+        let pose = self.local_pose();
+        let can_go = |pos| {
+            self.pose
+                .to_global(pos)
+                .is_some_and(|pos| true_map().get(pos))
+        };
+        // the costs change with every scan, so the search starts over
+        nav.initialize(pose, target);
+        nav.run_with_cost(can_go, &tile_cost).await;
+        match nav.get_state() {
+            NavigationState::Success(_) => nav.next_move_with_cost(pose, tile_cost),
+            NavigationState::Error(err) => {
+                let mut log = self.log;
+                _ = writeln!(log, "local nav err: {:?}", err);
+                None
             }
+            _ => None,
         }
     }
+}
+
+/// A move towards a bot next to us, followed by a stab that is given up at `arm_timeout`
+#[derive(Clone, Copy, Debug)]
+struct Attack {
+    motor: Option<Move>,
+    arm_timeout: Instant,
+}
+
+/// Bots next to us are attacked before navigating: stab a bot in front, turn to a bot on the left
+/// or right, or step towards a bot two tiles ahead.
+fn instincts<D: RadarSize>(
+    arm: &Arm,
+    motor: &Motor,
+    radar_scan: &RadarScan<D>,
+    time_stamp: Instant,
+) -> Option<Attack> {
+    let max_stab_wait = Duration::from_ticks(10_000);
+    let is_bot = |vec| radar_scan.at(vec).is_some_and(|tile| tile.is_bot());
+    if !arm.is_ready() {
+        return None;
+    }
+    let motor = if is_bot(Vec2::new_front(1)) {
+        None
+    } else if is_bot(Vec2::new_left(1)) {
+        Some(Move::TurnLeft)
+    } else if is_bot(Vec2::new_right(1)) {
+        Some(Move::TurnRight)
+    } else if is_bot(Vec2::new_front(2))
+        && radar_scan
+            .at(Vec2::new_front(1))
+            .is_some_and(|tile| tile.is_empty())
+        && motor.is_ready()
+    {
+        Some(Move::Forward)
+    } else {
+        return None;
+    };
+    Some(Attack {
+        motor,
+        arm_timeout: time_stamp + max_stab_wait,
+    })
+}
+
+/// First execute the move of `attack`, then stab. Canceled if a new radar scan is ready, or at the
+/// arm timeout.
+async fn execute_attack(
+    radar: &mut Radar,
+    motor: &mut Motor,
+    arm: &mut Arm,
+    attack: &Attack,
+) -> Transform {
+    let transform = match attack.motor {
+        Some(step) => match select(radar.wait(), execute(motor, step)).await {
+            Either::First(()) => return Transform::identity(),
+            Either::Second(transform) => transform,
+        },
+        None => Transform::identity(),
+    };
+    select3(radar.wait(), arm.stab(), Timer::at(attack.arm_timeout)).await;
+    transform
+}
+
+/// While the position is unknown: walk straight until a wall, then turn, to see new terrain for
+/// the localizer.
+fn explore(radar_scan: &RadarScan<D7>) -> Move {
+    if radar_scan
+        .at(Vec2::new_front(1))
+        .is_some_and(|tile| tile.is_empty())
+    {
+        Move::Forward
+    } else if radar_scan
+        .at(Vec2::new_left(1))
+        .is_some_and(|tile| tile.is_walkable_terrain())
+    {
+        Move::TurnLeft
+    } else {
+        Move::TurnRight
+    }
+}
+
+/// whether `step` can be executed now, steps need an empty tile
+fn is_free(radar_scan: &RadarScan<D7>, step: Move) -> bool {
+    let vec = match step {
+        Move::Forward => Vec2::new_front(1),
+        Move::Backward => Vec2::new_back(1),
+        Move::TurnLeft | Move::TurnRight => return true,
+    };
+    radar_scan.at(vec).is_some_and(|tile| tile.is_empty())
+}
 
-    /// navigation directions
-    fn preferred_directions(&self) -> &[Direction] {
-        self.navigation_section
-            .filter(|section| Some(section.start) == self.global_pos)
-            .map(|section| section.directions())
-            .unwrap_or(&[])
+async fn execute(motor: &mut Motor, step: Move) -> Transform {
+    match step {
+        Move::Forward => {
+            motor.step_fw().await;
+            Transform::from(Vec2::new_front(1))
+        }
+        Move::Backward => {
+            motor.step_bw().await;
+            Transform::from(Vec2::new_back(1))
+        }
+        Move::TurnLeft => {
+            motor.turn_left().await;
+            Transform::from(Rotation::Left)
+        }
+        Move::TurnRight => {
+            motor.turn_right().await;
+            Transform::from(Rotation::Right)
+        }
     }
 }
 
@@ -629,61 +355,60 @@ async fn foreground(
     mut bot: Bot,
     sync: DataSync,
     signal_complete: &'static Signal<NoopRawMutex, ()>,
+    mut log: &'static Log,
 ) -> ! {
-    // settings
-    const MAX_N_BOTS: usize = 28;
-
-    let radar = &mut bot.radar;
-    let arm = &mut bot.arm;
-    let motor = &mut bot.motor;
+    // settings: a bot costs as much as ten steps on its tile, falling off over two tiles, and is
+    // forgotten after three seconds
+    const DANGER_COST: u16 = 40;
+    const DANGER_RADIUS: u16 = 2;
+    const DANGER_SECS: u32 = 3;
 
     let destination = pos_east_south(14, 36);
     sync.signal_destination.signal(destination);
     // println!("-> dest {}", destination);
-    println!("destination: {}", destination);
+    _ = writeln!(log, "destination: {}", destination);
 
-    let mut nav_state = BotNavState::new(bot.compass.try_direction().unwrap());
+    let mut nav_state = BotNavState::new(bot.compass.try_direction().unwrap(), log);
+    let mut danger = Danger::new(DANGER_COST, DANGER_RADIUS, Duration::from_secs(DANGER_SECS));
+    let mut local_nav: Box<LocalNav> = Default::default();
 
-    'main_loop: loop {
-        let radar_scan = &radar.scan::<D7>().await;
+    loop {
+        let radar_scan = &bot.radar.scan::<D7>().await;
         let radar_timestamp = Instant::now();
 
-        let action = instincts(arm, motor, radar_scan, radar_timestamp);
-        let (transform, execution_result) =
-            execute_with_arm_timeout(radar, motor, arm, &action).await;
-
         nav_state.analyse_scan(radar_scan);
-        nav_state.update_and_sync(transform, &sync).await;
+        nav_state.sync_navigation(&sync);
+        let (pos, facing) = nav_state.local_pose();
+        danger.observe(radar_scan, pos, facing, radar_timestamp);
 
-        if nav_state.global_pos == Some(destination) {
-            println!("-- done --");
+        if nav_state.pose.global_position() == Some(destination) {
+            _ = writeln!(log, "-- done --");
             signal_complete.signal(());
             Timer::after_secs(2).await;
             exit();
         }
 
-        if execution_result == ExecutionResult::RadarReady {
-            // new information is available and should be used
-            continue 'main_loop;
-        } else if action.motor.is_some() {
-            // keep radar and motor in sync by not performing any more movements
-            continue 'main_loop;
+        let attack = instincts(&bot.arm, &bot.motor, radar_scan, radar_timestamp);
+        let transform = if let Some(attack) = attack {
+            // fighting a bot next to us goes before navigation
+            execute_attack(&mut bot.radar, &mut bot.motor, &mut bot.arm, &attack).await
         } else {
-            let bots = get_bot_list::<MAX_N_BOTS, _>(transform, action.arm.is_some(), radar_scan);
-
-            nav_state.sync_navigation(&sync);
-            let action = movement(
-                &nav_state,
-                transform,
-                radar_scan,
-                &bots,
-                arm.is_ready(),
-                radar_timestamp,
-            );
-            let (transform, _result) = execute_with_arm_timeout(radar, motor, arm, &action).await;
+            let step = match nav_state.pose.global_position() {
+                Some(_) => {
+                    nav_state
+                        .next_move(&mut local_nav, |pos| danger.cost_at(pos, radar_timestamp))
+                        .await
+                }
+                None => Some(explore(radar_scan)),
+            };
+            match step {
+                Some(step) if is_free(radar_scan, step) => execute(&mut bot.motor, step).await,
+                // wait for the next scan
+                _ => Transform::identity(),
+            }
+        };
 
-            nav_state.update_and_sync(transform, &sync).await;
-        }
+        nav_state.update_and_sync(transform, &sync).await;
     }
 }
 
@@ -859,7 +584,7 @@ async fn navigation(sync: DataSync) -> ! {
 }
 
 #[task]
-async fn watchdog(signal_complete: &'static Signal<NoopRawMutex, ()>) -> ! {
+async fn watchdog(signal_complete: &'static Signal<NoopRawMutex, ()>, mut log: &'static Log) -> ! {
     let mut dog = StatsDog::<KartoffelClock>::new();
     loop {
         dog.restart_timer();
@@ -871,7 +596,23 @@ async fn watchdog(signal_complete: &'static Signal<NoopRawMutex, ()>) -> ! {
         // }
 
         if signal_complete.try_take().is_some() {
-            println!("{}", dog);
+            send_telemetry(log, Record::from(&dog));
+            let dropped = log.dropped();
+            _ = writeln!(log, "log dropped {}", dropped);
         }
     }
 }
+
+/// text for the serial port, written by the other tasks without blocking them
+type Log = SerialWriter<1024>;
+
+/// Moves the log to the serial port, a few bytes per poll. It shares the executor with navigation
+/// and is polled as often, the chunk limits how long it blocks the others.
+#[task]
+async fn serial(log: &'static Log) -> ! {
+    log.run(Serial, 8).await
+}
+
+fn to_position(pos: GlobalPos) -> Position {
+    Position::add_to_anchor(pos.subtract_anchor())
+}
@@ -28,6 +28,7 @@ fn main() {
     println!("terrain back against wall");
 
     executor.run(|spawner| {
+        spawner.spawn(example_kartoffels::reactor()).unwrap();
         spawner.spawn(foreground(Bot::take())).unwrap();
     })
 }
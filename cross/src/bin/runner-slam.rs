@@ -8,25 +8,25 @@ use alloc::boxed::Box;
 
 use alloc::string::ToString;
 use async_algorithm::{
-    Breakpoint, ChunkMapHash, ChunkTerrain, DistanceBotWalk, DistanceMeasure, Exploration, Map,
-    Navigation, StatsDog, Terrain, distance_walk_with_rotation, update_chunk_map,
+    Breakpoint, ChunkLocation, ChunkMapHash, ChunkTerrain, DistanceBotWalk, DistanceMeasure,
+    Estimate, Exploration, IncrementalNavigation, Map, StatsDog, Terrain,
+    distance_walk_with_rotation, telemetry::Record, update_chunk_map_reporting,
 };
 use async_kartoffel::Duration;
 use async_kartoffel::{
-    Arm, Bot, Instant, KartoffelClock, Motor, Radar, RadarScan, RadarScanWeak, Timer, print,
-    println,
+    Arm, Bot, Instant, KartoffelClock, Motor, Radar, RadarScan, RadarScanWeak, Serial, Timer,
+    print, println, send_telemetry,
 };
 use async_kartoffel_generic::{
     D3, Direction, Local, Position, RadarScanTrait, RadarSize, Rotation, Tile, Transform, Vec2,
 };
-use core::num::NonZeroU16;
 use core::ops::Deref;
 use core::ops::DerefMut;
 use core::ops::RangeInclusive;
 use embassy_executor::{Executor, task};
 use embassy_futures::select::{Either, Either3, select, select3};
 use embassy_sync::{blocking_mutex::raw::NoopRawMutex, signal::Signal};
-use heapless::Vec;
+use heapless::{FnvIndexSet, Vec};
 use static_cell::StaticCell;
 
 extern crate alloc;
@@ -70,6 +70,7 @@ fn main() {
     println!("explorer");
 
     executor.run(|spawner| {
+        spawner.spawn(example_kartoffels::reactor()).unwrap();
         spawner
             .spawn(foreground(bot, signal_map, signal_navigation))
             .unwrap();
@@ -463,12 +464,35 @@ async fn background(
             if let Some(radar_scan) = scan.upgrade() {
                 {
                     let t = DropTimer::new("tmap");
-                    if let Err(err) =
-                        update_chunk_map(map.deref_mut(), &radar_scan, scan_pos, direction).await
+                    // at most the 81 tiles of a D9 scan
+                    let mut changed = Vec::<Position, 81>::new();
+                    if let Err(err) = update_chunk_map_reporting(
+                        map.deref_mut(),
+                        &radar_scan,
+                        scan_pos,
+                        direction,
+                        |pos| _ = changed.push(pos),
+                    )
+                    .await
                     {
                         println!("error in map {:?}", err);
                     }
                     drop(t);
+                    // stream the changed chunks, a D9 scan touches at most 4 of them
+                    let mut chunks = FnvIndexSet::<ChunkLocation, 4>::new();
+                    for &pos in &changed {
+                        _ = chunks.insert(ChunkLocation::containing(pos));
+                    }
+                    for &location in &chunks {
+                        if let Some(&terrain) = map.chunk(location) {
+                            send_telemetry(Serial, Record::Chunk { location, terrain });
+                        }
+                    }
+                    // repair the navigation instead of starting over, no error without a
+                    // destination
+                    for pos in changed {
+                        _ = nav.update_tile(pos, can_go(&map));
+                    }
                 }
                 Breakpoint::new().await;
                 {
@@ -553,8 +577,7 @@ async fn background(
 
         // navigation
         println!("nr");
-        nav.run(|pos| map.get(pos).is_some_and(|t| t.is_known_walkable()))
-            .await;
+        nav.run(can_go(&map)).await;
 
         Breakpoint::new().await;
         println!("ns");
@@ -597,6 +620,10 @@ async fn background(
     }
 }
 
+fn can_go(map: &MyMap) -> impl Fn(Position) -> bool + '_ {
+    |pos| map.get(pos).is_some_and(|t| t.is_known_walkable())
+}
+
 fn print_map(
     map: &impl Map<Terrain>,
     pos: Position,
@@ -623,7 +650,7 @@ fn print_map(
 }
 
 type MyMap = ChunkMapHash<128, Terrain, ChunkTerrain>;
-type MyNav = Navigation<ChunkMapHash<64, Option<NonZeroU16>, [[Option<NonZeroU16>; 8]; 8]>, 64>;
+type MyNav = IncrementalNavigation<ChunkMapHash<64, Estimate, [[Estimate; 8]; 8]>, 64>;
 type MyExp = Exploration<256, MyMap>;
 
 #[task]
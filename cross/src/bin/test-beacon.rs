@@ -9,7 +9,7 @@ use async_kartoffel_generic::{
     D3, D7 as DRadar, Direction, RadarScanTrait, RadarSize, Rotation, Vec2,
 };
 use embassy_executor::{Executor, task};
-use example_kartoffels::{beacon_info, get_global_pos, get_navigator_info, navigator_resources};
+use example_kartoffels::{beacon_info, get_navigator_info, gps, navigator_resources};
 use kartoffel_gps::{
     GlobalPos,
     beacon::Navigator,
@@ -25,6 +25,7 @@ fn main() {
     let executor = EXECUTOR.init(Executor::new());
 
     executor.run(|spawner| {
+        spawner.spawn(example_kartoffels::reactor()).unwrap();
         spawner.spawn(main_task(Bot::take())).unwrap();
     })
 }
@@ -85,7 +86,7 @@ async fn main_task(mut bot: Bot) -> ! {
             {
                 // position update if out of sync
                 let scan = bot.radar.scan::<DRadar>().await;
-                if let Some(new_pos) = get_global_pos(&MapSection::from_scan(&scan, facing))
+                if let Some(new_pos) = gps().get_global_pos(&MapSection::from_scan(&scan, facing))
                     && new_pos != pos
                 {
                     println!("correction pos: {} -> {}", pos, new_pos);
@@ -224,7 +225,7 @@ async fn random_walk(bot: &mut Bot, facing: &mut Direction, rng: &mut SmallRng)
     loop {
         let scan = bot.radar.scan::<DRadar>().await;
 
-        if let Some(pos) = get_global_pos(&MapSection::from_scan(&scan, *facing)) {
+        if let Some(pos) = gps().get_global_pos(&MapSection::from_scan(&scan, *facing)) {
             return pos;
         }
 
@@ -7,7 +7,7 @@
 use async_kartoffel::{Bot, Instant, println};
 use async_kartoffel_generic::{D7 as DRadar, RadarScanTrait, Rotation, Vec2};
 use embassy_executor::{Executor, task};
-use example_kartoffels::{get_global_pos, global_pos_entries};
+use example_kartoffels::{MAPS, MapDetector, gps};
 use kartoffel_gps::gps::{MapSection, MapSectionTrait};
 use static_cell::StaticCell;
 
@@ -17,6 +17,7 @@ fn main() {
     let executor = EXECUTOR.init(Executor::new());
 
     executor.run(|spawner| {
+        spawner.spawn(example_kartoffels::reactor()).unwrap();
         spawner.spawn(main_task(Bot::take())).unwrap();
     })
 }
@@ -27,15 +28,21 @@ async fn main_task(mut bot: Bot) -> ! {
 
     println!(
         "number of uniquely identifyable positions: {:?}",
-        global_pos_entries().count()
+        gps().n_unique()
     );
 
+    let mut detector = MapDetector::new(&MAPS);
     loop {
         let scan = bot.radar.scan::<DRadar>().await;
 
+        match detector.update(&scan) {
+            Some(map) => println!("map: {}", map.name),
+            None => println!("map candidates: {}", detector.possible().count()),
+        }
+
         let chunk = MapSection::from_scan(&scan, facing);
         let t = Instant::now();
-        let pos = get_global_pos(&chunk);
+        let pos = gps().get_global_pos(&chunk);
         let dur = (Instant::now() - t).unwrap();
         if let Some(gpos) = pos {
             println!("global pos: ({})", gpos);
@@ -44,6 +51,16 @@ async fn main_task(mut bot: Bot) -> ! {
         }
         println!("{}", dur);
 
+        let t = Instant::now();
+        let pose = gps().get_global_pose(&scan);
+        let dur = (Instant::now() - t).unwrap();
+        if let Some((gpos, gfacing)) = pose {
+            println!("global pose without compass: ({}) {:?}", gpos, gfacing);
+        } else {
+            println!("global pose without compass unknown");
+        }
+        println!("{}", dur);
+
         if scan.at(Vec2::new_front(1)).unwrap().is_walkable_terrain() {
             bot.motor.step_fw().await;
         } else if scan.at(Vec2::new_right(1)).unwrap().is_walkable_terrain() {